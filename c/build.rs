@@ -152,10 +152,12 @@ fn main() {
 		.clang_arg("-DBW_BINDGEN")
 		.header("src/application.h")
 		.header("src/browser_window.h")
+		.header("src/certificate.h")
 		.header("src/cookie.h")
 		.header("src/common.h")
 		.header("src/err.h")
 		.header("src/event.h")
+		.header("src/scheme.h")
 		.header("src/string.h")
 		.header("src/window.h");
 
@@ -282,10 +284,14 @@ fn main() {
 			.file("src/application/cef.cpp")
 			.file("src/application/cef_window.cpp")
 			.file("src/browser_window/cef.cpp")
+			.file("src/certificate/cef.cpp")
 			.file("src/cookie/cef.cpp")
 			.file("src/cef/bw_handle_map.cpp")
 			.file("src/cef/client_handler.cpp")
+			.file("src/cef/download_client.cpp")
 			.file("src/cef/exception.cpp")
+			.file("src/cef/resource_request_handler.cpp")
+			.file("src/cef/scheme_handler.cpp")
 			.file("src/cef/util.cpp")
 			.file("src/window/cef.cpp")
 			.define("BW_CEF", None)
@@ -371,7 +377,9 @@ fn main() {
 			.file("src/application/win32.c")
 			.file("src/browser_window/edge2.c")
 			.file("src/window/win32.c")
+			.file("src/certificate/unsupported.c")
 			.file("src/cookie/unsupported.c")
+			.file("src/scheme/unsupported.c")
 			.file("src/win32.c");
 	}
 	else {
@@ -6,6 +6,7 @@ extern "C" {
 #endif
 
 #include "bool.h"
+#include "scheme.h"
 #include "string.h"
 
 
@@ -58,6 +59,7 @@ struct bw_Application {
 	BOOL is_done;
 	bw_ApplicationImpl impl;
 	bw_ApplicationEngineImpl engine_impl;	/// Can be set by the implementation of a browser engine
+	void* user_data;	/// Belongs to the Rust side
 };
 
 typedef struct bw_Application bw_Application;
@@ -72,6 +74,11 @@ typedef struct {
 	bw_CStrSlice engine_seperate_executable_path;
 	uint16_t remote_debugging_port;
 	bw_CStrSlice resource_dir;
+	const bw_Scheme* schemes;
+	size_t scheme_count;
+	/// The script that defines `invoke_extern` on every page, and needs to live as long as the application does.
+	/// In it, `POST_MESSAGE` has already been replaced by the function that passes the message on to the application.
+	bw_CStrSlice invoke_extern_js;
 } bw_ApplicationSettings;
 
 
@@ -2,6 +2,7 @@
 #include "../debug.h"
 #include "../cef/app_handler.hpp"
 #include "../cef/client_handler.hpp"
+#include "../cef/scheme_handler.hpp"
 
 #include "impl.h"
 
@@ -59,7 +60,7 @@ bw_Err bw_ApplicationEngineImpl_initialize( bw_ApplicationEngineImpl* impl, bw_A
 	if (settings->remote_debugging_port != 0) {
 		app_settings.remote_debugging_port = settings->remote_debugging_port;
 	}
-	CefRefPtr<CefApp> cef_app_handle( new AppHandler( app ) );
+	CefRefPtr<CefApp> cef_app_handle( new AppHandler( app, settings ) );
 
 	if (settings->engine_seperate_executable_path.len == 0) {
 		int exit_code = CefExecuteProcess( main_args, cef_app_handle.get(), 0 );
@@ -91,11 +92,13 @@ bw_Err bw_ApplicationEngineImpl_initialize( bw_ApplicationEngineImpl* impl, bw_A
 	}
 
 	CefInitialize( main_args, app_settings, cef_app_handle.get(), 0 );
+	bw::registerSchemeHandlers( app, settings );
 
 	CefRefPtr<CefClient>* client = new CefRefPtr<CefClient>(new ClientHandler( app ));
 
 	impl->exit_code = 0;
 	impl->cef_client = (void*)client;
+	impl->invoke_extern_js = settings->invoke_extern_js;
 
 	BW_ERR_RETURN_SUCCESS;
 }
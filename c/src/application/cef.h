@@ -5,11 +5,14 @@
 extern "C" {
 #endif
 
+#include "../string.h"
+
 
 
 typedef struct {
 	void* cef_client;
 	int exit_code;
+	bw_CStrSlice invoke_extern_js;
 } bw_ApplicationEngineImpl;
 
 
@@ -57,6 +57,7 @@ bw_Err bw_Application_initialize( bw_Application** app, int argc, char** argv, c
 	(*app)->windows_alive = 0;
 	(*app)->is_running = FALSE;
 	(*app)->is_done = FALSE;
+	(*app)->user_data = NULL;
 
 	bw_Err error = bw_ApplicationEngineImpl_initialize( &(*app)->engine_impl, (*app), argc, argv, settings );
 	if (BW_ERR_IS_FAIL(error))	return error;
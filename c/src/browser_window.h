@@ -16,6 +16,7 @@ typedef struct {} bw_BrowserWindowImpl;
 #include "application.h"
 #include "event.h"
 #include "err.h"
+#include "scheme.h"
 #include "string.h"
 #include "window.h"
 
@@ -24,34 +25,203 @@ typedef struct {} bw_BrowserWindowImpl;
 
 
 typedef struct bw_BrowserWindow bw_BrowserWindow;
+/// Answers a request for credentials, see `bw_BrowserWindowAuthRequest`.
+typedef struct bw_AuthCallback bw_AuthCallback;
+/// Decides whether to trust a certificate, see `bw_BrowserWindowCertificateError`.
+typedef struct bw_CertificateCallback bw_CertificateCallback;
+/// Selects a client certificate, see `bw_BrowserWindowClientCertificateRequest`.
+typedef struct bw_ClientCertificateCallback bw_ClientCertificateCallback;
+/// Decides where a download is saved, see `bw_BrowserWindowDownload`.
+typedef struct bw_DownloadCallback bw_DownloadCallback;
+/// Chooses the files of a file dialog, see `bw_BrowserWindowFileDialog`.
+typedef struct bw_FileDialogCallback bw_FileDialogCallback;
 
 
 typedef void (*bw_BrowserWindowCreationCallbackFn)( bw_BrowserWindow* window, void* data );
 typedef void (*bw_BrowserWindowHandlerFn)( bw_BrowserWindow* window, bw_CStrSlice cmd, bw_CStrSlice* args, size_t arg_count );
 typedef void (*bw_BrowserWindowJsCallbackFn)( bw_BrowserWindow* window, void* user_data, const char* result, const bw_Err* err );
+/// Gets invoked for an entry of the back/forward list. The strings are only valid during the call.
+typedef void (*bw_BrowserWindowHistoryFn)( void* data, unsigned int index, bw_StrSlice url, bw_StrSlice title, BOOL current );
 
 
 typedef struct {
 	bw_Event on_address_changed;
+	bw_Event on_auth_credentials;	// Takes a `bw_BrowserWindowAuthRequest`, fired on the GUI thread.
+	bw_Event on_certificate_error;	// Takes a `bw_BrowserWindowCertificateError`, fired on the GUI thread.
 	bw_Event on_console_message;
+	bw_Event on_download_finished;	// Takes a `bw_BrowserWindowDownloadFinished`.
+	bw_Event on_download_progress;	// Takes a `bw_BrowserWindowDownloadProgress`.
+	bw_Event on_download_started;	// Takes a `bw_BrowserWindowDownload`.
 	bw_Event on_favicon_changed;
+	bw_Event on_file_dialog;	// Takes a `bw_BrowserWindowFileDialog`, returns TRUE if handled.
 	bw_Event on_fullscreen_mode_changed;
+	bw_Event on_key_press;	// Takes a `bw_BrowserWindowKeyEvent`, returns TRUE to keep the key from the page.
+	bw_Event on_key_pressed;	// Takes a `bw_BrowserWindowKeyEvent` of a key that the page didn't handle, returns TRUE if handled.
 	bw_Event on_loading_progress_changed;
 	bw_Event on_message;
+	bw_Event on_navigation_request;	// Takes a `bw_BrowserWindowNavigationRequest`, returns TRUE to cancel the navigation.
 	bw_Event on_navigation_start;
 	bw_Event on_navigation_end;
 	bw_Event on_page_title_changed;
+	bw_Event on_request;	// Takes a `bw_BrowserWindowRequest`, fired on the GUI thread.
+	bw_Event on_response;	// Takes a `bw_BrowserWindowResponse`, fired on the GUI thread.
+	bw_Event on_select_client_certificate;	// Takes a `bw_BrowserWindowClientCertificateRequest`, fired on the GUI thread.
 	bw_Event on_status_message;
 	bw_Event on_tooltip;
 } bw_BrowserWindowEvents;
 
 /// `cmd` is always a string. The arguments are JS values in the form of a string.
+/// `id` identifies the promise that `invoke_extern` returned.
 typedef struct {
+	unsigned int id;
 	bw_CStrSlice cmd;
 	size_t arg_count;
 	bw_CStrSlice* args;
 } bw_BrowserWindowMessageArgs;
 
+#define BW_NAVIGATION_TYPE_OTHER 0
+#define BW_NAVIGATION_TYPE_LINK 1
+#define BW_NAVIGATION_TYPE_FORM_SUBMISSION 2
+#define BW_NAVIGATION_TYPE_BACK_FORWARD 3
+#define BW_NAVIGATION_TYPE_RELOAD 4
+
+/// A navigation that is about to happen.
+typedef struct {
+	bw_CStrSlice url;
+	BOOL is_main_frame;
+	BOOL user_gesture;	// Whether or not the navigation was initiated by the user
+	unsigned int type;	// One of the `BW_NAVIGATION_TYPE_*` values
+} bw_BrowserWindowNavigationRequest;
+
+#define BW_KEY_MODIFIER_CTRL 1
+#define BW_KEY_MODIFIER_SHIFT 2
+#define BW_KEY_MODIFIER_ALT 4
+#define BW_KEY_MODIFIER_META 8
+
+/// A key that has been pressed.
+typedef struct {
+	unsigned int key_code;	// The Windows virtual-key code
+	unsigned int modifiers;	// A combination of the `BW_KEY_MODIFIER_*` flags
+	BOOL is_repeat;	// Whether the key is being held down
+	uint32_t character;	// The unicode character that the key produces, or 0
+} bw_BrowserWindowKeyEvent;
+
+/// A server or proxy that asks for a username and password.
+typedef struct {
+	bw_CStrSlice host;
+	uint16_t port;
+	bw_CStrSlice realm;
+	bw_CStrSlice scheme;	// E.g. "basic" or "digest"
+	BOOL is_proxy;
+	BOOL is_retry;	// Whether credentials have already been given for the same request, and have been rejected
+	/// Has to be given to either `bw_AuthCallback_continue` or `bw_AuthCallback_cancel` at some point, which frees it.
+	bw_AuthCallback* callback;
+} bw_BrowserWindowAuthRequest;
+
+#define BW_CERTIFICATE_ERROR_OTHER 0
+#define BW_CERTIFICATE_ERROR_HOST_MISMATCH 1
+#define BW_CERTIFICATE_ERROR_INVALID_DATE 2
+#define BW_CERTIFICATE_ERROR_UNKNOWN_AUTHORITY 3
+#define BW_CERTIFICATE_ERROR_REVOKED 4
+#define BW_CERTIFICATE_ERROR_INVALID 5
+
+typedef struct {
+	bw_CStrSlice subject;
+	bw_CStrSlice issuer;
+	int64_t valid_from;	// Unix timestamp, or 0 if unknown
+	int64_t valid_until;	// Unix timestamp, or 0 if unknown
+	const uint8_t* der;
+	size_t der_len;
+} bw_Certificate;
+
+/// A certificate that isn't trusted.
+/// All data except for the callback is only valid while the `on_certificate_error` event is being fired.
+typedef struct {
+	bw_CStrSlice url;
+	unsigned int error;	// One of the `BW_CERTIFICATE_ERROR_*` values
+	bw_Certificate certificate;
+	/// Has to be given to either `bw_CertificateCallback_allow` or `bw_CertificateCallback_deny` at some point, which frees it.
+	bw_CertificateCallback* callback;
+} bw_BrowserWindowCertificateError;
+
+/// A server or proxy that asks for a certificate to identify the client with.
+/// All data except for the callback is only valid while the `on_select_client_certificate` event is being fired.
+typedef struct {
+	bw_CStrSlice host;
+	uint16_t port;
+	BOOL is_proxy;
+	const bw_Certificate* candidates;
+	size_t candidate_count;
+	/// Has to be given to either `bw_ClientCertificateCallback_select` or `bw_ClientCertificateCallback_cancel` at some point, which frees it.
+	bw_ClientCertificateCallback* callback;
+} bw_BrowserWindowClientCertificateRequest;
+
+/// A download that is about to start.
+/// All data except for the callback is only valid while the `on_download_started` event is being fired.
+typedef struct {
+	unsigned int id;
+	bw_CStrSlice url;
+	bw_CStrSlice suggested_name;
+	bw_CStrSlice mime_type;
+	int64_t total_size;	// -1 if unknown
+	/// Has to be given to either `bw_DownloadCallback_continue` or `bw_DownloadCallback_cancel` at some point, which frees it.
+	bw_DownloadCallback* callback;
+} bw_BrowserWindowDownload;
+
+typedef struct {
+	unsigned int id;
+	unsigned int request;	// The request given to `bw_BrowserWindow_download` if it has started the download, 0 otherwise
+	bw_CStrSlice url;
+	int64_t received;
+	int64_t total_size;	// -1 if unknown
+} bw_BrowserWindowDownloadProgress;
+
+#define BW_DOWNLOAD_RESULT_SUCCESS 0
+#define BW_DOWNLOAD_RESULT_CANCELLED 1
+#define BW_DOWNLOAD_RESULT_FAILED 2
+
+typedef struct {
+	unsigned int id;
+	unsigned int request;	// The request given to `bw_BrowserWindow_download` if it has started the download, 0 otherwise
+	bw_CStrSlice url;
+	bw_CStrSlice path;
+	unsigned int result;	// One of the `BW_DOWNLOAD_RESULT_*` values
+	bw_CStrSlice error;	// Describes why the download has failed, if it has
+} bw_BrowserWindowDownloadFinished;
+
+/// A file dialog that the page wants to show, for an `<input type="file">` element for example.
+/// All data except for the callback is only valid while the `on_file_dialog` event is being fired.
+typedef struct {
+	unsigned int mode;	// One of the `BW_FILE_DIALOG_*` values
+	bw_CStrSlice title;
+	bw_CStrSlice default_path;	// Empty if there is none
+	const bw_CStrSlice* accept;	// MIME types like `image/png`, or extensions like `.png`
+	size_t accept_count;
+	/// If the event returns TRUE, it has to be given to either `bw_FileDialogCallback_continue` or `bw_FileDialogCallback_cancel` at some point, which frees it.
+	/// Otherwise, it is freed for you.
+	bw_FileDialogCallback* callback;
+} bw_BrowserWindowFileDialog;
+
+/// A request that the page is about to send.
+/// All data is only valid while the `on_request` event is being fired.
+typedef struct {
+	bw_CStrSlice url;
+	bw_CStrSlice method;
+	const bw_HttpHeader* headers;
+	size_t header_count;
+	void* impl;
+} bw_BrowserWindowRequest;
+
+/// The response to a request that has completed.
+/// All data is only valid while the `on_response` event is being fired.
+typedef struct {
+	bw_CStrSlice url;
+	bw_CStrSlice method;
+	uint16_t status;
+	const bw_HttpHeader* headers;
+	size_t header_count;
+} bw_BrowserWindowResponse;
+
 typedef struct bw_BrowserWindowOptions {
 	BOOL dev_tools;
 	bw_CStrSlice resource_path;
@@ -71,6 +241,46 @@ struct bw_BrowserWindow {
 };
 
 
+/// Cancels the request for credentials, and frees the callback.
+void bw_AuthCallback_cancel( bw_AuthCallback* callback );
+/// Authenticates with the given credentials, and frees the callback.
+void bw_AuthCallback_continue( bw_AuthCallback* callback, bw_CStrSlice username, bw_CStrSlice password );
+
+/// Trusts the certificate anyway, and frees the callback.
+void bw_CertificateCallback_allow( bw_CertificateCallback* callback );
+/// Doesn't trust the certificate, and frees the callback.
+void bw_CertificateCallback_deny( bw_CertificateCallback* callback );
+
+/// Doesn't send any client certificate, and frees the callback.
+void bw_ClientCertificateCallback_cancel( bw_ClientCertificateCallback* callback );
+/// Sends the candidate at `index` as the client certificate, and frees the callback.
+void bw_ClientCertificateCallback_select( bw_ClientCertificateCallback* callback, size_t index );
+
+/// Cancels the download, and frees the callback.
+void bw_DownloadCallback_cancel( bw_DownloadCallback* callback );
+/// Saves the download at `path`, or in the user's download directory if `path` is empty, and frees the callback.
+void bw_DownloadCallback_continue( bw_DownloadCallback* callback, bw_CStrSlice path );
+
+/// Cancels the file dialog, and frees the callback.
+void bw_FileDialogCallback_cancel( bw_FileDialogCallback* callback );
+/// Chooses the given paths, and frees the callback.
+void bw_FileDialogCallback_continue( bw_FileDialogCallback* callback, const bw_CStrSlice* paths, size_t path_count );
+
+/// Defines the global JavaScript function `name`, which calls `invoke_extern` with `name` as its command.
+/// The function is also defined for every page that gets loaded afterwards.
+void bw_BrowserWindow_bind( bw_BrowserWindow* bw, bw_CStrSlice name );
+
+/// Whether or not there is a page to go back to in the history.
+BOOL bw_BrowserWindow_canGoBack( bw_BrowserWindow* bw );
+/// Whether or not there is a page to go forward to in the history.
+BOOL bw_BrowserWindow_canGoForward( bw_BrowserWindow* bw );
+
+/// Downloads the file at `url` to `path`, or to the user's download directory if `path` is empty.
+/// The download doesn't fire `on_download_started`, but fires `on_download_progress` and `on_download_finished` with the given `request`.
+/// `on_download_finished` is fired for it as well if it fails before it has started.
+/// `request` should not be 0.
+void bw_BrowserWindow_download( bw_BrowserWindow* bw, bw_CStrSlice url, bw_CStrSlice path, unsigned int request );
+
 /// Executes the given JavaScript and calls the given callback (on the GUI thread) to provide the result.
 void bw_BrowserWindow_evalJs( bw_BrowserWindow* bw, bw_CStrSlice js, bw_BrowserWindowJsCallbackFn callback, void* cb_data );
 void bw_BrowserWindow_evalJsThreaded( bw_BrowserWindow* bw, bw_CStrSlice js, bw_BrowserWindowJsCallbackFn callback, void* cb_data );
@@ -81,9 +291,38 @@ bw_Application* bw_BrowserWindow_getApp( bw_BrowserWindow* bw );
 void* bw_BrowserWindow_getUserData( bw_BrowserWindow* bw );
 BOOL bw_BrowserWindow_getUrl(bw_BrowserWindow* bw, bw_StrSlice* url);
 bw_Window* bw_BrowserWindow_getWindow( bw_BrowserWindow* bw );
+/// Invokes `callback` for every entry of the back/forward list, from the oldest to the newest.
+void bw_BrowserWindow_getHistory( bw_BrowserWindow* bw, bw_BrowserWindowHistoryFn callback, void* data );
+
+void bw_BrowserWindow_goBack( bw_BrowserWindow* bw );
+void bw_BrowserWindow_goForward( bw_BrowserWindow* bw );
+
+/// Starts passing the requests and responses of the page on to the GUI thread, where `on_request` and `on_response` are fired for them.
+/// Should be called once either of them has been given a callback.
+void bw_BrowserWindow_listenForRequests( bw_BrowserWindow* bw );
 
 bw_Err bw_BrowserWindow_navigate( bw_BrowserWindow* bw, bw_CStrSlice url );
 
+/// Reloads the current page. If `ignore_cache` is set, nothing is taken from the cache.
+void bw_BrowserWindow_reload( bw_BrowserWindow* bw, BOOL ignore_cache );
+/// Stops loading the current page.
+void bw_BrowserWindow_stop( bw_BrowserWindow* bw );
+
+/// Cancels the request.
+void bw_BrowserWindowRequest_block( bw_BrowserWindowRequest* request );
+/// Sends the request to the given URL instead.
+void bw_BrowserWindowRequest_redirect( bw_BrowserWindowRequest* request, bw_CStrSlice url );
+/// Removes all headers with the given name from the request.
+void bw_BrowserWindowRequest_removeHeader( bw_BrowserWindowRequest* request, bw_CStrSlice name );
+/// Answers the request with the given response, instead of sending it.
+/// The response data is copied, so it doesn't need to outlive this call.
+void bw_BrowserWindowRequest_respond( bw_BrowserWindowRequest* request, const bw_SchemeResponse* response );
+/// Sets a header of the request, replacing any existing header with the same name.
+void bw_BrowserWindowRequest_setHeader( bw_BrowserWindowRequest* request, bw_CStrSlice name, bw_CStrSlice value );
+
+/// Removes the global JavaScript function that has been defined by `bw_BrowserWindow_bind`.
+void bw_BrowserWindow_unbind( bw_BrowserWindow* bw, bw_CStrSlice name );
+
 /// Allocates a browser window and creates the window for it.
 /// Call `bw_BrowserWindow_create` on it to add the actual browser framework to this window.
 bw_BrowserWindow* bw_BrowserWindow_new(
@@ -3,7 +3,9 @@
 #include "../application/cef.h"
 #include "../browser_window.h"
 #include "../cef/bw_handle_map.hpp"
+#include "../cef/download_client.hpp"
 #include "../cef/exception.hpp"
+#include "../cef/external_invocation_handler.hpp"
 #include "../cef/util.hpp"
 #include "../common.h"
 #include "../debug.h"
@@ -13,6 +15,8 @@
 #include <include/base/cef_bind.h>
 #include <include/cef_browser.h>
 #include <include/cef_client.h>
+#include <include/cef_navigation_entry.h>
+#include <include/cef_parser.h>
 #include <include/cef_v8.h>
 #include <include/views/cef_browser_view.h>
 #include <include/views/cef_window.h>
@@ -58,6 +62,81 @@ CefWindowInfo _bw_BrowserWindow_windowInfo( bw_Window* window, int width, int he
 
 
 
+class BwHistoryVisitor : public CefNavigationEntryVisitor {
+public:
+	bw_BrowserWindowHistoryFn callback;
+	void* data;
+
+	BwHistoryVisitor(bw_BrowserWindowHistoryFn callback, void* data) : callback(callback), data(data) {}
+
+	bool Visit(CefRefPtr<CefNavigationEntry> entry, bool current, int index, int total) override {
+		UNUSED(total);
+
+		bw_StrSlice url = bw_cef_copyToStrSlice(entry->GetURL());
+		bw_StrSlice title = bw_cef_copyToStrSlice(entry->GetTitle());
+		this->callback(this->data, (unsigned int)index, url, title, current);
+		bw_string_free(url);
+		bw_string_free(title);
+
+		return true;
+	}
+
+protected:
+	IMPLEMENT_REFCOUNTING(BwHistoryVisitor);
+};
+
+
+
+void bw_BrowserWindow_bind( bw_BrowserWindow* bw, bw_CStrSlice name ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	std::string _name( name.data, name.len );
+
+	// The name is remembered so that it can be passed on to new render processes as well.
+	std::optional<bw::BrowserInfo*> bw_info = bw::bw_handle_map.fetch( cef_browser );
+	BW_ASSERT( bw_info.has_value(), "Link between CEF's browser handle and our handle does not exist!\n" );
+	bw_info.value()->bindings.insert( _name );
+
+	bw::sendBindingMessage( cef_browser->GetMainFrame(), "bind", _name );
+}
+
+BOOL bw_BrowserWindow_canGoBack( bw_BrowserWindow* bw ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	return cef_browser->CanGoBack();
+}
+
+BOOL bw_BrowserWindow_canGoForward( bw_BrowserWindow* bw ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	return cef_browser->CanGoForward();
+}
+
+void bw_BrowserWindow_download( bw_BrowserWindow* bw, bw_CStrSlice url, bw_CStrSlice path, unsigned int request ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	std::string _url( url.data, url.len );
+	std::string _path( path.data, path.len );
+
+	// Without a path, the file is named after the last part of the URL's path
+	if (_path.empty()) {
+		std::string name;
+		CefURLParts parts;
+		if (CefParseURL( _url, parts )) {
+			std::string url_path = CefString( &parts.path ).ToString();
+			name = url_path.substr( url_path.find_last_of( '/' ) + 1 );
+		}
+		_path = bw::defaultDownloadPath( cef_browser, name.empty() ? "download" : name );
+	}
+
+	CefRefPtr<CefRequest> cef_request = CefRequest::Create();
+	cef_request->SetURL( _url );
+	cef_request->SetMethod( "GET" );
+	// Cookies are sent along, like with the downloads that the page starts itself
+	cef_request->SetFlags( UR_FLAG_ALLOW_STORED_CREDENTIALS );
+	CefURLRequest::Create(
+		cef_request,
+		new bw::DownloadClient( cef_browser, request, _url, _path ),
+		cef_browser->GetHost()->GetRequestContext()
+	);
+}
+
 void bw_BrowserWindow_evalJs( bw_BrowserWindow* bw, bw_CStrSlice js, bw_BrowserWindowJsCallbackFn cb, void* user_data ) {
 	// Wrap the JS code within a temporary function and execute it, and convert the return value to a string
 	// This allows executing JS code that isn't terminated with a semicolon, and does the javascript value string conversion inside JS.
@@ -90,6 +169,32 @@ BOOL bw_BrowserWindow_getUrl(bw_BrowserWindow* bw, bw_StrSlice* url) {
 	return TRUE;
 }
 
+void bw_BrowserWindow_getHistory( bw_BrowserWindow* bw, bw_BrowserWindowHistoryFn callback, void* data ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+
+	// CEF visits the entries synchronously
+	CefRefPtr<BwHistoryVisitor> visitor = new BwHistoryVisitor( callback, data );
+	cef_browser->GetHost()->GetNavigationEntries( visitor, false );
+}
+
+void bw_BrowserWindow_goBack( bw_BrowserWindow* bw ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	cef_browser->GoBack();
+}
+
+void bw_BrowserWindow_goForward( bw_BrowserWindow* bw ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	cef_browser->GoForward();
+}
+
+void bw_BrowserWindow_listenForRequests( bw_BrowserWindow* bw ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+
+	std::optional<bw::BrowserInfo*> bw_info = bw::bw_handle_map.fetch( cef_browser );
+	if (bw_info.has_value())
+		bw_info.value()->listening_for_requests = true;
+}
+
 #ifdef BW_GTK
 void bw_BrowserWindowCef_connectToGtkWindow( bw_BrowserWindow* bw, CefWindowInfo& info, int width, int height ) {
 #ifdef CEF_X11
@@ -153,6 +258,30 @@ bw_Err bw_BrowserWindow_navigate( bw_BrowserWindow* bw, bw_CStrSlice url ) {
 	BW_ERR_RETURN_SUCCESS;
 }
 
+void bw_BrowserWindow_reload( bw_BrowserWindow* bw, BOOL ignore_cache ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	if ( ignore_cache )
+		cef_browser->ReloadIgnoreCache();
+	else
+		cef_browser->Reload();
+}
+
+void bw_BrowserWindow_stop( bw_BrowserWindow* bw ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	cef_browser->StopLoad();
+}
+
+void bw_BrowserWindow_unbind( bw_BrowserWindow* bw, bw_CStrSlice name ) {
+	CefRefPtr<CefBrowser> cef_browser = *(CefRefPtr<CefBrowser>*)bw->impl.cef_ptr;
+	std::string _name( name.data, name.len );
+
+	std::optional<bw::BrowserInfo*> bw_info = bw::bw_handle_map.fetch( cef_browser );
+	BW_ASSERT( bw_info.has_value(), "Link between CEF's browser handle and our handle does not exist!\n" );
+	bw_info.value()->bindings.erase( _name );
+
+	bw::sendBindingMessage( cef_browser->GetMainFrame(), "unbind", _name );
+}
+
 void bw_BrowserWindowImpl_new(
 	bw_BrowserWindow* browser,
 	bw_BrowserWindowSource source,
@@ -197,6 +326,9 @@ void bw_BrowserWindowImpl_new(
 	dict->SetBinary( "callback", CefBinaryValue::Create( (const void*)&callback, sizeof(callback) ) );
 	dict->SetBinary( "callback-data", CefBinaryValue::Create( (const void*)&callback_data, sizeof(callback_data) ) );
 	dict->SetBool( "dev-tools", browser_window_options->dev_tools );
+	// The render process needs it to define `invoke_extern` with
+	const bw_CStrSlice& invoke_extern_js = browser->window->app->engine_impl.invoke_extern_js;
+	dict->SetString( BW_CEF_INVOKE_EXTERN_JS_KEY, std::string( invoke_extern_js.data, invoke_extern_js.len ) );
 	
 	// Create the browser
 	CefRefPtr<CefClient>* cef_client = (CefRefPtr<CefClient>*)browser->window->app->engine_impl.cef_client;
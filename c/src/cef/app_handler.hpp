@@ -11,19 +11,75 @@
 
 #include <include/cef_app.h>
 #include <include/cef_client.h>
+#include <include/cef_command_line.h>
 #include <include/cef_life_span_handler.h>
 #include <include/cef_v8.h>
+#include <map>
+#include <set>
+#include <sstream>
+#include <string>
+#include <vector>
 
+// The command line switch by which the custom schemes are passed on to child processes
+#define BW_CEF_SCHEMES_SWITCH "bw-custom-schemes"
 
 
-class AppHandler : public CefApp, public CefRenderProcessHandler {
+
+class AppHandler : public CefApp, public CefBrowserProcessHandler, public CefRenderProcessHandler {
 
 	bw_Application* app;
+	std::vector<std::string> schemes;
+	// The script that defines `invoke_extern`, for each browser that lives in this render process, keyed by browser ID
+	std::map<int, std::string> invoke_extern_js;
+	// The names of the bound functions for each browser that lives in this render process, keyed by browser ID
+	std::map<int, std::set<std::string>> bindings;
 
 public:
 	AppHandler( bw_Application* app ) : app(app) {}
+	AppHandler( bw_Application* app, const bw_ApplicationSettings* settings ) : app(app) {
+		for ( size_t i = 0; i < settings->scheme_count; i++ ) {
+			const bw_CStrSlice& name = settings->schemes[i].name;
+			this->schemes.push_back( std::string( name.data, name.len ) );
+		}
+	}
+
+	virtual void OnBeforeChildProcessLaunch( CefRefPtr<CefCommandLine> command_line ) override {
+		if ( this->schemes.empty() ) { return; }
+
+		std::string value;
+		for ( auto it = this->schemes.begin(); it != this->schemes.end(); it++ ) {
+			if ( !value.empty() ) { value += ","; }
+			value += *it;
+		}
+		command_line->AppendSwitchWithValue( BW_CEF_SCHEMES_SWITCH, value );
+	}
+
+	// Custom schemes need to be registered in every process, child processes receive them through the command line.
+	virtual void OnRegisterCustomSchemes( CefRawPtr<CefSchemeRegistrar> registrar ) override {
+		if ( this->schemes.empty() ) {
+			CefRefPtr<CefCommandLine> command_line = CefCommandLine::GetGlobalCommandLine();
+			if ( command_line.get() != nullptr && command_line->HasSwitch( BW_CEF_SCHEMES_SWITCH ) ) {
+				std::stringstream stream( command_line->GetSwitchValue( BW_CEF_SCHEMES_SWITCH ).ToString() );
+				std::string name;
+				while ( std::getline( stream, name, ',' ) ) {
+					this->schemes.push_back( name );
+				}
+			}
+		}
+
+		for ( auto it = this->schemes.begin(); it != this->schemes.end(); it++ ) {
+			registrar->AddCustomScheme(
+				*it,
+				CEF_SCHEME_OPTION_STANDARD | CEF_SCHEME_OPTION_SECURE | CEF_SCHEME_OPTION_CORS_ENABLED | CEF_SCHEME_OPTION_FETCH_ENABLED
+			);
+		}
+	}
 
 	virtual void OnBrowserCreated( CefRefPtr<CefBrowser> browser, CefRefPtr<CefDictionaryValue> extra_info ) override {
+		if ( extra_info != nullptr && extra_info->HasKey( BW_CEF_INVOKE_EXTERN_JS_KEY ) ) {
+			this->invoke_extern_js[ browser->GetIdentifier() ] = extra_info->GetString( BW_CEF_INVOKE_EXTERN_JS_KEY ).ToString();
+		}
+
 		/*if (browser->IsPopup()) { return; }
 		printf("OnBrowserCreated %p\n", browser->GetMainFrame());
 		auto main_frame = browser->GetMainFrame();
@@ -42,6 +98,11 @@ public:
 		}*/
 	}
 
+	virtual void OnBrowserDestroyed( CefRefPtr<CefBrowser> browser ) override {
+		this->bindings.erase( browser->GetIdentifier() );
+		this->invoke_extern_js.erase( browser->GetIdentifier() );
+	}
+
 	virtual void OnContextCreated( CefRefPtr<CefBrowser> browser, CefRefPtr<CefFrame> frame, CefRefPtr<CefV8Context> context ) override {
 		if (browser->IsPopup()) { return; }
 		
@@ -51,10 +112,32 @@ public:
 		CefRefPtr<CefV8Value> object = context->GetGlobal();
 
 		CefRefPtr<CefV8Handler> handler = new bw::ExternalInvocationHandler( browser );
-		CefRefPtr<CefV8Value> func = CefV8Value::CreateFunction("invoke_extern", handler);
+		CefRefPtr<CefV8Value> func = CefV8Value::CreateFunction(BW_CEF_POST_MESSAGE_FUNC, handler);
+
+		bool result = object->SetValue( BW_CEF_POST_MESSAGE_FUNC, func, V8_PROPERTY_ATTRIBUTE_NONE );
+		BW_ASSERT( result, "Unable to set " BW_CEF_POST_MESSAGE_FUNC " function." );
+
+		// The actual `invoke_extern` function is written in JavaScript, because it returns a promise.
+		// The script is the same one that the other browser frameworks use, which is why it comes from the Rust side.
+		auto js = this->invoke_extern_js.find( browser->GetIdentifier() );
+		if ( js != this->invoke_extern_js.end() ) {
+			CefRefPtr<CefV8Value> ret_val;
+			CefRefPtr<CefV8Exception> exception;
+			result = context->Eval( js->second, CefString(), 0, ret_val, exception );
+			BW_ASSERT( result, "Unable to define invoke_extern function." );
+		}
+
+		// Define the functions that are bound for this browser, so that they survive navigations and reloads
+		auto it = this->bindings.find( browser->GetIdentifier() );
+		if ( it != this->bindings.end() ) {
+			for ( auto name = it->second.begin(); name != it->second.end(); name++ ) {
+				this->call_binding_func( context, BW_CEF_BIND_FUNC, *name );
+			}
+		}
+	}
 
-		bool result = object->SetValue( "invoke_extern", func, V8_PROPERTY_ATTRIBUTE_NONE );
-		BW_ASSERT( result, "Unable to set invoke_extern function." );
+	virtual CefRefPtr<CefBrowserProcessHandler> GetBrowserProcessHandler() override {
+		return this;
 	}
 
 	virtual CefRefPtr<CefRenderProcessHandler> GetRenderProcessHandler() override {
@@ -89,12 +172,42 @@ public:
 
 			return true;
 		}
+		// The messages to (un)define a bound function, in the current page as well as in all pages loaded afterwards
+		else if ( message->GetName() == "bind" || message->GetName() == "unbind" ) {
+			bool bind = message->GetName() == "bind";
+			std::string name = message->GetArgumentList()->GetString( 0 ).ToString();
+
+			std::set<std::string>& names = this->bindings[ browser->GetIdentifier() ];
+			if ( bind ) { names.insert( name ); }
+			else { names.erase( name ); }
+
+			CefRefPtr<CefV8Context> context = frame->GetV8Context();
+			if ( context.get() != nullptr && context->IsValid() ) {
+				this->call_binding_func( context, bind ? BW_CEF_BIND_FUNC : BW_CEF_UNBIND_FUNC, name );
+			}
+
+			return true;
+		}
 		else
 			fprintf(stderr, "Unknown process message received: %s\n", message->GetName().ToString().c_str() );
 
 		return false;
 	}
 
+	// Calls the JavaScript function that (un)defines the bound function with the given name.
+	void call_binding_func( CefRefPtr<CefV8Context> context, const char* func_name, const std::string& name ) {
+		context->Enter();
+
+		CefRefPtr<CefV8Value> func = context->GetGlobal()->GetValue( func_name );
+		if ( func.get() != nullptr && func->IsFunction() ) {
+			CefV8ValueList args;
+			args.push_back( CefV8Value::CreateString( name ) );
+			func->ExecuteFunction( nullptr, args );
+		}
+
+		context->Exit();
+	}
+
 	// Evaluate JavaScript, and send back a message to the main process with the result
 	void eval_js(
 		CefRefPtr<CefBrowser> browser,
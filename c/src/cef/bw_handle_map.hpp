@@ -4,9 +4,13 @@
 #include "../browser_window.h"
 
 #include <include/cef_browser.h>
+#include <atomic>
 #include <optional>
 #include <map>
 #include <mutex>
+#include <set>
+#include <string>
+#include <tuple>
 
 
 
@@ -17,9 +21,22 @@ namespace bw {
 		void* data;
 	};
 
+	// The host, port, realm and whether it is a proxy, of a request for credentials.
+	typedef std::tuple<std::string, int, std::string, bool> AuthRequestKey;
+
 	struct BrowserInfo {
 		bw_BrowserWindow* handle;
 		std::optional<OnCreateCallback> callback;
+		// The names of the functions bound with `bw_BrowserWindow_bind`
+		std::set<std::string> bindings;
+		// Set while a dialog of `bw_Window_fileDialog` is being opened, so that `on_file_dialog` isn't fired for it
+		bool own_file_dialog = false;
+		// The requests that credentials have been given for while the page is loading.
+		// CEF doesn't tell whether a request is a retry, but it only asks again if the credentials have been rejected.
+		std::set<AuthRequestKey> given_credentials;
+		// Whether `on_request` or `on_response` may have a callback.
+		// The requests are seen on the IO thread, which can't look at the events themselves while the GUI thread may change them.
+		std::atomic<bool> listening_for_requests = false;
 	};
 
 	// A thread safe class that links CEF browser handles to our browser window handdles.
@@ -68,6 +85,14 @@ namespace bw {
 
 			return result;
 		}
+
+		// Fetches the info of any browser window, if there is one.
+		std::optional<BrowserInfo*> any() {
+			std::lock_guard<std::mutex> lock( this->mutex );
+			if ( this->map.empty() )
+				return std::optional<BrowserInfo*>();
+			return std::optional<BrowserInfo*>( &this->map.begin()->second );
+		}
 	};
 
 	// A global instance
@@ -1,6 +1,31 @@
 #include "client_handler.hpp"
 
 
+void ClientHandler::authCredentialsHandlerFunc( bw_Application* app, void* _data ) {
+	auto data = (AuthCredentialsData*)_data;
+	bw::AuthRequestKey request( data->host, data->port, data->realm, data->is_proxy );
+	auto callback = new bw_AuthCallback { data->callback, data->browser, request };
+
+	// The browser window may have been closed in the meantime
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( data->browser );
+	if ( bw_info_opt.has_value() && bw_info_opt.value()->handle->events.on_auth_credentials.callback != NULL ) {
+		bw_BrowserWindowAuthRequest args = {
+			{ data->host.length(), data->host.c_str() },
+			(uint16_t)data->port,
+			{ data->realm.length(), data->realm.c_str() },
+			{ data->scheme.length(), data->scheme.c_str() },
+			data->is_proxy,
+			bw_info_opt.value()->given_credentials.count( request ) > 0,
+			callback
+		};
+		bw_Event_fire( &bw_info_opt.value()->handle->events.on_auth_credentials, (void*)&args );
+	}
+	else
+		bw_AuthCallback_cancel( callback );
+
+	delete data;
+}
+
 void ClientHandler::externalInvocationHandlerFunc( bw_Application* app, void* _data ) {
 	auto data = (ExternalInvocationHandlerData*)_data;
 
@@ -25,9 +50,75 @@ void ClientHandler::externalInvocationHandlerFunc( bw_Application* app, void* _d
 
 	// Fire!
 	bw_BrowserWindowMessageArgs args = {
+		data->id,
 		cmd_str_slice,
 		params_slices.size(),
 		&params_slices[0]
 	};
 	bw_Event_fire(&data->bw->events.on_message, (void*)&args);
-}
\ No newline at end of file
+}
+
+
+void bw_AuthCallback_cancel( bw_AuthCallback* callback ) {
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( callback->browser );
+	if ( bw_info_opt.has_value() )
+		bw_info_opt.value()->given_credentials.erase( callback->request );
+
+	callback->inner->Cancel();
+	delete callback;
+}
+
+void bw_AuthCallback_continue( bw_AuthCallback* callback, bw_CStrSlice username, bw_CStrSlice password ) {
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( callback->browser );
+	if ( bw_info_opt.has_value() )
+		bw_info_opt.value()->given_credentials.insert( callback->request );
+
+	callback->inner->Continue( std::string( username.data, username.len ), std::string( password.data, password.len ) );
+	delete callback;
+}
+
+void bw_CertificateCallback_allow( bw_CertificateCallback* callback ) {
+	callback->inner->Continue();
+	delete callback;
+}
+
+void bw_CertificateCallback_deny( bw_CertificateCallback* callback ) {
+	callback->inner->Cancel();
+	delete callback;
+}
+
+void bw_ClientCertificateCallback_cancel( bw_ClientCertificateCallback* callback ) {
+	callback->inner->Select( nullptr );
+	delete callback;
+}
+
+void bw_ClientCertificateCallback_select( bw_ClientCertificateCallback* callback, size_t index ) {
+	callback->inner->Select( callback->candidates[index] );
+	delete callback;
+}
+
+void bw_DownloadCallback_cancel( bw_DownloadCallback* callback ) {
+	// The download is cancelled when the callback is released without having been executed
+	delete callback;
+}
+
+void bw_DownloadCallback_continue( bw_DownloadCallback* callback, bw_CStrSlice path ) {
+	if (path.len == 0)
+		callback->inner->Continue( callback->default_path, false );
+	else
+		callback->inner->Continue( std::string( path.data, path.len ), false );
+	delete callback;
+}
+
+void bw_FileDialogCallback_cancel( bw_FileDialogCallback* callback ) {
+	callback->inner->Cancel();
+	delete callback;
+}
+
+void bw_FileDialogCallback_continue( bw_FileDialogCallback* callback, const bw_CStrSlice* paths, size_t path_count ) {
+	std::vector<CefString> file_paths;
+	for (size_t i = 0; i < path_count; i++)
+		file_paths.push_back( std::string( paths[i].data, paths[i].len ) );
+	callback->inner->Continue( file_paths );
+	delete callback;
+}
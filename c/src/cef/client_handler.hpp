@@ -2,48 +2,305 @@
 #define BW_CEF_CLIENT_HANDLER_H
 
 #include <include/cef_client.h>
+#include <include/cef_dialog_handler.h>
 #include <include/cef_download_handler.h>
+#include <include/cef_keyboard_handler.h>
 #include <include/cef_life_span_handler.h>
 #include <include/cef_load_handler.h>
 #include <include/cef_request_handler.h>
 #include <include/cef_version.h>
 #include <include/cef_v8.h>
+#include <set>
 #include <string>
 #include <vector>
 
 #include "bw_handle_map.hpp"
+#include "download_client.hpp"
+#include "external_invocation_handler.hpp"
+#include "resource_request_handler.hpp"
 #include "util.hpp"
 #include "../application.h"
 #include "../common.h"
 
 
 
+struct bw_AuthCallback {
+	CefRefPtr<CefAuthCallback> inner;
+	CefRefPtr<CefBrowser> browser;
+	bw::AuthRequestKey request;
+};
+
+struct bw_CertificateCallback {
+	CefRefPtr<CefCallback> inner;
+};
+
+struct bw_DownloadCallback {
+	CefRefPtr<CefBeforeDownloadCallback> inner;
+	// Where to save the download if no path is given
+	std::string default_path;
+};
+
+struct bw_FileDialogCallback {
+	CefRefPtr<CefFileDialogCallback> inner;
+};
+
+struct bw_ClientCertificateCallback {
+	CefRefPtr<CefSelectClientCertificateCallback> inner;
+	CefRequestHandler::X509CertificateList candidates;
+};
+
+/// Keeps the data that a `bw_Certificate` points to alive.
+struct CertificateData {
+	std::string subject;
+	std::string issuer;
+	int64_t valid_from;
+	int64_t valid_until;
+	std::vector<uint8_t> der;
+
+	CertificateData(CefRefPtr<CefX509Certificate> cert) :
+		subject(cert->GetSubject()->GetDisplayName().ToString()),
+		issuer(cert->GetIssuer()->GetDisplayName().ToString()),
+		valid_from(bw_cef_toUnixTime(cert->GetValidStart())),
+		valid_until(bw_cef_toUnixTime(cert->GetValidExpiry()))
+	{
+		CefRefPtr<CefBinaryValue> encoded = cert->GetDEREncoded();
+		der.resize(encoded->GetSize());
+		if (der.size() > 0)
+			encoded->GetData(&der[0], der.size(), 0);
+	}
+
+	bw_Certificate toC() const {
+		return bw_Certificate {
+			{ subject.length(), subject.c_str() },
+			{ issuer.length(), issuer.c_str() },
+			valid_from,
+			valid_until,
+			der.data(),
+			der.size()
+		};
+	}
+};
+
+struct AuthCredentialsData {
+	CefRefPtr<CefBrowser> browser;
+	bool is_proxy;
+	std::string host;
+	int port;
+	std::string realm;
+	std::string scheme;
+	CefRefPtr<CefAuthCallback> callback;
+};
+
 struct ExternalInvocationHandlerData {
 	bw_BrowserWindow* bw;
+	unsigned int id;
 	std::string cmd;
 	std::vector<std::string> params;
 };
 
 class ClientHandler :
 	public CefClient,
+	public CefDialogHandler,
 	public CefDisplayHandler,
 	public CefDownloadHandler,
+	public CefKeyboardHandler,
 	public CefRequestHandler,
 	public CefLifeSpanHandler,
 	public CefLoadHandler
 {
 
 	bw_Application* app;
+	std::set<uint32_t> finished_downloads;
+
+	/// Fires `on_key_press` if `before_page` is true, and `on_key_pressed` otherwise.
+	/// Only the key down events are passed on.
+	bool fireKeyEvent(CefRefPtr<CefBrowser> browser, const CefKeyEvent& event, bool before_page) {
+		if (event.type != KEYEVENT_RAWKEYDOWN && event.type != KEYEVENT_KEYDOWN)
+			return false;
+
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value())
+			return false;
+		auto bw_info = bw_info_opt.value();
+
+		bw_BrowserWindowKeyEvent args;
+		args.key_code = event.windows_key_code;
+		args.modifiers = 0;
+		if (event.modifiers & EVENTFLAG_CONTROL_DOWN) args.modifiers |= BW_KEY_MODIFIER_CTRL;
+		if (event.modifiers & EVENTFLAG_SHIFT_DOWN) args.modifiers |= BW_KEY_MODIFIER_SHIFT;
+		if (event.modifiers & EVENTFLAG_ALT_DOWN) args.modifiers |= BW_KEY_MODIFIER_ALT;
+		if (event.modifiers & EVENTFLAG_COMMAND_DOWN) args.modifiers |= BW_KEY_MODIFIER_META;
+		args.is_repeat = (event.modifiers & EVENTFLAG_IS_REPEAT) != 0;
+		// Control characters don't count as characters
+		args.character = event.character >= 0x20 && event.character != 0x7F ? event.character : 0;
+
+		bw_Event* bw_event = before_page ? &bw_info->handle->events.on_key_press : &bw_info->handle->events.on_key_pressed;
+		return bw_Event_fire(bw_event, (void*)&args);
+	}
 
 public:
 	ClientHandler( bw_Application* app ) : app(app) {}
 
+	CefRefPtr<CefDialogHandler> GetDialogHandler() override { return this; }
 	CefRefPtr<CefDisplayHandler> GetDisplayHandler() override { return this; }
 	CefRefPtr<CefDownloadHandler> GetDownloadHandler() override { return this; }
+	CefRefPtr<CefKeyboardHandler> GetKeyboardHandler() override { return this; }
 	CefRefPtr<CefLifeSpanHandler> GetLifeSpanHandler() override { return this; }
 	CefRefPtr<CefLoadHandler> GetLoadHandler() override { return this; }
 	CefRefPtr<CefRequestHandler> GetRequestHandler() override { return this; }
 
+	bool OnBeforeBrowse(CefRefPtr<CefBrowser> browser, CefRefPtr<CefFrame> frame, CefRefPtr<CefRequest> request, bool user_gesture, bool is_redirect) override {
+		UNUSED(is_redirect);
+
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value())
+			return false;
+		auto bw_info = bw_info_opt.value();
+
+		cef_transition_type_t transition = request->GetTransitionType();
+		unsigned int type;
+		if (transition & TT_FORWARD_BACK_FLAG)
+			type = BW_NAVIGATION_TYPE_BACK_FORWARD;
+		else switch (transition & TT_SOURCE_MASK) {
+		case TT_LINK: type = BW_NAVIGATION_TYPE_LINK; break;
+		case TT_FORM_SUBMIT: type = BW_NAVIGATION_TYPE_FORM_SUBMISSION; break;
+		case TT_RELOAD: type = BW_NAVIGATION_TYPE_RELOAD; break;
+		default: type = BW_NAVIGATION_TYPE_OTHER;
+		}
+
+		bw_BrowserWindowNavigationRequest args;
+		args.url = bw_cef_copyToCStrSlice(request->GetURL());
+		args.is_main_frame = frame->IsMain();
+		args.user_gesture = user_gesture;
+		args.type = type;
+		BOOL cancel = bw_Event_fire(&bw_info->handle->events.on_navigation_request, (void*)&args);
+		bw_string_freeC(args.url);
+		return cancel;
+	}
+
+	CefRefPtr<CefResourceRequestHandler> GetResourceRequestHandler(
+		CefRefPtr<CefBrowser> browser,
+		CefRefPtr<CefFrame> frame,
+		CefRefPtr<CefRequest> request,
+		bool is_navigation,
+		bool is_download,
+		const CefString& request_initiator,
+		bool& disable_default_handling
+	) override {
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value())
+			return nullptr;
+		auto bw_info = bw_info_opt.value();
+
+		// Only bother with the requests when someone is interested in them
+		if (!bw_info->listening_for_requests)
+			return nullptr;
+		return new bw::ResourceRequestHandler(this->app);
+	}
+
+	bool GetAuthCredentials(
+		CefRefPtr<CefBrowser> browser,
+		const CefString& origin_url,
+		bool isProxy,
+		const CefString& host,
+		int port,
+		const CefString& realm,
+		const CefString& scheme,
+		CefRefPtr<CefAuthCallback> callback
+	) override {
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value() || bw_info_opt.value()->handle->events.on_auth_credentials.callback == NULL)
+			return false;
+
+		// This is called on the IO thread, so the event is fired on the GUI thread
+		auto data = new AuthCredentialsData {
+			browser,
+			isProxy,
+			host.ToString(),
+			port,
+			realm.ToString(),
+			scheme.ToString(),
+			callback
+		};
+		bw_Application_dispatch(this->app, authCredentialsHandlerFunc, data);
+		return true;
+	}
+
+	bool OnCertificateError(
+		CefRefPtr<CefBrowser> browser,
+		cef_errorcode_t cert_error,
+		const CefString& request_url,
+		CefRefPtr<CefSSLInfo> ssl_info,
+		CefRefPtr<CefCallback> callback
+	) override {
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value() || bw_info_opt.value()->handle->events.on_certificate_error.callback == NULL)
+			return false;
+		auto bw_info = bw_info_opt.value();
+
+		unsigned int error;
+		switch (cert_error) {
+		case ERR_CERT_COMMON_NAME_INVALID: error = BW_CERTIFICATE_ERROR_HOST_MISMATCH; break;
+		case ERR_CERT_DATE_INVALID: error = BW_CERTIFICATE_ERROR_INVALID_DATE; break;
+		case ERR_CERT_AUTHORITY_INVALID: error = BW_CERTIFICATE_ERROR_UNKNOWN_AUTHORITY; break;
+		case ERR_CERT_REVOKED: error = BW_CERTIFICATE_ERROR_REVOKED; break;
+		case ERR_CERT_INVALID:
+		case ERR_CERT_WEAK_SIGNATURE_ALGORITHM:
+		case ERR_CERT_WEAK_KEY:
+			error = BW_CERTIFICATE_ERROR_INVALID; break;
+		default: error = BW_CERTIFICATE_ERROR_OTHER;
+		}
+
+		std::string url = request_url.ToString();
+		CertificateData certificate(ssl_info->GetX509Certificate());
+
+		// This is already called on the GUI thread
+		bw_BrowserWindowCertificateError args = {
+			{ url.length(), url.c_str() },
+			error,
+			certificate.toC(),
+			new bw_CertificateCallback { callback }
+		};
+		bw_Event_fire(&bw_info->handle->events.on_certificate_error, (void*)&args);
+		return true;
+	}
+
+	bool OnSelectClientCertificate(
+		CefRefPtr<CefBrowser> browser,
+		bool isProxy,
+		const CefString& host,
+		int port,
+		const X509CertificateList& certificates,
+		CefRefPtr<CefSelectClientCertificateCallback> callback
+	) override {
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value() || bw_info_opt.value()->handle->events.on_select_client_certificate.callback == NULL)
+			return false;
+		auto bw_info = bw_info_opt.value();
+
+		std::string host_str = host.ToString();
+		std::vector<CertificateData> candidates_data;
+		std::vector<bw_Certificate> candidates;
+		candidates_data.reserve(certificates.size());
+		candidates.reserve(certificates.size());
+		for (auto it = certificates.begin(); it != certificates.end(); it++) {
+			candidates_data.emplace_back(*it);
+			candidates.push_back(candidates_data.back().toC());
+		}
+
+		// This is already called on the GUI thread
+		bw_BrowserWindowClientCertificateRequest args = {
+			{ host_str.length(), host_str.c_str() },
+			(uint16_t)port,
+			isProxy,
+			candidates.data(),
+			candidates.size(),
+			new bw_ClientCertificateCallback { callback, certificates }
+		};
+		bw_Event_fire(&bw_info->handle->events.on_select_client_certificate, (void*)&args);
+		return true;
+	}
+
 	void OnAddressChange(CefRefPtr<CefBrowser> browser, CefRefPtr<CefFrame> frame, const CefString& url) override {
 		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
 		if (bw_info_opt.has_value()) {
@@ -60,9 +317,144 @@ public:
 	bool
 #endif
 	OnBeforeDownload(CefRefPtr<CefBrowser> browser, CefRefPtr<CefDownloadItem> download_item, const CefString& suggested_name, CefRefPtr<CefBeforeDownloadCallback> callback ) {
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value() || bw_info_opt.value()->handle->events.on_download_started.callback == NULL) {
 #if CEF_VERSION_MAJOR >= 141
-		return false;
+			return false;
+#else
+			return;
+#endif
+		}
+		auto bw_info = bw_info_opt.value();
+
+		std::string url = download_item->GetOriginalUrl().ToString();
+		std::string name = suggested_name.ToString();
+		std::string mime_type = download_item->GetMimeType().ToString();
+		int64_t total_size = download_item->GetTotalBytes();
+
+		// Without a path, CEF would save the file in the temporary directory, so the user's download directory is filled in instead.
+		std::string default_path = bw::defaultDownloadPath(browser, name);
+
+		// This is already called on the GUI thread
+		bw_BrowserWindowDownload args = {
+			download_item->GetId(),
+			{ url.length(), url.c_str() },
+			{ name.length(), name.c_str() },
+			{ mime_type.length(), mime_type.c_str() },
+			total_size > 0 ? total_size : -1,
+			new bw_DownloadCallback { callback, default_path }
+		};
+		bw_Event_fire(&bw_info->handle->events.on_download_started, (void*)&args);
+#if CEF_VERSION_MAJOR >= 141
+		return true;
+#endif
+	}
+
+	bool OnFileDialog(CefRefPtr<CefBrowser> browser,
+		FileDialogMode mode,
+		const CefString& title,
+		const CefString& default_file_path,
+		const std::vector<CefString>& accept_filters,
+		const std::vector<CefString>& accept_extensions,
+		const std::vector<CefString>& accept_descriptions,
+		CefRefPtr<CefFileDialogCallback> callback
+	) override {
+		UNUSED(accept_extensions);
+		UNUSED(accept_descriptions);
+
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value())
+			return false;
+		auto bw_info = bw_info_opt.value();
+		// Our own dialogs are shown by CEF
+		if (bw_info->own_file_dialog) {
+			bw_info->own_file_dialog = false;
+			return false;
+		}
+
+		std::string title_str = title.ToString();
+		std::string default_path = default_file_path.ToString();
+		std::vector<std::string> accept;
+		for (const CefString& filter : accept_filters)
+			accept.push_back(filter.ToString());
+		std::vector<bw_CStrSlice> accept_slices;
+		for (const std::string& filter : accept)
+			accept_slices.push_back({ filter.length(), filter.c_str() });
+
+		bw_BrowserWindowFileDialog args = {
+			(unsigned int)mode,
+			{ title_str.length(), title_str.c_str() },
+			{ default_path.length(), default_path.c_str() },
+			accept_slices.data(),
+			accept_slices.size(),
+			new bw_FileDialogCallback { callback }
+		};
+		if (!bw_Event_fire(&bw_info->handle->events.on_file_dialog, (void*)&args)) {
+			delete args.callback;
+			return false;
+		}
+		return true;
+	}
+
+	bool OnPreKeyEvent(CefRefPtr<CefBrowser> browser, const CefKeyEvent& event, CefEventHandle os_event, bool* is_keyboard_shortcut) override {
+		UNUSED(os_event);
+		UNUSED(is_keyboard_shortcut);
+		return this->fireKeyEvent(browser, event, true);
+	}
+
+	bool OnKeyEvent(CefRefPtr<CefBrowser> browser, const CefKeyEvent& event, CefEventHandle os_event) override {
+		UNUSED(os_event);
+		return this->fireKeyEvent(browser, event, false);
+	}
+
+	void OnDownloadUpdated(CefRefPtr<CefBrowser> browser, CefRefPtr<CefDownloadItem> download_item, CefRefPtr<CefDownloadItemCallback> callback) override {
+		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+		if (!bw_info_opt.has_value())
+			return;
+		auto bw_info = bw_info_opt.value();
+
+		unsigned int id = download_item->GetId();
+		std::string url = download_item->GetOriginalUrl().ToString();
+		int64_t total_size = download_item->GetTotalBytes();
+
+		unsigned int result;
+		std::string error;
+		if (download_item->IsComplete())
+			result = BW_DOWNLOAD_RESULT_SUCCESS;
+		else if (download_item->IsCanceled())
+			result = BW_DOWNLOAD_RESULT_CANCELLED;
+#if CEF_VERSION_MAJOR >= 122
+		else if (download_item->IsInterrupted()) {
+			result = BW_DOWNLOAD_RESULT_FAILED;
+			error = "interrupted with reason " + std::to_string((int)download_item->GetInterruptReason());
+		}
 #endif
+		else {
+			bw_BrowserWindowDownloadProgress args = {
+				id,
+				0,
+				{ url.length(), url.c_str() },
+				download_item->GetReceivedBytes(),
+				total_size > 0 ? total_size : -1
+			};
+			bw_Event_fire(&bw_info->handle->events.on_download_progress, (void*)&args);
+			return;
+		}
+
+		// CEF may keep on updating downloads that have already finished
+		if (!this->finished_downloads.insert(id).second)
+			return;
+
+		std::string path = download_item->GetFullPath().ToString();
+		bw_BrowserWindowDownloadFinished args = {
+			id,
+			0,
+			{ url.length(), url.c_str() },
+			{ path.length(), path.c_str() },
+			result,
+			{ error.length(), error.c_str() }
+		};
+		bw_Event_fire(&bw_info->handle->events.on_download_finished, (void*)&args);
 	}
 
 	bool OnConsoleMessage( CefRefPtr< CefBrowser > browser, cef_log_severity_t level, const CefString& message, const CefString& source, int line ) override {
@@ -89,6 +481,13 @@ public:
 	}
 
 	virtual void OnLoadEnd(CefRefPtr<CefBrowser> browser, CefRefPtr<CefFrame> frame, int httpStatusCode) override {
+		// Once the page has loaded, the credentials that have been given for it have done their job
+		if (frame->IsMain()) {
+			std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
+			if (bw_info_opt.has_value())
+				bw_info_opt.value()->given_credentials.clear();
+		}
+
 		BW_ERR_DECLARE_SUCCESS(error);
 		this->invokeCreationCallback(browser, error);
 	}
@@ -133,6 +532,14 @@ public:
 		std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(browser);
 		if (bw_info_opt.has_value()) {
 			auto bw_info = bw_info_opt.value();
+
+			// The page may have been loaded in a new render process, which doesn't know about the bound functions yet.
+			if (frame->IsMain()) {
+				for (auto it = bw_info->bindings.begin(); it != bw_info->bindings.end(); it++) {
+					bw::sendBindingMessage(frame, "bind", *it);
+				}
+			}
+
 			bw_CStrSlice slice = { 0, 0 };
 			bw_Event_fire(&bw_info->handle->events.on_navigation_start, (void*)&slice);
 		}
@@ -203,6 +610,7 @@ public:
 
 protected:
 
+	static void authCredentialsHandlerFunc( bw_Application* app, void* data );
 	static void externalInvocationHandlerFunc( bw_Application* app, void* data );
 
 	void onEvalJsResultReceived(
@@ -268,13 +676,16 @@ protected:
 
 		auto msg_args = msg->GetArgumentList();
 
+		// The first argument identifies the promise that is waiting for a response
+		unsigned int id = (unsigned int)msg_args->GetInt( 0 );
+
 		// This argument is the command string
-		CefString cmd = msg_args->GetString( 0 );
+		CefString cmd = msg_args->GetString( 1 );
 		std::string cmd_str = cmd.ToString();
 
 		// All next message arguments are the arguments of the command
-		std::vector<std::string> params; params.reserve( msg_args->GetSize() - 1 );
-		for ( size_t i = 1; i < msg_args->GetSize(); i++ ) {
+		std::vector<std::string> params; params.reserve( msg_args->GetSize() - 2 );
+		for ( size_t i = 2; i < msg_args->GetSize(); i++ ) {
 			std::string param = msg_args->GetString( i ).ToString();
 
 			params.push_back( param );
@@ -283,6 +694,7 @@ protected:
 		// Dispatch the invocation of the external handler to the thread from which the BrowserWindow main loop runs.
 		auto dispatch_data = new ExternalInvocationHandlerData {
 			our_handle,
+			id,
 			cmd_str,
 			params
 		};
@@ -0,0 +1,115 @@
+#include "download_client.hpp"
+#include "bw_handle_map.hpp"
+
+#include <include/cef_request_context.h>
+#include <climits>
+#include <cstdio>
+
+
+
+bw::DownloadClient::DownloadClient( CefRefPtr<CefBrowser> browser, unsigned int request, const std::string& url, const std::string& path ) :
+	browser(browser), request(request), url(url), path(path)
+{
+	// CEF gives out the IDs of its own downloads counting up from 1, so these are counted down to stay clear of them.
+	static unsigned int next_id = UINT_MAX;
+	this->id = next_id--;
+}
+
+void bw::DownloadClient::OnDownloadProgress( CefRefPtr<CefURLRequest> request, int64_t current, int64_t total ) {
+	// The download is of no use anymore once the browser window has been closed
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( this->browser );
+	if ( !bw_info_opt.has_value() ) {
+		request->Cancel();
+		return;
+	}
+
+	bw_BrowserWindowDownloadProgress args = {
+		this->id,
+		this->request,
+		{ this->url.length(), this->url.c_str() },
+		current,
+		total > 0 ? total : -1
+	};
+	bw_Event_fire( &bw_info_opt.value()->handle->events.on_download_progress, (void*)&args );
+}
+
+void bw::DownloadClient::OnDownloadData( CefRefPtr<CefURLRequest> request, const void* data, size_t data_length ) {
+	if ( !this->open() ) {
+		request->Cancel();
+		return;
+	}
+
+	this->file.write( (const char*)data, data_length );
+	if ( !this->file ) {
+		this->error = "unable to write to " + this->path;
+		request->Cancel();
+	}
+}
+
+void bw::DownloadClient::OnRequestComplete( CefRefPtr<CefURLRequest> request ) {
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( this->browser );
+
+	unsigned int result = BW_DOWNLOAD_RESULT_FAILED;
+	std::string error = this->error;
+	if ( error.empty() ) {
+		CefRefPtr<CefResponse> response = request->GetResponse();
+		switch ( request->GetRequestStatus() ) {
+		case UR_SUCCESS:
+			// Like the downloads of CEF itself, error pages are not saved
+			if ( response != nullptr && response->GetStatus() >= 400 )
+				error = "the server responded with status " + std::to_string( response->GetStatus() );
+			// Files without any content still need to be created
+			else if ( this->open() )
+				result = BW_DOWNLOAD_RESULT_SUCCESS;
+			break;
+		case UR_CANCELED:
+			result = BW_DOWNLOAD_RESULT_CANCELLED;
+			break;
+		default:
+			error = "failed with error code " + std::to_string( (int)request->GetRequestError() );
+		}
+	}
+	// What has been written of a download that hasn't finished is of no use
+	bool created = this->file.is_open();
+	this->file.close();
+	if ( created && result != BW_DOWNLOAD_RESULT_SUCCESS )
+		std::remove( this->path.c_str() );
+
+	if ( !bw_info_opt.has_value() )
+		return;
+	bw_BrowserWindowDownloadFinished args = {
+		this->id,
+		this->request,
+		{ this->url.length(), this->url.c_str() },
+		{ this->path.length(), this->path.c_str() },
+		result,
+		{ error.length(), error.c_str() }
+	};
+	bw_Event_fire( &bw_info_opt.value()->handle->events.on_download_finished, (void*)&args );
+}
+
+bool bw::DownloadClient::open() {
+	if ( this->file.is_open() )
+		return true;
+	if ( !this->error.empty() )
+		return false;
+
+	this->file.open( this->path, std::ios::binary | std::ios::trunc );
+	if ( !this->file.is_open() ) {
+		this->error = "unable to create " + this->path;
+		return false;
+	}
+	return true;
+}
+
+std::string bw::defaultDownloadPath( CefRefPtr<CefBrowser> browser, const std::string& name ) {
+	CefRefPtr<CefValue> download_dir = browser->GetHost()->GetRequestContext()->GetPreference("download.default_directory");
+	if (download_dir == nullptr || download_dir->GetType() != VTYPE_STRING)
+		return std::string();
+
+#ifdef BW_WINDOWS
+	return download_dir->GetString().ToString() + "\\" + name;
+#else
+	return download_dir->GetString().ToString() + "/" + name;
+#endif
+}
@@ -0,0 +1,60 @@
+#ifndef BW_CEF_DOWNLOAD_CLIENT_H
+#define BW_CEF_DOWNLOAD_CLIENT_H
+
+#include "../browser_window.h"
+
+#include <include/cef_browser.h>
+#include <include/cef_urlrequest.h>
+#include <fstream>
+#include <string>
+
+
+
+namespace bw {
+
+	// Downloads a file for `bw_BrowserWindow_download`.
+	// CEF doesn't tell which download `StartDownload` has started, so the file is fetched with a URL request of its own instead.
+	// This way the download can be told apart by its request, also when it fails before any response has come in.
+	class DownloadClient : public CefURLRequestClient {
+		CefRefPtr<CefBrowser> browser;
+		unsigned int id;
+		unsigned int request;
+		std::string url;
+		std::string path;
+		std::ofstream file;
+		// Why the download has failed on our end, if it has
+		std::string error;
+
+	public:
+		DownloadClient( CefRefPtr<CefBrowser> browser, unsigned int request, const std::string& url, const std::string& path );
+
+		void OnRequestComplete( CefRefPtr<CefURLRequest> request ) override;
+		void OnUploadProgress( CefRefPtr<CefURLRequest> request, int64_t current, int64_t total ) override {}
+		void OnDownloadProgress( CefRefPtr<CefURLRequest> request, int64_t current, int64_t total ) override;
+		void OnDownloadData( CefRefPtr<CefURLRequest> request, const void* data, size_t data_length ) override;
+
+		bool GetAuthCredentials(
+			bool is_proxy,
+			const CefString& host,
+			int port,
+			const CefString& realm,
+			const CefString& scheme,
+			CefRefPtr<CefAuthCallback> callback
+		) override {
+			return false;
+		}
+
+	private:
+		// Opens the file if it isn't open yet, and returns whether it is open.
+		bool open();
+
+		IMPLEMENT_REFCOUNTING(DownloadClient);
+	};
+
+	// The path in the user's download directory to save a file with the given name at, or an empty string if there is no such directory.
+	std::string defaultDownloadPath( CefRefPtr<CefBrowser> browser, const std::string& name );
+}
+
+
+
+#endif//BW_CEF_DOWNLOAD_CLIENT_H
@@ -3,15 +3,33 @@
 
 #include <include/cef_v8.h>
 #include <optional>
+#include <string>
 
 #include "bw_handle_map.hpp"
 #include "v8_to_string.hpp"
 #include "../assert.h"
 
+// The native function that passes the message of `invoke_extern` on to the browser process
+#define BW_CEF_POST_MESSAGE_FUNC "__bw_post_message"
+
+// The functions that (un)define the functions that are bound with `bw_BrowserWindow_bind`
+#define BW_CEF_BIND_FUNC "__bw_bind"
+#define BW_CEF_UNBIND_FUNC "__bw_unbind"
+
+// The key of the browser's extra info under which the script that defines `invoke_extern` is passed on to the render process
+#define BW_CEF_INVOKE_EXTERN_JS_KEY "invoke-extern-js"
+
 
 
 namespace bw {
 
+	// Sends a "bind" or "unbind" message for the function with the given name to the render process of the frame.
+	inline void sendBindingMessage( CefRefPtr<CefFrame> frame, const char* message_name, const std::string& name ) {
+		CefRefPtr<CefProcessMessage> msg = CefProcessMessage::Create( message_name );
+		msg->GetArgumentList()->SetString( 0, name );
+		frame->SendProcessMessage( PID_RENDERER, msg );
+	}
+
 	class ExternalInvocationHandler : public CefV8Handler {
 		CefRefPtr<CefBrowser> cef_browser;
 
@@ -29,19 +47,24 @@ namespace bw {
 			(void)(retval);
 			(void)(exception);
 
-			if ( name == "invoke_extern" ) {
+			if ( name == BW_CEF_POST_MESSAGE_FUNC ) {
 
 				CefRefPtr<CefProcessMessage> msg = CefProcessMessage::Create("invoke-handler");
 				CefRefPtr<CefListValue> msg_args = msg->GetArgumentList();
 
-				// Convert all function arguments to strings
-				size_t index = 0;
-				for ( auto it = arguments.begin(); it != arguments.end(); it++, index++ ) {
+				// The only argument is an array of which the first item is the promise id, the second one the command, and the rest are converted to strings
+				if ( arguments.size() != 1 || !arguments[0]->IsArray() )
+					return false;
+				CefRefPtr<CefV8Value> items = arguments[0];
+				for ( int index = 0; index < items->GetArrayLength(); index++ ) {
+					CefRefPtr<CefV8Value> item = items->GetValue( index );
 
 					if (index == 0) {
-						msg_args->SetString(0, (*it)->GetStringValue());
+						msg_args->SetInt(0, item->GetIntValue());
+					} else if (index == 1) {
+						msg_args->SetString(1, item->GetStringValue());
 					} else {
-						CefString string = V8ToString::convert(*it);
+						CefString string = V8ToString::convert(item);
 						msg_args->SetString( index, string );
 					}
 				}
@@ -0,0 +1,213 @@
+#include "resource_request_handler.hpp"
+#include "bw_handle_map.hpp"
+
+#include <algorithm>
+#include <utility>
+#include <vector>
+
+
+
+struct ResponseData {
+	CefRefPtr<CefBrowser> browser;
+	std::string url;
+	std::string method;
+	uint16_t status;
+	std::vector<std::pair<std::string, std::string>> headers;
+};
+
+void _bw_ResourceRequestHandler_dispatchRequest( bw_Application* app, void* data );
+void _bw_ResourceRequestHandler_dispatchResponse( bw_Application* app, void* data );
+std::string _bw_ResourceRequestHandler_lowercase( std::string string );
+std::vector<bw_HttpHeader> _bw_ResourceRequestHandler_toHttpHeaders( const std::vector<std::pair<std::string, std::string>>& headers );
+
+
+
+CefResourceRequestHandler::ReturnValue bw::ResourceRequestHandler::OnBeforeResourceLoad(
+	CefRefPtr<CefBrowser> browser,
+	CefRefPtr<CefFrame> frame,
+	CefRefPtr<CefRequest> request,
+	CefRefPtr<CefCallback> callback
+) {
+	if ( browser.get() == nullptr ) { return RV_CONTINUE; }
+
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( browser );
+	if ( !bw_info_opt.has_value() || !bw_info_opt.value()->listening_for_requests ) {
+		return RV_CONTINUE;
+	}
+
+	// The request is continued once the event has been fired on the GUI thread, if it has a callback there by then
+	this->browser = browser;
+	this->request = request;
+	this->callback = callback;
+	bw_Application_dispatch( this->app, _bw_ResourceRequestHandler_dispatchRequest, new CefRefPtr<ResourceRequestHandler>( this ) );
+	return RV_CONTINUE_ASYNC;
+}
+
+void bw::ResourceRequestHandler::OnResourceLoadComplete(
+	CefRefPtr<CefBrowser> browser,
+	CefRefPtr<CefFrame> frame,
+	CefRefPtr<CefRequest> request,
+	CefRefPtr<CefResponse> response,
+	URLRequestStatus status,
+	int64_t received_content_length
+) {
+	if ( browser.get() == nullptr || status != UR_SUCCESS ) { return; }
+
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( browser );
+	if ( !bw_info_opt.has_value() || !bw_info_opt.value()->listening_for_requests ) {
+		return;
+	}
+
+	auto data = new ResponseData;
+	data->browser = browser;
+	data->url = request->GetURL().ToString();
+	data->method = request->GetMethod().ToString();
+	data->status = (uint16_t)response->GetStatus();
+
+	CefResponse::HeaderMap headers;
+	response->GetHeaderMap( headers );
+	for ( auto it = headers.begin(); it != headers.end(); it++ ) {
+		data->headers.push_back( std::make_pair( it->first.ToString(), it->second.ToString() ) );
+	}
+
+	bw_Application_dispatch( this->app, _bw_ResourceRequestHandler_dispatchResponse, data );
+}
+
+void bw::ResourceRequestHandler::handle() {
+	// The browser window may have been closed in the meantime
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( this->browser );
+	if ( bw_info_opt.has_value() ) {
+		std::string url = this->request->GetURL().ToString();
+		std::string method = this->request->GetMethod().ToString();
+
+		CefRequest::HeaderMap header_map;
+		this->request->GetHeaderMap( header_map );
+		std::vector<std::pair<std::string, std::string>> header_strings;
+		for ( auto it = header_map.begin(); it != header_map.end(); it++ ) {
+			header_strings.push_back( std::make_pair( it->first.ToString(), it->second.ToString() ) );
+		}
+		std::vector<bw_HttpHeader> headers = _bw_ResourceRequestHandler_toHttpHeaders( header_strings );
+
+		bw_BrowserWindowRequest args = {
+			{ url.length(), url.c_str() },
+			{ method.length(), method.c_str() },
+			headers.data(),
+			headers.size(),
+			(void*)this
+		};
+		bw_Event_fire( &bw_info_opt.value()->handle->events.on_request, (void*)&args );
+	}
+
+	if ( this->blocked ) {
+		this->callback->Cancel();
+	}
+	else {
+		// Changing the URL makes CEF redirect the request
+		if ( !this->redirect_url.empty() ) {
+			this->request->SetURL( this->redirect_url );
+		}
+		this->callback->Continue();
+	}
+
+	this->browser = nullptr;
+	this->request = nullptr;
+	this->callback = nullptr;
+}
+
+void bw::ResourceRequestHandler::block() {
+	this->blocked = true;
+}
+
+void bw::ResourceRequestHandler::redirect( const std::string& url ) {
+	this->redirect_url = url;
+}
+
+void bw::ResourceRequestHandler::removeHeader( const std::string& name ) {
+	std::string lowercase_name = _bw_ResourceRequestHandler_lowercase( name );
+
+	CefRequest::HeaderMap headers;
+	this->request->GetHeaderMap( headers );
+	for ( auto it = headers.begin(); it != headers.end(); ) {
+		if ( _bw_ResourceRequestHandler_lowercase( it->first.ToString() ) == lowercase_name )
+			it = headers.erase( it );
+		else
+			it++;
+	}
+	this->request->SetHeaderMap( headers );
+}
+
+void bw::ResourceRequestHandler::respond( const bw_SchemeResponse* response ) {
+	// `GetResourceHandler` will hand it over to CEF
+	this->response = new SchemeResourceHandler();
+	this->response->respond( response );
+}
+
+void bw::ResourceRequestHandler::setHeader( const std::string& name, const std::string& value ) {
+	this->request->SetHeaderByName( name, value, true );
+}
+
+void _bw_ResourceRequestHandler_dispatchRequest( bw_Application* app, void* data ) {
+	auto handler = (CefRefPtr<bw::ResourceRequestHandler>*)data;
+
+	(*handler)->handle();
+	delete handler;
+}
+
+void _bw_ResourceRequestHandler_dispatchResponse( bw_Application* app, void* _data ) {
+	auto data = (ResponseData*)_data;
+
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch( data->browser );
+	if ( bw_info_opt.has_value() ) {
+		std::vector<bw_HttpHeader> headers = _bw_ResourceRequestHandler_toHttpHeaders( data->headers );
+
+		bw_BrowserWindowResponse args = {
+			{ data->url.length(), data->url.c_str() },
+			{ data->method.length(), data->method.c_str() },
+			data->status,
+			headers.data(),
+			headers.size()
+		};
+		bw_Event_fire( &bw_info_opt.value()->handle->events.on_response, (void*)&args );
+	}
+
+	delete data;
+}
+
+std::string _bw_ResourceRequestHandler_lowercase( std::string string ) {
+	std::transform( string.begin(), string.end(), string.begin(), ::tolower );
+	return string;
+}
+
+std::vector<bw_HttpHeader> _bw_ResourceRequestHandler_toHttpHeaders( const std::vector<std::pair<std::string, std::string>>& headers ) {
+	std::vector<bw_HttpHeader> result;
+	for ( auto it = headers.begin(); it != headers.end(); it++ ) {
+		bw_HttpHeader header = {
+			{ it->first.length(), it->first.c_str() },
+			{ it->second.length(), it->second.c_str() }
+		};
+		result.push_back( header );
+	}
+	return result;
+}
+
+
+
+void bw_BrowserWindowRequest_block( bw_BrowserWindowRequest* request ) {
+	((bw::ResourceRequestHandler*)request->impl)->block();
+}
+
+void bw_BrowserWindowRequest_redirect( bw_BrowserWindowRequest* request, bw_CStrSlice url ) {
+	((bw::ResourceRequestHandler*)request->impl)->redirect( std::string( url.data, url.len ) );
+}
+
+void bw_BrowserWindowRequest_removeHeader( bw_BrowserWindowRequest* request, bw_CStrSlice name ) {
+	((bw::ResourceRequestHandler*)request->impl)->removeHeader( std::string( name.data, name.len ) );
+}
+
+void bw_BrowserWindowRequest_respond( bw_BrowserWindowRequest* request, const bw_SchemeResponse* response ) {
+	((bw::ResourceRequestHandler*)request->impl)->respond( response );
+}
+
+void bw_BrowserWindowRequest_setHeader( bw_BrowserWindowRequest* request, bw_CStrSlice name, bw_CStrSlice value ) {
+	((bw::ResourceRequestHandler*)request->impl)->setHeader( std::string( name.data, name.len ), std::string( value.data, value.len ) );
+}
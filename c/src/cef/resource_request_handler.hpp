@@ -0,0 +1,70 @@
+#ifndef BW_CEF_RESOURCE_REQUEST_HANDLER_H
+#define BW_CEF_RESOURCE_REQUEST_HANDLER_H
+
+#include "scheme_handler.hpp"
+#include "../application.h"
+#include "../browser_window.h"
+
+#include <include/cef_resource_request_handler.h>
+#include <string>
+
+
+
+namespace bw {
+
+	// Lets the `on_request` and `on_response` events of a browser window see the requests that its page makes.
+	// The events are fired on the GUI thread, while CEF waits for them on its IO thread.
+	class ResourceRequestHandler : public CefResourceRequestHandler {
+		bw_Application* app;
+		CefRefPtr<CefBrowser> browser;
+		CefRefPtr<CefRequest> request;
+		CefRefPtr<CefCallback> callback;
+
+		bool blocked;
+		std::string redirect_url;
+		CefRefPtr<SchemeResourceHandler> response;
+
+	public:
+		ResourceRequestHandler( bw_Application* app ) : app(app), blocked(false) {}
+
+		ReturnValue OnBeforeResourceLoad(
+			CefRefPtr<CefBrowser> browser,
+			CefRefPtr<CefFrame> frame,
+			CefRefPtr<CefRequest> request,
+			CefRefPtr<CefCallback> callback
+		) override;
+
+		CefRefPtr<CefResourceHandler> GetResourceHandler(
+			CefRefPtr<CefBrowser> browser,
+			CefRefPtr<CefFrame> frame,
+			CefRefPtr<CefRequest> request
+		) override {
+			return this->response;
+		}
+
+		void OnResourceLoadComplete(
+			CefRefPtr<CefBrowser> browser,
+			CefRefPtr<CefFrame> frame,
+			CefRefPtr<CefRequest> request,
+			CefRefPtr<CefResponse> response,
+			URLRequestStatus status,
+			int64_t received_content_length
+		) override;
+
+		// Fires the `on_request` event, should be called on the GUI thread.
+		void handle();
+
+		void block();
+		void redirect( const std::string& url );
+		void removeHeader( const std::string& name );
+		void respond( const bw_SchemeResponse* response );
+		void setHeader( const std::string& name, const std::string& value );
+
+	protected:
+		IMPLEMENT_REFCOUNTING(ResourceRequestHandler);
+	};
+}
+
+
+
+#endif//BW_CEF_RESOURCE_REQUEST_HANDLER_H
@@ -0,0 +1,139 @@
+#include "scheme_handler.hpp"
+
+#include <algorithm>
+#include <cstring>
+#include <string>
+
+
+
+void _bw_SchemeResourceHandler_dispatch( bw_Application* app, void* data );
+
+
+
+bool bw::SchemeResourceHandler::Open( CefRefPtr<CefRequest> request, bool& handle_request, CefRefPtr<CefCallback> callback ) {
+	// Without a handler, the response is already there
+	if ( this->handler == nullptr ) {
+		handle_request = true;
+		return true;
+	}
+
+	this->url = request->GetURL().ToString();
+	this->method = request->GetMethod().ToString();
+
+	CefRequest::HeaderMap headers;
+	request->GetHeaderMap( headers );
+	for ( auto it = headers.begin(); it != headers.end(); it++ ) {
+		this->request_headers.push_back( std::make_pair( it->first.ToString(), it->second.ToString() ) );
+	}
+
+	CefRefPtr<CefPostData> post_data = request->GetPostData();
+	if ( post_data.get() != nullptr ) {
+		CefPostData::ElementVector elements;
+		post_data->GetElements( elements );
+		for ( auto it = elements.begin(); it != elements.end(); it++ ) {
+			if ( (*it)->GetType() != PDE_TYPE_BYTES ) { continue; }
+
+			size_t offset = this->request_body.size();
+			this->request_body.resize( offset + (*it)->GetBytesCount() );
+			(*it)->GetBytes( (*it)->GetBytesCount(), &this->request_body[offset] );
+		}
+	}
+
+	// The response will be provided asynchronously, after the handler has been invoked on the GUI thread
+	handle_request = false;
+	this->callback = callback;
+	bw_Application_dispatch( this->app, _bw_SchemeResourceHandler_dispatch, new CefRefPtr<SchemeResourceHandler>( this ) );
+	return true;
+}
+
+void bw::SchemeResourceHandler::GetResponseHeaders( CefRefPtr<CefResponse> response, int64_t& response_length, CefString& redirect_url ) {
+	response->SetStatus( this->status );
+	response->SetMimeType( this->mime_type );
+	response->SetHeaderMap( this->response_headers );
+	response_length = this->response_body.size();
+}
+
+bool bw::SchemeResourceHandler::Read( void* data_out, int bytes_to_read, int& bytes_read, CefRefPtr<CefResourceReadCallback> callback ) {
+	size_t remaining = this->response_body.size() - this->offset;
+	if ( remaining == 0 || bytes_to_read <= 0 ) {
+		bytes_read = 0;
+		return false;
+	}
+
+	size_t count = std::min( remaining, (size_t)bytes_to_read );
+	memcpy( data_out, &this->response_body[this->offset], count );
+	this->offset += count;
+	bytes_read = (int)count;
+	return true;
+}
+
+void bw::SchemeResourceHandler::handle() {
+	std::vector<bw_HttpHeader> headers;
+	for ( auto it = this->request_headers.begin(); it != this->request_headers.end(); it++ ) {
+		bw_HttpHeader header = {
+			{ it->first.length(), it->first.c_str() },
+			{ it->second.length(), it->second.c_str() }
+		};
+		headers.push_back( header );
+	}
+
+	bw_SchemeRequest request = {
+		{ this->url.length(), this->url.c_str() },
+		{ this->method.length(), this->method.c_str() },
+		headers.data(),
+		headers.size(),
+		this->request_body.data(),
+		this->request_body.size(),
+		(void*)this
+	};
+	this->handler( this->handler_data, &request );
+
+	this->callback->Continue();
+	this->callback = nullptr;
+}
+
+void bw::SchemeResourceHandler::respond( const bw_SchemeResponse* response ) {
+	this->status = response->status;
+
+	for ( size_t i = 0; i < response->header_count; i++ ) {
+		const bw_HttpHeader& header = response->headers[i];
+		std::string name( header.name.data, header.name.len );
+		std::string value( header.value.data, header.value.len );
+
+		// CEF wants the mime type to be set seperately, without its parameters
+		std::string lowercase_name = name;
+		std::transform( lowercase_name.begin(), lowercase_name.end(), lowercase_name.begin(), ::tolower );
+		if ( lowercase_name == "content-type" ) {
+			this->mime_type = value.substr( 0, value.find( ';' ) );
+		}
+
+		this->response_headers.insert( std::make_pair( CefString( name ), CefString( value ) ) );
+	}
+
+	this->response_body.assign( response->body, response->body + response->body_len );
+}
+
+void bw::registerSchemeHandlers( bw_Application* app, const bw_ApplicationSettings* settings ) {
+	for ( size_t i = 0; i < settings->scheme_count; i++ ) {
+		const bw_Scheme& scheme = settings->schemes[i];
+
+		CefRegisterSchemeHandlerFactory(
+			std::string( scheme.name.data, scheme.name.len ),
+			"",
+			new SchemeHandlerFactory( app, scheme.handler, scheme.data )
+		);
+	}
+}
+
+void _bw_SchemeResourceHandler_dispatch( bw_Application* app, void* data ) {
+	auto handler = (CefRefPtr<bw::SchemeResourceHandler>*)data;
+
+	(*handler)->handle();
+	delete handler;
+}
+
+
+
+void bw_SchemeRequest_respond( bw_SchemeRequest* request, const bw_SchemeResponse* response ) {
+	((bw::SchemeResourceHandler*)request->impl)->respond( response );
+}
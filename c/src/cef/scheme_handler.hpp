@@ -0,0 +1,83 @@
+#ifndef BW_CEF_SCHEME_HANDLER_H
+#define BW_CEF_SCHEME_HANDLER_H
+
+#include "../application.h"
+#include "../scheme.h"
+
+#include <include/cef_resource_handler.h>
+#include <include/cef_scheme.h>
+#include <string>
+#include <utility>
+#include <vector>
+
+
+
+namespace bw {
+
+	// Serves a request made to a custom scheme, by invoking the scheme's handler on the GUI thread.
+	class SchemeResourceHandler : public CefResourceHandler {
+		bw_Application* app;
+		bw_SchemeHandlerFn handler;
+		void* handler_data;
+		CefRefPtr<CefCallback> callback;
+
+		std::string url;
+		std::string method;
+		std::vector<std::pair<std::string, std::string>> request_headers;
+		std::vector<uint8_t> request_body;
+
+		int status;
+		std::string mime_type;
+		CefResponse::HeaderMap response_headers;
+		std::vector<uint8_t> response_body;
+		size_t offset;
+
+	public:
+		SchemeResourceHandler( bw_Application* app, bw_SchemeHandlerFn handler, void* handler_data ) :
+			app(app), handler(handler), handler_data(handler_data), status(500), offset(0) {}
+		// Serves whatever response is given to `respond`, without invoking any handler.
+		SchemeResourceHandler() :
+			app(nullptr), handler(nullptr), handler_data(nullptr), status(500), offset(0) {}
+
+		bool Open( CefRefPtr<CefRequest> request, bool& handle_request, CefRefPtr<CefCallback> callback ) override;
+		void GetResponseHeaders( CefRefPtr<CefResponse> response, int64_t& response_length, CefString& redirect_url ) override;
+		bool Read( void* data_out, int bytes_to_read, int& bytes_read, CefRefPtr<CefResourceReadCallback> callback ) override;
+		void Cancel() override {}
+
+		// Invokes the handler of the scheme, should be called on the GUI thread.
+		void handle();
+		void respond( const bw_SchemeResponse* response );
+
+	protected:
+		IMPLEMENT_REFCOUNTING(SchemeResourceHandler);
+	};
+
+	class SchemeHandlerFactory : public CefSchemeHandlerFactory {
+		bw_Application* app;
+		bw_SchemeHandlerFn handler;
+		void* handler_data;
+
+	public:
+		SchemeHandlerFactory( bw_Application* app, bw_SchemeHandlerFn handler, void* handler_data ) :
+			app(app), handler(handler), handler_data(handler_data) {}
+
+		CefRefPtr<CefResourceHandler> Create(
+			CefRefPtr<CefBrowser> browser,
+			CefRefPtr<CefFrame> frame,
+			const CefString& scheme_name,
+			CefRefPtr<CefRequest> request
+		) override {
+			return new SchemeResourceHandler( this->app, this->handler, this->handler_data );
+		}
+
+	protected:
+		IMPLEMENT_REFCOUNTING(SchemeHandlerFactory);
+	};
+
+	// Registers a handler factory for every custom scheme found in the settings.
+	void registerSchemeHandlers( bw_Application* app, const bw_ApplicationSettings* settings );
+}
+
+
+
+#endif//BW_CEF_SCHEME_HANDLER_H
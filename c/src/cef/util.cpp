@@ -45,4 +45,12 @@ bw_StrSlice bw_cef_copyToStrSlice(const CefString& string) {
 	slice.data = data;
 	slice.len = len;
 	return slice;
-}
\ No newline at end of file
+}
+
+int64_t bw_cef_toUnixTime(const CefBaseTime& time) {
+	cef_time_t exploded;
+	time_t unix_time;
+	if (!cef_time_from_basetime(time, &exploded) || !cef_time_to_timet(&exploded, &unix_time))
+		return 0;
+	return (int64_t)unix_time;
+}
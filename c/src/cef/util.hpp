@@ -4,12 +4,16 @@
 #include "../string.h"
 
 #include <include/internal/cef_string.h>
+#include <include/internal/cef_time_wrappers.h>
+#include <stdint.h>
 
 
 CefString bw_cef_copyFromStrSlice( bw_CStrSlice slice );
 size_t bw_cef_copyToCstr( const CefString& cef_string, char** cstr );
 bw_CStrSlice bw_cef_copyToCStrSlice(const CefString& string);
 bw_StrSlice bw_cef_copyToStrSlice(const CefString& string);
+/// Converts the time into a unix timestamp, or 0 if it isn't valid.
+int64_t bw_cef_toUnixTime(const CefBaseTime& time);
 
 
 #endif//BW_CEF_H
\ No newline at end of file
@@ -1,6 +1,9 @@
 #ifndef BW_CEF_V8_TO_STRING_HPP
 #define BW_CEF_V8_TO_STRING_HPP
 
+#include <cmath>
+#include <cstdio>
+#include <cstdlib>
 #include <string>
 #include <vector>
 
@@ -8,8 +11,8 @@
 class V8ToString {
 public:
 
-	// Convert a javascript value into a string, for very basic compatibility purposes with the Rust application
-	// Note: This function is not yet complete. Not all types are converted appropriately.
+	// Convert a javascript value into a javascript literal, which is parsed by `JsValue::from_string` on the Rust side.
+	// Note: Values that have no literal representation, like functions, are converted to a bare identifier.
 	static CefString convert( CefRefPtr<CefV8Value> val ) {
 
 		// If undefined
@@ -21,13 +24,8 @@ public:
 			return "null";
 
 		// If string
-		if ( val->IsString() ) {
-			std::string string = "\"";
-			// TODO: Escape the string value:
-			string += val->GetStringValue();
-			string += "\"";
-			return string;
-		}
+		if ( val->IsString() )
+			return escapeString( val->GetStringValue() );
 
 		// If boolean
 		if ( val->IsBool() )
@@ -41,9 +39,9 @@ public:
 		if ( val->IsUInt() )
 			return intoString( val->GetUIntValue() );
 
-		// If unsigned integer
+		// If double
 		if ( val->IsDouble() )
-			return intoString( val->GetDoubleValue() );
+			return doubleToString( val->GetDoubleValue() );
 
 		// If array
 		if ( val->IsArray() ) {
@@ -68,7 +66,7 @@ public:
 				if (i != 0) {
 					string += ",";
 				}
-				string += key + ":" + convert(val->GetValue(i)).ToString();
+				string += escapeString( key ) + ":" + convert(val->GetValue(keys[i])).ToString();
 			}
 			string += "}";
 			return string;
@@ -81,8 +79,8 @@ public:
 		if ( val->IsFunction() )
 			return "function";
 
-		// If type is not accounted for, return this string:
-		return "unknown type";
+		// If type is not accounted for, return this identifier:
+		return "unknown";
 	}
 
 protected:
@@ -94,6 +92,44 @@ protected:
 		return "false";
 	}
 
+	// Formats the double with the least amount of digits needed to get the exact same value back.
+	static CefString doubleToString( double value ) {
+		if ( std::isnan( value ) )
+			return "NaN";
+		if ( std::isinf( value ) )
+			return value > 0 ? "Infinity" : "-Infinity";
+
+		char buffer[32];
+		snprintf( buffer, sizeof( buffer ), "%.15g", value );
+		if ( strtod( buffer, nullptr ) != value )
+			snprintf( buffer, sizeof( buffer ), "%.17g", value );
+		return CefString( std::string( buffer ) );
+	}
+
+	// Encloses the string in double quotes, and escapes all characters that need to be escaped.
+	static std::string escapeString( const std::string& input ) {
+		std::string string = "\"";
+		for ( char c : input ) {
+			switch ( c ) {
+			case '"':  string += "\\\""; break;
+			case '\\': string += "\\\\"; break;
+			case '\n': string += "\\n"; break;
+			case '\r': string += "\\r"; break;
+			case '\t': string += "\\t"; break;
+			default:
+				if ( (unsigned char)c < 0x20 ) {
+					char buffer[8];
+					snprintf( buffer, sizeof( buffer ), "\\x%02x", (unsigned char)c );
+					string += buffer;
+				}
+				else
+					string += c;
+			}
+		}
+		string += "\"";
+		return string;
+	}
+
 	template <class V>
 	static CefString intoString( const V& value ) {
 		std::string str = std::to_string( value );
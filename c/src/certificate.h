@@ -0,0 +1,28 @@
+#ifndef BW_CERTIFICATE_H
+#define BW_CERTIFICATE_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#include "bool.h"
+#include "string.h"
+
+#include <stddef.h>
+#include <stdint.h>
+
+
+
+/// Decodes the base64 in `input` into `output`, which has room for `*output_len` bytes.
+/// `*output_len` is set to the number of bytes that have been decoded.
+/// Returns `FALSE` if `input` isn't valid base64, or if it doesn't fit into `output`.
+/// Not supported for Edge WebView2, for which it always returns `FALSE`.
+BOOL bw_decodeBase64( bw_CStrSlice input, uint8_t* output, size_t* output_len );
+
+
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif//BW_CERTIFICATE_H
@@ -0,0 +1,15 @@
+#include "../certificate.h"
+
+#include <string>
+#include <include/cef_parser.h>
+
+
+
+BOOL bw_decodeBase64( bw_CStrSlice input, uint8_t* output, size_t* output_len ) {
+	CefRefPtr<CefBinaryValue> decoded = CefBase64Decode( std::string( input.data, input.len ) );
+	if ( decoded == nullptr || decoded->GetSize() > *output_len )
+		return FALSE;
+
+	*output_len = decoded->GetData( output, *output_len, 0 );
+	return TRUE;
+}
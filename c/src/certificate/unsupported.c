@@ -0,0 +1,11 @@
+#include "../certificate.h"
+
+#include "../common.h"
+
+
+BOOL bw_decodeBase64( bw_CStrSlice input, uint8_t* output, size_t* output_len ) {
+	UNUSED(input);
+	UNUSED(output);
+	*output_len = 0;
+	return FALSE;
+}
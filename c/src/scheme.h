@@ -0,0 +1,62 @@
+#ifndef BW_SCHEME_H
+#define BW_SCHEME_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#include "string.h"
+
+#include <stddef.h>
+#include <stdint.h>
+
+
+
+typedef struct {
+	bw_CStrSlice name;
+	bw_CStrSlice value;
+} bw_HttpHeader;
+
+/// A request that has been made to a custom scheme.
+/// All data is owned by the browser engine, and is only valid while the `bw_SchemeHandlerFn` is being invoked.
+typedef struct {
+	bw_CStrSlice url;
+	bw_CStrSlice method;
+	const bw_HttpHeader* headers;
+	size_t header_count;
+	const uint8_t* body;
+	size_t body_len;
+	void* impl;
+} bw_SchemeRequest;
+
+typedef struct {
+	uint16_t status;
+	const bw_HttpHeader* headers;
+	size_t header_count;
+	const uint8_t* body;
+	size_t body_len;
+} bw_SchemeResponse;
+
+/// Handles a request made to a custom scheme, and is always invoked on the GUI thread.
+/// It should call `bw_SchemeRequest_respond` before it returns.
+typedef void (*bw_SchemeHandlerFn)( void* data, bw_SchemeRequest* request );
+
+typedef struct {
+	bw_CStrSlice name;
+	bw_SchemeHandlerFn handler;
+	void* data;
+} bw_Scheme;
+
+
+
+/// Sends the response back to the browser engine.
+/// The response data is copied, so it doesn't need to outlive this call.
+void bw_SchemeRequest_respond( bw_SchemeRequest* request, const bw_SchemeResponse* response );
+
+
+
+#ifdef __cplusplus
+} // extern "C"
+#endif
+
+#endif//BW_SCHEME_H
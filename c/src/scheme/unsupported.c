@@ -0,0 +1,9 @@
+#include "../scheme.h"
+
+#include "../common.h"
+
+
+void bw_SchemeRequest_respond( bw_SchemeRequest* request, const bw_SchemeResponse* response ) {
+	UNUSED(request);
+	UNUSED(response);
+}
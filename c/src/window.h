@@ -7,6 +7,7 @@ extern "C" {
 
 #include "application.h"
 #include "common.h"
+#include "event.h"
 #include "string.h"
 
 #include <stdbool.h>
@@ -22,11 +23,58 @@ typedef struct bw_WindowOptions {
 	bool keep_above;
 	bool minimizable;
 	bool resizable;
+	bool center;	// Centers the window on its parent, or on the screen if it has no parent. Overrides `x` and `y`.
+	bool fullscreen;
+	bool has_position;	// Whether or not `x` and `y` should be used
+	bool maximized;
+	bool visible;	// Shows the window right after it has been created
+	// The position relative to the screen, which can be negative on setups with multiple monitors
+	int32_t x;
+	int32_t y;
+	bw_Dims2D min_size;	// A width or height of 0 means that there is no minimum
+	bw_Dims2D max_size;	// A width or height of 0 means that there is no maximum
 } bw_WindowOptions;
 
 typedef void (*bw_WindowDispatchFn)( bw_Window* window, void* data );
 typedef struct bw_WindowDispatchData bw_WindowDispatchData;
 
+/// The events of a window.
+/// The handler of `on_close_requested` returns `TRUE` to keep the window open.
+/// `on_moved` gets a `bw_Pos2D`, `on_resized` a `bw_Dims2D`, and the other events a `BOOL`.
+typedef struct {
+	bw_Event on_close_requested;
+	bw_Event on_focus_changed;
+	bw_Event on_maximized_changed;
+	bw_Event on_minimized_changed;
+	bw_Event on_moved;
+	bw_Event on_resized;
+	bw_Event on_visibility_changed;
+} bw_WindowEvents;
+
+#define BW_FILE_DIALOG_OPEN 0
+#define BW_FILE_DIALOG_OPEN_MULTIPLE 1
+#define BW_FILE_DIALOG_OPEN_FOLDER 2
+#define BW_FILE_DIALOG_SAVE 3
+
+/// A type of files that can be chosen in a file dialog.
+typedef struct {
+	bw_CStrSlice name;
+	const bw_CStrSlice* patterns;	// Glob patterns like `*.png`, or MIME types like `image/png`
+	size_t pattern_count;
+} bw_FileFilter;
+
+typedef struct {
+	unsigned int mode;	// One of the `BW_FILE_DIALOG_*` values
+	bw_CStrSlice title;
+	bw_CStrSlice default_path;	// Empty if there is none
+	const bw_FileFilter* filters;
+	size_t filter_count;
+} bw_FileDialogOptions;
+
+/// Receives the chosen paths, of which there are none if the dialog has been cancelled.
+/// The paths are only valid during the call.
+typedef void (*bw_FileDialogCallbackFn)( void* data, const bw_CStrSlice* paths, size_t path_count );
+
 
 #if defined(BW_WIN32)
 #include "window/win32.h"
@@ -46,8 +94,11 @@ struct bw_Window {
 	const bw_Window* parent;	// An optional window that acts as the parent to this window. If the parent gets destroyed, children will get destroyed too.
 	bool closed;	// Whether or not the window has been closed already
 	bool dropped;	// Whether or not the window may be destroyed when it is actually closed
+	bool pending_maximize;	// Whether or not to maximize the window once it gets shown for the first time
+	bool pending_fullscreen;	// Whether or not to go fullscreen once the window gets shown for the first time
 	void* user_data;	// TODO: Put the user_data ptr on bw_BrowserWindow
 	bw_BrowserWindow* browser;
+	bw_WindowEvents events;
 	bw_WindowImpl impl;	// Data for the implementation of the window
 };
 
@@ -61,6 +112,16 @@ void bw_Window_free(bw_Window* window);
 /// Frees the user data that was attached to this window.
 void bw_Window_freeUserData(bw_Window* window);
 
+/// Shows a file dialog on top of the window, and invokes `callback` once it has been closed.
+/// `window` may be NULL. The options are only used during this call.
+/// Returns FALSE if the dialog can't be shown, in which case `callback` is never invoked.
+/// CEF can only show dialogs for browsers, so for other windows it uses the browser of any browser window, and fails if there is none.
+/// Win32 doesn't implement file dialogs yet.
+BOOL bw_Window_fileDialog( bw_Window* window, const bw_FileDialogOptions* options, bw_FileDialogCallbackFn callback, void* data );
+
+/// Brings the window to the front and gives it the keyboard focus.
+void bw_Window_focus( bw_Window* window );
+
 /// Gets the width and height of the usable area inside the window.
 bw_Dims2D bw_Window_getContentDimensions( bw_Window* window );
 
@@ -82,10 +143,19 @@ void bw_Window_hide( bw_Window* window );
 
 void* bw_Window_innerHandle(bw_Window* window);
 
+/// Returns whether or not the window covers the whole screen.
+bool bw_Window_isFullscreen( bw_Window* window );
+
 /// Returns whether or not the window is not hidden.
 /// `bw_Window_show` and `bw_Window_hide` change the visibility.
 bool bw_Window_isVisible( const bw_Window* window );
 
+/// Maximizes the window.
+void bw_Window_maximize( bw_Window* window );
+
+/// Minimizes the window.
+void bw_Window_minimize( bw_Window* window );
+
 /// Creates a new (empty) window
 /// The returned pointer is a handler for the window.
 /// bw_Window_drop needs to be called on it after it is done being used,
@@ -98,13 +168,26 @@ bw_Window* bw_Window_new(
 	const bw_WindowOptions* options
 );
 
+/// Restores the window to its normal size, if it was minimized or maximized.
+void bw_Window_restore( bw_Window* window );
+
 
+/// Resizes the window so that its client area gets the given dimensions.
 void bw_Window_setContentDimensions( bw_Window* window, bw_Dims2D dimensions );
 
+/// Makes the window cover the whole screen, or restores it from that.
+void bw_Window_setFullscreen( bw_Window* window, bool fullscreen );
+
+/// Sets whether or not the window stays on top of other windows.
+void bw_Window_setKeepAbove( bw_Window* window, bool keep_above );
+
 void bw_Window_setOpacity( bw_Window* window, uint8_t opacity );
 
 void bw_Window_setPosition( bw_Window* window, bw_Pos2D position );
 
+/// Sets whether or not the window can be resized by the user.
+void bw_Window_setResizable( bw_Window* window, bool resizable );
+
 void bw_Window_setUserData(bw_Window* bw, void* user_data);
 
 /// Applies the given title;
@@ -1,23 +1,61 @@
 #include "../window.h"
 
+#include "../browser_window.h"
+#include "../cef/bw_handle_map.hpp"
 #include "../cef/util.hpp"
 #include "../common.h"
 
+#include <climits>
+#include <string>
+#include <vector>
 #include <include/cef_base.h>
+#include <include/cef_browser.h>
 #include <include/views/cef_window.h>
 
+#ifdef BW_WINDOWS
+#define WIN32_LEAN_AND_MEAN
+#include <Windows.h>
+#endif
+
+// X11 headers, when used by CEF
+#if defined(CEF_X11)
+#include <X11/Xlib.h>
+#include <X11/Xutil.h>
+#endif
+
 
 
 class MyWindowDelegate : public CefWindowDelegate {
 	bw_Window* window;
 	bw_WindowOptions options;
+	// The last known state of the window, so that only actual changes are reported.
+	CefRect bounds;
+	bool maximized = false;
+	bool minimized = false;
+	// Set when the window is closed by `bw_WindowImpl_close`, rather than by the user.
+	bool closing = false;
 
 public:
 	MyWindowDelegate( bw_Window* window, const bw_WindowOptions& options ) : window(window), options(options) {}
 
+	void SetClosing() {
+		this->closing = true;
+	}
+
+	const bw_WindowOptions& GetOptions() const {
+		return this->options;
+	}
+
+	void SetResizable( bool resizable ) {
+		this->options.resizable = resizable;
+	}
+
 	bool CanClose( CefRefPtr<CefWindow> window ) override {
 		UNUSED( window );
-		return true;
+		// Only the user gets to be asked whether the window can close.
+		if (this->closing)
+			return true;
+		return !bw_Event_fire(&this->window->events.on_close_requested, NULL);
 	}
 
 	bool CanMaximize( CefRefPtr<CefWindow> window ) override {
@@ -35,6 +73,24 @@ public:
 		return this->options.resizable;
 	}
 
+	CefSize GetMaximumSize( CefRefPtr<CefView> view ) override {
+		UNUSED( view );
+		// CEF takes an empty size to mean that there is no maximum, but a size is already empty when only one of its dimensions is 0.
+		// So a dimension without a maximum gets one that no window can reach, as the dimensions are only 16 bits wide on the Rust side anyway.
+		bw_Dims2D max_size = this->options.max_size;
+		if ( max_size.width == 0 && max_size.height == 0 )
+			return CefSize( 0, 0 );
+		return CefSize(
+			max_size.width != 0 ? max_size.width : 65535,
+			max_size.height != 0 ? max_size.height : 65535
+		);
+	}
+
+	CefSize GetMinimumSize( CefRefPtr<CefView> view ) override {
+		UNUSED( view );
+		return CefSize( this->options.min_size.width, this->options.min_size.height );
+	}
+
 	CefRefPtr<CefWindow> GetParentWindow( CefRefPtr<CefWindow> window, bool* is_menu, bool* can_activate_menu ) override {
 		UNUSED( window );
 		UNUSED( is_menu );
@@ -58,8 +114,47 @@ public:
 		return false;
 	}
 
-	void OnWindowCreated( CefRefPtr<CefWindow> window ) override {
+	void OnWindowActivationChanged( CefRefPtr<CefWindow> window, bool active ) override {
 		UNUSED( window );
+
+		BOOL focused = active;
+		bw_Event_fire(&this->window->events.on_focus_changed, (void*)&focused);
+	}
+
+	void OnWindowBoundsChanged( CefRefPtr<CefWindow> window, const CefRect& new_bounds ) override {
+		CefRect old_bounds = this->bounds;
+		this->bounds = new_bounds;
+
+		if (new_bounds.x != old_bounds.x || new_bounds.y != old_bounds.y) {
+			bw_Pos2D position;
+			position.x = new_bounds.x;
+			position.y = new_bounds.y;
+			bw_Event_fire(&this->window->events.on_moved, (void*)&position);
+		}
+		if (new_bounds.width != old_bounds.width || new_bounds.height != old_bounds.height) {
+			bw_Dims2D dimensions;
+			dimensions.width = new_bounds.width;
+			dimensions.height = new_bounds.height;
+			bw_Event_fire(&this->window->events.on_resized, (void*)&dimensions);
+		}
+
+		// CEF has no separate notification for (un)minimizing or (un)maximizing, but they change the bounds.
+		bool minimized = window->IsMinimized();
+		if (minimized != this->minimized) {
+			this->minimized = minimized;
+			BOOL arg = minimized;
+			bw_Event_fire(&this->window->events.on_minimized_changed, (void*)&arg);
+		}
+		bool maximized = window->IsMaximized();
+		if (maximized != this->maximized) {
+			this->maximized = maximized;
+			BOOL arg = maximized;
+			bw_Event_fire(&this->window->events.on_maximized_changed, (void*)&arg);
+		}
+	}
+
+	void OnWindowCreated( CefRefPtr<CefWindow> window ) override {
+		this->bounds = window->GetBounds();
 	}
 
 	void OnWindowDestroyed( CefRefPtr<CefWindow> window ) override {
@@ -100,6 +195,23 @@ extern "C" bw_WindowImpl bw_WindowImpl_new(
 	window->SetSize(size);
 	window->SetTitle(bw_cef_copyFromStrSlice(_title));
 
+	// The window is still hidden, so it doesn't show up at the default position first.
+	if (options->center) {
+		if (_window->parent != NULL) {
+			CefRect parent_bounds = (*(CefRefPtr<CefWindow>*)_window->parent->impl.handle_ptr)->GetBounds();
+			CefSize actual_size = window->GetSize();
+			window->SetPosition(CefPoint(
+				parent_bounds.x + (parent_bounds.width - actual_size.width) / 2,
+				parent_bounds.y + (parent_bounds.height - actual_size.height) / 2
+			));
+		}
+		else
+			window->CenterWindow(window->GetSize());
+	}
+	else if (options->has_position) {
+		window->SetPosition(CefPoint(options->x, options->y));
+	}
+
 	bw_WindowImpl impl;
 	impl.handle_ptr = new CefRefPtr<CefWindow>( window );
 	return impl;
@@ -107,6 +219,7 @@ extern "C" bw_WindowImpl bw_WindowImpl_new(
 
 extern "C" void bw_WindowImpl_close( bw_WindowImpl* window ) {
 	auto window_ptr = (CefRefPtr<CefWindow>*)window->handle_ptr;
+	static_cast<MyWindowDelegate*>((*window_ptr)->GetDelegate().get())->SetClosing();
 	(*window_ptr)->Close();
 	delete window_ptr;
 }
@@ -125,6 +238,78 @@ extern "C" void* bw_WindowImpl_innerHandle(bw_WindowImpl* window) {
 
 
 
+class FileDialogCallback : public CefRunFileDialogCallback {
+	bw_FileDialogCallbackFn callback;
+	void* data;
+
+public:
+	FileDialogCallback( bw_FileDialogCallbackFn callback, void* data ) : callback(callback), data(data) {}
+
+	void OnFileDialogDismissed( const std::vector<CefString>& file_paths ) override {
+		std::vector<std::string> paths;
+		for (const CefString& path : file_paths)
+			paths.push_back(path.ToString());
+		std::vector<bw_CStrSlice> slices;
+		for (const std::string& path : paths)
+			slices.push_back({ path.length(), path.c_str() });
+
+		this->callback(this->data, slices.data(), slices.size());
+	}
+
+	IMPLEMENT_REFCOUNTING(FileDialogCallback);
+};
+
+BOOL bw_Window_fileDialog( bw_Window* window, const bw_FileDialogOptions* options, bw_FileDialogCallbackFn callback, void* data ) {
+	CefRefPtr<CefBrowser> cef_browser;
+	if (window != NULL && window->browser != NULL)
+		cef_browser = *(CefRefPtr<CefBrowser>*)window->browser->impl.cef_ptr;
+	// CEF only runs file dialogs for browsers, so the one of any browser window has to do
+	else {
+		std::optional<bw::BrowserInfo*> any = bw::bw_handle_map.any();
+		if (!any.has_value())
+			return FALSE;
+		cef_browser = *(CefRefPtr<CefBrowser>*)any.value()->handle->impl.cef_ptr;
+	}
+
+	// Each filter becomes something like "Images|.png;.jpg", and MIME types are passed on by themselves.
+	std::vector<CefString> accept_filters;
+	for (size_t i = 0; i < options->filter_count; i++) {
+		const bw_FileFilter& filter = options->filters[i];
+		std::string extensions;
+		for (size_t j = 0; j < filter.pattern_count; j++) {
+			std::string pattern(filter.patterns[j].data, filter.patterns[j].len);
+			if (pattern.find('/') != std::string::npos)
+				accept_filters.push_back(pattern);
+			else {
+				if (pattern.rfind("*", 0) == 0)
+					pattern.erase(0, 1);
+				if (!extensions.empty())
+					extensions += ";";
+				extensions += pattern;
+			}
+		}
+		if (!extensions.empty())
+			accept_filters.push_back(std::string(filter.name.data, filter.name.len) + "|" + extensions);
+	}
+
+	std::optional<bw::BrowserInfo*> bw_info_opt = bw::bw_handle_map.fetch(cef_browser);
+	if (bw_info_opt.has_value())
+		bw_info_opt.value()->own_file_dialog = true;
+
+	cef_browser->GetHost()->RunFileDialog(
+		(CefBrowserHost::FileDialogMode)options->mode,
+		std::string(options->title.data, options->title.len),
+		std::string(options->default_path.data, options->default_path.len),
+		accept_filters,
+		new FileDialogCallback(callback, data)
+	);
+	return TRUE;
+}
+
+void bw_Window_focus( bw_Window* window ) {
+	(*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->Activate();
+}
+
 bw_Dims2D bw_Window_getContentDimensions( bw_Window* window ) {
 	CefRect rect = (*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->GetClientAreaBoundsInScreen();
 
@@ -162,13 +347,41 @@ bw_Dims2D bw_Window_getWindowDimensions( bw_Window* window ) {
 	return dims;
 }
 
+bool bw_Window_isFullscreen( bw_Window* window ) {
+	return (*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->IsFullscreen();
+}
+
+void bw_Window_maximize( bw_Window* window ) {
+	(*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->Maximize();
+}
+
+void bw_Window_minimize( bw_Window* window ) {
+	(*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->Minimize();
+}
+
+void bw_Window_restore( bw_Window* window ) {
+	(*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->Restore();
+}
+
 void bw_Window_setContentDimensions( bw_Window* window, bw_Dims2D dims ) {
-	// FIXME: This doesn't work quite yet...
-	CefRefPtr<CefView> browser_view = (*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->GetChildViewAt(0);
-	
-	CefRect rect(0, 0, dims.width, dims.height);
-	browser_view->SetBounds(rect);
-	(*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->Layout();
+	CefRefPtr<CefWindow> cef_window = *(CefRefPtr<CefWindow>*)window->impl.handle_ptr;
+
+	// The frame takes up the difference between the bounds of the window and those of its client area.
+	CefRect bounds = cef_window->GetBoundsInScreen();
+	CefRect client_area = cef_window->GetClientAreaBoundsInScreen();
+	CefSize size(
+		dims.width + bounds.width - client_area.width,
+		dims.height + bounds.height - client_area.height
+	);
+	cef_window->SetSize( size );
+}
+
+void bw_Window_setFullscreen( bw_Window* window, bool fullscreen ) {
+	(*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->SetFullscreen(fullscreen);
+}
+
+void bw_Window_setKeepAbove( bw_Window* window, bool keep_above ) {
+	(*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->SetAlwaysOnTop(keep_above);
 }
 
 void bw_Window_setPosition( bw_Window* window, bw_Pos2D position ) {
@@ -177,6 +390,60 @@ void bw_Window_setPosition( bw_Window* window, bw_Pos2D position ) {
 	(*(CefRefPtr<CefWindow>*)window->impl.handle_ptr)->SetPosition(point);
 }
 
+void bw_Window_setResizable( bw_Window* window, bool resizable ) {
+	CefRefPtr<CefWindow> cef_window = *(CefRefPtr<CefWindow>*)window->impl.handle_ptr;
+	MyWindowDelegate* delegate = static_cast<MyWindowDelegate*>(cef_window->GetDelegate().get());
+	delegate->SetResizable(resizable);
+
+	// CEF asks the delegate whether the window can be resized, but only when it creates the window.
+	// So the native window needs to be changed as well.
+#if defined(BW_WINDOWS)
+	HWND handle = cef_window->GetWindowHandle();
+	LONG_PTR style = GetWindowLongPtrW( handle, GWL_STYLE );
+	if ( resizable )
+		style |= WS_SIZEBOX | WS_MAXIMIZEBOX;
+	else
+		style &= ~( WS_SIZEBOX | WS_MAXIMIZEBOX );
+	SetWindowLongPtrW( handle, GWL_STYLE, style );
+	// The frame is only redrawn with the new style after this
+	SetWindowPos( handle, 0, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED );
+#elif defined(CEF_X11)
+	// X11 has no notion of resizability, but window managers don't let the user resize a window that has the same minimum and maximum size.
+	Display* display = XOpenDisplay( NULL );
+	if ( display == NULL )
+		return;
+	XSizeHints* hints = XAllocSizeHints();
+	long supplied;
+	Window handle = cef_window->GetWindowHandle();
+	XGetWMNormalHints( display, handle, hints, &supplied );
+
+	if ( resizable ) {
+		const bw_WindowOptions& options = delegate->GetOptions();
+		hints->flags &= ~( PMinSize | PMaxSize );
+		if ( options.min_size.width != 0 || options.min_size.height != 0 ) {
+			hints->flags |= PMinSize;
+			hints->min_width = options.min_size.width;
+			hints->min_height = options.min_size.height;
+		}
+		if ( options.max_size.width != 0 || options.max_size.height != 0 ) {
+			hints->flags |= PMaxSize;
+			hints->max_width = options.max_size.width != 0 ? options.max_size.width : INT_MAX;
+			hints->max_height = options.max_size.height != 0 ? options.max_size.height : INT_MAX;
+		}
+	}
+	else {
+		CefRect client_area = cef_window->GetClientAreaBoundsInScreen();
+		hints->flags |= PMinSize | PMaxSize;
+		hints->min_width = hints->max_width = client_area.width;
+		hints->min_height = hints->max_height = client_area.height;
+	}
+
+	XSetWMNormalHints( display, handle, hints );
+	XFree( hints );
+	XCloseDisplay( display );
+#endif
+}
+
 void bw_Window_setTitle( bw_Window* window, bw_CStrSlice _title ) {
 	CefString title = bw_cef_copyFromStrSlice( _title );
 
@@ -34,9 +34,15 @@ const bw_Application* bw_Window_getApp( bw_Window* window ) {
 }
 
 void bw_Window_hide( bw_Window* window ) {
+	bool was_visible = !window->closed;
 	window->closed = true;
 
 	bw_WindowImpl_hide( &window->impl );
+
+	if (was_visible) {
+		BOOL visible = FALSE;
+		bw_Event_fire(&window->events.on_visibility_changed, (void*)&visible);
+	}
 }
 
 void* bw_Window_innerHandle(bw_Window* window) {
@@ -62,18 +68,41 @@ bw_Window* bw_Window_new(
 	window->parent = parent;
 	window->closed = true;  // Windows start out hidden to the user
 	window->dropped = false;
+	// Maximizing a hidden window would show it on some platforms, so it is done once the window gets shown.
+	window->pending_maximize = options->maximized;
+	window->pending_fullscreen = options->fullscreen;
 	window->user_data = NULL;
 	window->browser = NULL;
+	memset(&window->events, 0, sizeof(bw_WindowEvents));
 
 	window->impl = bw_WindowImpl_new( window, title, width, height, options );
 
 	app->windows_alive += 1;
 
+	if (options->visible) {
+		bw_Window_show(window);
+	}
+
 	return window;
 }
 
 void bw_Window_show( bw_Window* window ) {
+	bool was_visible = !window->closed;
 	window->closed = false;
 
 	bw_WindowImpl_show( &window->impl );
+
+	if (window->pending_maximize) {
+		window->pending_maximize = false;
+		bw_Window_maximize(window);
+	}
+	if (window->pending_fullscreen) {
+		window->pending_fullscreen = false;
+		bw_Window_setFullscreen(window, true);
+	}
+
+	if (!was_visible) {
+		BOOL visible = TRUE;
+		bw_Event_fire(&window->events.on_visibility_changed, (void*)&visible);
+	}
 }
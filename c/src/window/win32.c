@@ -27,6 +27,19 @@ void bw_WindowWin32_calculatePositionCentered( int width, int height, int* x, in
 
 
 
+BOOL bw_Window_fileDialog( bw_Window* window, const bw_FileDialogOptions* options, bw_FileDialogCallbackFn callback, void* data ) {
+	UNUSED( window );
+	UNUSED( options );
+	UNUSED( callback );
+	UNUSED( data );
+	// TODO: Implement with IFileDialog
+	return FALSE;
+}
+
+void bw_Window_focus( bw_Window* window ) {
+	SetForegroundWindow( window->impl.handle );
+}
+
 bw_Dims2D bw_Window_getContentDimensions( bw_Window* window ) {
 	bw_Dims2D dims;
 
@@ -92,6 +105,22 @@ bw_Dims2D bw_Window_getWindowDimensions( bw_Window* window ) {
    return dims;
 }
 
+bool bw_Window_isFullscreen( bw_Window* window ) {
+	return window->impl.fullscreen;
+}
+
+void bw_Window_maximize( bw_Window* window ) {
+	ShowWindow( window->impl.handle, SW_MAXIMIZE );
+}
+
+void bw_Window_minimize( bw_Window* window ) {
+	ShowWindow( window->impl.handle, SW_MINIMIZE );
+}
+
+void bw_Window_restore( bw_Window* window ) {
+	ShowWindow( window->impl.handle, SW_RESTORE );
+}
+
 void bw_Window_setContentDimensions( bw_Window* window, bw_Dims2D dimensions ) {
 	RECT rect;
 	rect.left = 0; rect.right = dimensions.width;
@@ -109,6 +138,43 @@ void bw_Window_setContentDimensions( bw_Window* window, bw_Dims2D dimensions ) {
 		BW_WIN32_PANIC_LAST_ERROR
 }
 
+void bw_Window_setFullscreen( bw_Window* window, bool fullscreen ) {
+	if ( fullscreen == window->impl.fullscreen )
+		return;
+
+	HWND handle = window->impl.handle;
+	LONG_PTR style = GetWindowLongPtrW( handle, GWL_STYLE );
+
+	// Remove the frame and cover the monitor that the window is on
+	if ( fullscreen ) {
+		MONITORINFO monitor;
+		monitor.cbSize = sizeof( MONITORINFO );
+		GetWindowPlacement( handle, &window->impl.placement );
+		GetMonitorInfoW( MonitorFromWindow( handle, MONITOR_DEFAULTTONEAREST ), &monitor );
+
+		SetWindowLongPtrW( handle, GWL_STYLE, style & ~WS_OVERLAPPEDWINDOW );
+		SetWindowPos(
+			handle, HWND_TOP,
+			monitor.rcMonitor.left, monitor.rcMonitor.top,
+			monitor.rcMonitor.right - monitor.rcMonitor.left,
+			monitor.rcMonitor.bottom - monitor.rcMonitor.top,
+			SWP_NOOWNERZORDER | SWP_FRAMECHANGED
+		);
+	}
+	else {
+		SetWindowLongPtrW( handle, GWL_STYLE, style | ( window->impl.style & WS_OVERLAPPEDWINDOW ) );
+		SetWindowPlacement( handle, &window->impl.placement );
+		SetWindowPos( handle, NULL, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOOWNERZORDER | SWP_FRAMECHANGED );
+	}
+
+	window->impl.fullscreen = fullscreen;
+}
+
+void bw_Window_setKeepAbove( bw_Window* window, bool keep_above ) {
+	if ( !SetWindowPos( window->impl.handle, keep_above ? HWND_TOPMOST : HWND_NOTOPMOST, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE ) )
+		BW_WIN32_PANIC_LAST_ERROR
+}
+
 void bw_Window_setOpacity( bw_Window* window, uint8_t opacity ) {
 	
 	if ( !SetLayeredWindowAttributes( window->impl.handle, 0, opacity, LWA_ALPHA ) )
@@ -122,6 +188,21 @@ void bw_Window_setPosition( bw_Window* window, bw_Pos2D position ) {
 		BW_WIN32_PANIC_LAST_ERROR
 }
 
+void bw_Window_setResizable( bw_Window* window, bool resizable ) {
+	if ( resizable )
+		window->impl.style |= WS_SIZEBOX | WS_MAXIMIZEBOX;
+	else
+		window->impl.style &= ~( WS_SIZEBOX | WS_MAXIMIZEBOX );
+
+	// While in fullscreen mode, the style gets applied when leaving it
+	if ( !window->impl.fullscreen ) {
+		LONG_PTR style = GetWindowLongPtrW( window->impl.handle, GWL_STYLE );
+		style = ( style & ~( WS_SIZEBOX | WS_MAXIMIZEBOX ) ) | ( window->impl.style & ( WS_SIZEBOX | WS_MAXIMIZEBOX ) );
+		SetWindowLongPtrW( window->impl.handle, GWL_STYLE, style );
+		SetWindowPos( window->impl.handle, NULL, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_FRAMECHANGED );
+	}
+}
+
 void bw_Window_setTitle( bw_Window* window, bw_CStrSlice _title ) {
 	WCHAR* title = bw_win32_copyAsNewWstr( _title );
 
@@ -163,6 +244,11 @@ bw_WindowImpl bw_WindowImpl_new(
 	const bw_WindowOptions* options
 ) {
 	bw_WindowImpl impl;
+	impl.minimized = FALSE;
+	impl.maximized = FALSE;
+	impl.fullscreen = FALSE;
+	impl.min_size = options->min_size;
+	impl.max_size = options->max_size;
 
 	impl.style = options->decorated ? WS_OVERLAPPEDWINDOW : 0;
 
@@ -197,6 +283,29 @@ bw_WindowImpl bw_WindowImpl_new(
 	if ( impl.handle == NULL ) {
 		BW_WIN32_PANIC_LAST_ERROR;
 	}
+
+	// The window is still hidden, so it doesn't show up at the default position first.
+	if ( options->center ) {
+		RECT rect;
+		GetWindowRect( impl.handle, &rect );
+		int actual_width = rect.right - rect.left;
+		int actual_height = rect.bottom - rect.top;
+
+		int x, y;
+		if ( window->parent != NULL ) {
+			RECT parent_rect;
+			GetWindowRect( window->parent->impl.handle, &parent_rect );
+			x = parent_rect.left + ( parent_rect.right - parent_rect.left - actual_width ) / 2;
+			y = parent_rect.top + ( parent_rect.bottom - parent_rect.top - actual_height ) / 2;
+		}
+		else
+			bw_WindowWin32_calculatePositionCentered( actual_width, actual_height, &x, &y );
+
+		SetWindowPos( impl.handle, 0, x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER );
+	}
+	else if ( options->has_position ) {
+		SetWindowPos( impl.handle, 0, options->x, options->y, 0, 0, SWP_NOSIZE | SWP_NOZORDER );
+	}
 	
 	// Store a pointer to our window handle in win32's window handle
 	SetWindowLongPtrW( impl.handle, GWLP_USERDATA, (LONG_PTR)window );
@@ -222,10 +331,56 @@ LRESULT CALLBACK bw_Window_proc(HWND hwnd, UINT msg, WPARAM wp, LPARAM lp) {
 			RECT rect;
 			GetClientRect( window->impl.handle, &rect );
 			bw_WindowWin32_onResize(window, rect.left, rect.right, rect.top, rect.bottom);
+
+			BOOL minimized = wp == SIZE_MINIMIZED;
+			if (minimized != window->impl.minimized) {
+				window->impl.minimized = minimized;
+				bw_Event_fire(&window->events.on_minimized_changed, (void*)&minimized);
+			}
+			BOOL maximized = wp == SIZE_MAXIMIZED;
+			if (maximized != window->impl.maximized) {
+				window->impl.maximized = maximized;
+				bw_Event_fire(&window->events.on_maximized_changed, (void*)&maximized);
+			}
+			if (!minimized) {
+				bw_Dims2D dimensions = bw_Window_getWindowDimensions(window);
+				bw_Event_fire(&window->events.on_resized, (void*)&dimensions);
+			}
+			break;
+		}
+		case WM_MOVE: {
+			// Not available yet while the window is being created
+			if (window != NULL) {
+				// The position in `lp` is that of the client area, so use the same position as `bw_Window_getPosition`.
+				bw_Pos2D position = bw_Window_getPosition(window);
+				bw_Event_fire(&window->events.on_moved, (void*)&position);
+			}
+			break;
+		}
+		case WM_GETMINMAXINFO: {
+			// Also sent before the window pointer is available
+			if (window != NULL) {
+				MINMAXINFO* info = (MINMAXINFO*)lp;
+				if (window->impl.min_size.width > 0) info->ptMinTrackSize.x = window->impl.min_size.width;
+				if (window->impl.min_size.height > 0) info->ptMinTrackSize.y = window->impl.min_size.height;
+				if (window->impl.max_size.width > 0) info->ptMaxTrackSize.x = window->impl.max_size.width;
+				if (window->impl.max_size.height > 0) info->ptMaxTrackSize.y = window->impl.max_size.height;
+			}
+			break;
+		}
+		case WM_SETFOCUS:
+		case WM_KILLFOCUS: {
+			if (window != NULL) {
+				BOOL focused = msg == WM_SETFOCUS;
+				bw_Event_fire(&window->events.on_focus_changed, (void*)&focused);
+			}
 			break;
 		}
 		// Triggered when a user closes the window
 		case WM_CLOSE: {
+			if (bw_Event_fire(&window->events.on_close_requested, NULL)) {
+				break;
+			}
 			if (window->user_data != NULL) {
 				bw_Window_freeUserData(window);
 				window->user_data = NULL;
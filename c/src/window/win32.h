@@ -7,6 +7,7 @@ extern "C" {
 
 #include "../win32.h"
 #include "../bool.h"
+#include "../common.h"
 
 
 typedef struct bw_Window bw_Window;
@@ -25,6 +26,12 @@ typedef struct {
 	BOOL closed;
 	DWORD style;
 	BYTE opacity;
+	BOOL minimized;
+	BOOL maximized;
+	BOOL fullscreen;
+	WINDOWPLACEMENT placement;	// The placement to restore when leaving fullscreen mode
+	bw_Dims2D min_size;
+	bw_Dims2D max_size;
 } bw_WindowImpl;
 
 
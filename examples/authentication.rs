@@ -1,7 +1,7 @@
 //! This is an example that demonstrates how you can get the session cookie data
 //! of a site after logging in.
 
-use std::time::Duration;
+use std::{env, time::Duration};
 
 use browser_window::{application::*, browser::*};
 
@@ -19,6 +19,17 @@ fn main() {
 		let bw = bwb.build_async(&app).await;
 		bw.show();
 
+		// If a proxy or server asks for a username and password, take them from the
+		// environment. If they have been rejected, asking again won't help.
+		bw.on_auth_credentials().register(|_, e| {
+			if e.is_retry {
+				return None;
+			}
+			let username = env::var("BW_USERNAME").ok()?;
+			let password = env::var("BW_PASSWORD").ok()?;
+			Some(Credentials::new(&username, &password))
+		});
+
 		let cookie_jar = app.cookie_jar().expect("cookies not supported");
 
 		// Wait until we moved away from the login page
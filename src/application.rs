@@ -93,7 +93,13 @@ use lazy_static::lazy_static;
 
 #[cfg(feature = "threadsafe")]
 use crate::delegate::*;
-use crate::{cookie::CookieJar, core::application::*, error};
+use crate::{
+	cookie::CookieJar,
+	core::application::*,
+	dialog::{self, FileDialogError, FileDialogMode, FileDialogOptions},
+	error,
+	scheme::Scheme,
+};
 
 /// Use this to initialize and start your application with.
 pub struct Application {
@@ -145,6 +151,15 @@ pub struct ApplicationSettings {
 	pub resource_dir: Option<PathBuf>,
 	/// CEF only: If set, will enable remote debugging at this port.
 	pub remote_debugging_port: Option<u16>,
+	/// Custom URL schemes of which all requests are served by a Rust handler.
+	/// See the [`scheme`](crate::scheme) module.
+	/// Implemented for CEF and WebkitGTK; Edge WebView2 fails to initialize
+	/// with any schemes set.
+	pub schemes: Vec<Scheme>,
+	/// Set with `trust_ca_certificate`.
+	pub(crate) trusted_ca_certificates: Vec<String>,
+	/// Set with `pin_certificate`.
+	pub(crate) pinned_certificates: Vec<(String, String)>,
 }
 
 // The trait to be implemented by all (user-level) handles that are able to
@@ -255,6 +270,28 @@ impl ApplicationSettings {
 
 		path
 	}
+
+	/// Trusts the given certificate in PEM format for the given host, no
+	/// matter who issued it, like the self-signed certificate of an on-premise
+	/// server.
+	/// Only the issuer is overlooked: the certificate is still rejected when
+	/// it has expired, or when it is meant for another host.
+	/// See the [`certificate`](../certificate/index.html) module.
+	/// Edge WebView2 fails to initialize with any certificates pinned.
+	pub fn pin_certificate(&mut self, host: &str, pem: &str) -> &mut Self {
+		self.pinned_certificates
+			.push((host.to_owned(), pem.to_owned()));
+		self
+	}
+
+	/// WebkitGTK only: Trusts the given CA certificate in PEM format, in
+	/// addition to the ones that the system trusts.
+	/// For the other browser frameworks, `Application::initialize` fails if
+	/// any are given.
+	pub fn trust_ca_certificate(&mut self, pem: &str) -> &mut Self {
+		self.trusted_ca_certificates.push(pem.to_owned());
+		self
+	}
 }
 
 impl Runtime {
@@ -368,7 +405,7 @@ impl From<ApplicationHandle> for Application {
 
 impl ApplicationHandle {
 	/// Returns an instance of a `CookieJar`, if the underlying browser
-	/// framework supports it. Currently, only CEF supports cookies.
+	/// framework supports it. Currently, CEF and WebKitGTK support cookies.
 	pub fn cookie_jar(&self) -> Option<CookieJar> { CookieJar::global() }
 
 	pub(crate) unsafe fn clone(&self) -> Self {
@@ -377,6 +414,35 @@ impl ApplicationHandle {
 		}
 	}
 
+	/// Shows a dialog to open files with, that doesn't belong to any window.
+	/// See [`WindowHandle::open_file`](crate::window::WindowHandle::open_file).
+	pub async fn open_file(
+		&self, options: &FileDialogOptions,
+	) -> Result<Vec<PathBuf>, FileDialogError> {
+		let mode = if options.multiple {
+			FileDialogMode::OpenMultiple
+		} else {
+			FileDialogMode::Open
+		};
+		dialog::show(None, mode, options).await
+	}
+
+	/// Like `open_file`, but for choosing a folder.
+	pub async fn pick_folder(
+		&self, options: &FileDialogOptions,
+	) -> Result<Option<PathBuf>, FileDialogError> {
+		let paths = dialog::show(None, FileDialogMode::OpenFolder, options).await?;
+		Ok(paths.into_iter().next())
+	}
+
+	/// Like `open_file`, but for choosing where to save a file.
+	pub async fn save_file(
+		&self, options: &FileDialogOptions,
+	) -> Result<Option<PathBuf>, FileDialogError> {
+		let paths = dialog::show(None, FileDialogMode::Save, options).await?;
+		Ok(paths.into_iter().next())
+	}
+
 	/// Causes the `Runtime` to terminate.
 	/// The `Runtime`'s [`Runtime::run`] or spawn command will return the exit
 	/// code provided. This will mean that not all tasks might complete.
@@ -6,9 +6,24 @@
 //! `BrowserWindow`, but the methods of `WindowHandle` are not displayed.
 //! Be sure to check them out [here](../window/struct.WindowHandle.html).
 
-#[cfg(feature = "threadsafe")]
-use std::future::Future;
-use std::{borrow::Cow, ops::Deref};
+use std::{
+	any::Any,
+	borrow::Cow,
+	cell::RefCell,
+	collections::HashMap,
+	convert::TryFrom,
+	error::Error as StdError,
+	fmt,
+	future::Future,
+	io,
+	mem::ManuallyDrop,
+	ops::Deref,
+	path::PathBuf,
+	process::Command,
+	ptr,
+	sync::{Arc, Mutex},
+	thread,
+};
 
 use futures_channel::oneshot;
 #[cfg(feature = "threadsafe")]
@@ -19,16 +34,24 @@ use crate::delegate::*;
 use crate::{
 	HasHandle,
 	application::*,
+	certificate::{Certificate, CertificateError, ClientCertificate},
 	core::{
 		browser_window::{
 			BrowserWindowEventExt, BrowserWindowExt, BrowserWindowImpl, JsEvaluationError,
 		},
-		window::WindowExt,
+		window::{WindowExt, WindowUserData},
 	},
-	decl_browser_event, decl_event,
-	event::EventHandler,
+	decl_browser_event, decl_event, def_browser_event, def_event,
+	dialog::FileDialogMode,
+	event::{
+		EventHandler, EventHandlers, HandlerCell, SharedEventHandler, SharedEventHandlers,
+		Subscription,
+	},
+	javascript::JsConversionError,
+	key::{Modifiers, ParseShortcutError, Shortcut},
 	prelude::*,
-	rc::Rc,
+	rc::{MaybeSend, Rc, Weak},
+	scheme::{SchemeResponse, find_header},
 	window::*,
 };
 
@@ -47,7 +70,13 @@ pub type BrowserDelegateFuture<'a, R> = DelegateFuture<'a, BrowserWindow, Browse
 /// by the user. If the window has been closed by the user but this handle still
 /// exists, the window is actually just been closed. It can be reshown by
 /// calling `show` on this handle.
-pub struct BrowserWindowOwner(pub(super) BrowserWindowHandle);
+/// Its fields are only dropped on the GUI thread, see `Drop`.
+pub struct BrowserWindowOwner(
+	pub(super) ManuallyDrop<BrowserWindowHandle>,
+	pub(super) ManuallyDrop<RefCell<Handlers>>,
+	/// The owner of the window, which has the handlers of the window events.
+	pub(super) ManuallyDrop<Window>,
+);
 #[derive(Clone)]
 pub struct BrowserWindow(pub(super) Rc<BrowserWindowOwner>);
 #[cfg(feature = "threadsafe")]
@@ -86,6 +115,8 @@ pub struct BrowserWindow(pub(super) Rc<BrowserWindowOwner>);
 #[derive(Clone)]
 pub struct BrowserWindowThreaded(BrowserWindow);
 #[cfg(feature = "threadsafe")]
+unsafe impl Send for BrowserWindowThreaded {}
+#[cfg(feature = "threadsafe")]
 unsafe impl Sync for BrowserWindowThreaded {}
 
 pub type BrowserWindowEventHandler<A> = EventHandler<BrowserWindowHandle, BrowserWindow, A>;
@@ -99,25 +130,329 @@ pub struct BrowserWindowHandle {
 
 pub struct MessageEventArgs {
 	pub cmd: String,
+	/// The arguments that were given after the command.
+	/// Use `arg` to convert one of them into a Rust type.
+	/// With feature `serde` enabled, they can also be turned into your own
+	/// types with [`from_value`](../javascript/fn.from_value.html).
 	pub args: Vec<JsValue>,
+	pub(crate) responder: Option<Responder>,
+}
+
+/// The argument of `on_auth_credentials`.
+pub struct AuthCredentialsEventArgs {
+	pub host: String,
+	pub port: u16,
+	pub realm: String,
+	/// The authentication scheme in lowercase, like `basic` or `digest`.
+	pub scheme: String,
+	/// Whether it is a proxy that asks for the credentials, rather than the
+	/// server itself.
+	pub is_proxy: bool,
+	/// Whether the credentials that have been given for the same host and realm
+	/// before were rejected.
+	pub is_retry: bool,
+	pub(crate) answer: Option<Answer<Option<Credentials>>>,
+}
+
+/// The argument of `on_certificate_error`.
+pub struct CertificateErrorEventArgs {
+	/// The URL of the request that the certificate has been presented for.
+	pub url: String,
+	pub error: CertificateError,
+	pub certificate: Certificate,
+	pub(crate) answer: Option<Answer<bool>>,
+}
+
+/// The argument of `on_select_client_certificate`.
+pub struct SelectClientCertificateEventArgs {
+	pub host: String,
+	pub port: u16,
+	/// Whether it is a proxy that asks for the certificate, rather than the
+	/// server itself.
+	pub is_proxy: bool,
+	/// The certificates of the system's store that the server would accept.
+	/// Always empty for WebkitGTK.
+	pub candidates: Vec<Certificate>,
+	pub(crate) answer: Option<Answer<Option<ClientCertificate>>>,
+}
+
+/// The argument of `on_download_started`.
+pub struct DownloadStartedEventArgs {
+	/// Identifies the download in the other download events.
+	pub id: u32,
+	/// The URL that the download has been started with.
+	pub url: String,
+	pub suggested_filename: String,
+	pub mime_type: String,
+	/// The size of the file in bytes, if known.
+	pub total_size: Option<u64>,
+	pub(crate) answer: Option<Answer<Option<PathBuf>>>,
+}
+
+/// The argument of `on_download_progress`.
+#[derive(Clone, Debug)]
+pub struct DownloadProgressEventArgs {
+	pub id: u32,
+	pub url: String,
+	/// The number of bytes that have been received so far.
+	pub received: u64,
+	pub total_size: Option<u64>,
+}
+
+/// The argument of `on_download_finished`.
+#[derive(Clone, Debug)]
+pub struct DownloadFinishedEventArgs {
+	pub id: u32,
+	pub url: String,
+	/// Where the file has been saved.
+	pub path: PathBuf,
+	pub result: Result<(), DownloadError>,
+}
+
+/// The reason why a download hasn't finished.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DownloadError {
+	/// The download has been cancelled, by a handler of `on_download_started`
+	/// or otherwise.
+	Cancelled,
+	/// The download has failed, with a message that describes why.
+	Failed(String),
+}
+
+/// The argument of `on_file_dialog`.
+pub struct FileDialogEventArgs {
+	/// Never `OpenFolder` for WebkitGTK, which doesn't tell whether the page
+	/// wants a folder.
+	pub mode: FileDialogMode,
+	/// Always empty for WebkitGTK.
+	pub title: String,
+	/// Always `None` for WebkitGTK.
+	pub default_path: Option<PathBuf>,
+	/// The types of files that the page accepts, like `image/png` or `.png`.
+	/// If empty, any file is accepted.
+	pub accept: Vec<String>,
+	pub(crate) answer: Option<Answer<Vec<PathBuf>>>,
+}
+
+type DownloadSender = oneshot::Sender<Result<(), DownloadError>>;
+
+/// The downloads that have been started with `BrowserWindowHandle::download`.
+#[derive(Default)]
+struct Downloads {
+	/// The request that has been given to the browser last.
+	last_request: u32,
+	/// The destinations of the downloads that the browser hasn't started yet,
+	/// by their request.
+	pending: HashMap<u32, (PathBuf, DownloadSender)>,
+	/// The downloads that are in progress, by their ID.
+	active: HashMap<u32, DownloadSender>,
+}
+
+/// A username and password to authenticate with.
+#[derive(Clone, Debug)]
+pub struct Credentials {
+	pub username: String,
+	pub password: String,
+}
+
+/// An entry of the back/forward list of a browser window.
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+	/// The position of the entry in the list, starting at 0 for the oldest one.
+	pub index: usize,
+	pub title: String,
+	pub url: String,
+	/// Whether or not this is the page that is currently loaded.
+	pub current: bool,
+}
+
+/// The argument of `on_key_press` and `on_key_pressed`.
+/// All handlers of the same key share whether it has been consumed.
+#[derive(Clone)]
+pub struct KeyEventArgs {
+	/// The Windows virtual-key code of the key, see the
+	/// [`key`](../key/index.html) module. It is 0 for keys without one.
+	pub key_code: u32,
+	pub modifiers: Modifiers,
+	/// Whether the key is being held down, and this is a repeated press.
+	pub is_repeat: bool,
+	/// The character that the key produces, if any.
+	pub character: Option<char>,
+	consumed: Arc<Mutex<bool>>,
+}
+
+/// The argument of `on_navigation_request`.
+/// All handlers of the same request share the same policy, so a handler can see
+/// what the handlers before it have decided.
+#[derive(Clone)]
+pub struct NavigationRequestEventArgs {
+	pub url: String,
+	/// Whether the navigation happens in the main frame, or in an iframe.
+	/// WebkitGTK doesn't tell which frame navigates, so it is always `true`
+	/// there, and can't be trusted.
+	pub is_main_frame: bool,
+	/// Whether or not the navigation was initiated by the user, e.g. by
+	/// clicking a link.
+	pub user_gesture: bool,
+	pub navigation_type: NavigationType,
+	policy: Arc<Mutex<NavigationPolicy>>,
+}
+
+/// What to do with a navigation that is about to happen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationPolicy {
+	Allow,
+	Cancel,
+	/// Cancels the navigation, and opens the URL in the default browser of the
+	/// system instead.
+	OpenExternally,
+}
+
+/// The kind of navigation that is about to happen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationType {
+	BackForward,
+	FormSubmission,
+	Link,
+	Reload,
+	Other,
+}
+
+/// The argument of `on_request`.
+/// All handlers of the same request share the same changes, so a handler can
+/// see what the handlers before it have decided.
+#[derive(Clone)]
+pub struct RequestEventArgs {
+	pub url: String,
+	pub method: String,
+	/// The headers that the request was going to be sent with, before any
+	/// handler changed them.
+	pub headers: Vec<(String, String)>,
+	changes: Arc<Mutex<RequestChanges>>,
+}
+
+/// What to do with a request that is about to be sent.
+#[derive(Clone, Debug)]
+pub enum RequestAction {
+	/// Sends the request, with any headers that have been changed.
+	Continue,
+	Block,
+	/// Sends the request to the given URL instead.
+	Redirect(String),
+	/// Doesn't send the request, but answers it with the given response.
+	Respond(SchemeResponse),
+}
+
+#[derive(Clone)]
+struct RequestChanges {
+	action: RequestAction,
+	/// The headers to set, or to remove if there is no value.
+	headers: Vec<(String, Option<String>)>,
+}
+
+/// The argument of `on_response`.
+#[derive(Clone, Debug)]
+pub struct ResponseEventArgs {
+	pub url: String,
+	/// The method of the request that has been answered.
+	pub method: String,
+	pub status: u16,
+	pub headers: Vec<(String, String)>,
+}
+
+/// Settles the promise that has been returned by `invoke_extern`.
+/// If it gets dropped without having responded, the promise resolves to
+/// `undefined`.
+pub(crate) struct Responder {
+	owner: Weak<BrowserWindowOwner>,
+	id: u32,
+	settled: bool,
+}
+#[cfg(feature = "threadsafe")]
+unsafe impl Send for Responder {}
+
+/// Answers a question that the browser has asked, like which credentials to
+/// use. If it gets dropped without having answered, the default value is given
+/// as the answer.
+pub(crate) struct Answer<T: Default>(Option<Box<dyn FnOnce(T)>>);
+#[cfg(feature = "threadsafe")]
+unsafe impl<T: Default> Send for Answer<T> {}
+
+/// Event arguments that carry the answer to a question of the browser.
+/// Their events only take handlers that answer, so that the answer can't end up
+/// with a handler that doesn't.
+trait Answerable {
+	type Answer: Default + 'static;
+
+	fn take_answer(&mut self) -> Option<Answer<Self::Answer>>;
+}
+
+/// The event handlers of a browser window, which are dropped together with its
+/// owner.
+pub(super) struct Handlers {
+	auth_credentials:
+		SharedEventHandlers<BrowserWindowHandle, BrowserWindow, AuthCredentialsEventArgs>,
+	certificate_errors:
+		SharedEventHandlers<BrowserWindowHandle, BrowserWindow, CertificateErrorEventArgs>,
+	client_certificates:
+		SharedEventHandlers<BrowserWindowHandle, BrowserWindow, SelectClientCertificateEventArgs>,
+	downloads_finished:
+		SharedEventHandlers<BrowserWindowHandle, BrowserWindow, DownloadFinishedEventArgs>,
+	download_progress:
+		SharedEventHandlers<BrowserWindowHandle, BrowserWindow, DownloadProgressEventArgs>,
+	downloads_started:
+		SharedEventHandlers<BrowserWindowHandle, BrowserWindow, DownloadStartedEventArgs>,
+	downloads: Downloads,
+	file_dialogs: SharedEventHandlers<BrowserWindowHandle, BrowserWindow, FileDialogEventArgs>,
+	/// The handlers that the messages for bound functions are dispatched to.
+	bindings:
+		HashMap<String, SharedEventHandler<BrowserWindowHandle, BrowserWindow, MessageEventArgs>>,
+	/// The handlers of `on_invoke`, which settle the promise of
+	/// `invoke_extern`.
+	invocations: SharedEventHandlers<BrowserWindowHandle, BrowserWindow, MessageEventArgs>,
+	key_presses: SharedEventHandlers<BrowserWindowHandle, BrowserWindow, KeyEventArgs>,
+	keys_pressed: SharedEventHandlers<BrowserWindowHandle, BrowserWindow, KeyEventArgs>,
+	/// The handlers of the shortcuts that have been added with `add_shortcut`.
+	shortcuts: Vec<(
+		Shortcut,
+		SharedEventHandler<BrowserWindowHandle, BrowserWindow, KeyEventArgs>,
+	)>,
+	/// The handlers that all other messages of `invoke_extern` are dispatched
+	/// to.
+	messages: SharedEventHandlers<BrowserWindowHandle, BrowserWindow, MessageEventArgs>,
+	/// The origins that the main frame is allowed to navigate to. If empty,
+	/// all origins are allowed.
+	allowed_origins: Vec<String>,
+	navigation_requests:
+		SharedEventHandlers<BrowserWindowHandle, BrowserWindow, NavigationRequestEventArgs>,
+	requests: SharedEventHandlers<BrowserWindowHandle, BrowserWindow, RequestEventArgs>,
+	/// The handler lists of the other events.
+	/// The backends only keep weak references to them.
+	#[allow(dead_code)]
+	events: Vec<Rc<dyn Any>>,
 }
 
 decl_browser_event!(AddressChangedEvent);
 decl_browser_event!(AuthCredentialsEvent);
 decl_browser_event!(CertificateErrorEvent);
 decl_browser_event!(ConsoleMessageEvent);
+decl_browser_event!(DownloadFinishedEvent);
 decl_browser_event!(DownloadProgressEvent);
 decl_browser_event!(DownloadStartedEvent);
 decl_browser_event!(FaviconChangedEvent);
 decl_browser_event!(FileDialogEvent);
 decl_browser_event!(FullscreenModeChangedEvent);
+decl_browser_event!(InvokeEvent);
 decl_browser_event!(KeyPressEvent);
 decl_browser_event!(KeyPressedEvent);
 decl_browser_event!(LoadingProgressChangedEvent);
 decl_browser_event!(MessageEvent);
 decl_browser_event!(NavigationEndEvent);
+decl_browser_event!(NavigationRequestEvent);
 decl_browser_event!(NavigationStartEvent);
 decl_browser_event!(PageTitleChangedEvent);
+decl_browser_event!(RequestEvent);
+decl_browser_event!(ResponseEvent);
 decl_browser_event!(ScrollOffsetChangedEvent);
 decl_browser_event!(SelectClientCertificateEvent);
 decl_browser_event!(StartDraggingEvent);
@@ -126,6 +461,151 @@ decl_browser_event!(TooltipEvent);
 decl_browser_event!(TextSelectionChangedEvent);
 
 impl BrowserWindow {
+	/// Defines a global JavaScript function with the given name, that invokes
+	/// the given handler.
+	/// Just like `invoke_extern`, the function returns a promise, which is
+	/// settled by the result of the handler.
+	/// The function stays available after navigating to another page, or
+	/// reloading the current one.
+	/// Binding a name that is already bound replaces its handler.
+	///
+	/// Calling the function is the same as calling `invoke_extern` with the
+	/// name as its command, so those calls won't reach `on_message` or
+	/// `on_invoke` either.
+	/// Implemented for CEF and WebkitGTK.
+	///
+	/// ```
+	/// use browser_window::{browser::*, prelude::*};
+	///
+	/// fn example(bw: BrowserWindow) {
+	/// 	// In JavaScript: `let sum = await add(1, 2);`
+	/// 	bw.bind("add", |_, e| {
+	/// 		let a: f64 = e.arg(0).map_err(|e| JsValue::String(e.to_string()))?;
+	/// 		let b: f64 = e.arg(1).map_err(|e| JsValue::String(e.to_string()))?;
+	/// 		Ok(JsValue::from(a + b))
+	/// 	});
+	/// }
+	/// ```
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn bind<X>(&self, name: &str, handler: X)
+	where
+		X: FnMut(&BrowserWindowHandle, MessageEventArgs) -> Result<JsValue, JsValue> + 'static,
+	{
+		self.0.bind_handler(name, invoke_handler(handler));
+	}
+
+	/// Defines a global JavaScript function with the given name, that invokes
+	/// the given handler.
+	/// Just like `invoke_extern`, the function returns a promise, which is
+	/// settled by the result of the handler.
+	/// The function stays available after navigating to another page, or
+	/// reloading the current one.
+	/// Binding a name that is already bound replaces its handler.
+	///
+	/// Calling the function is the same as calling `invoke_extern` with the
+	/// name as its command, so those calls won't reach `on_message` or
+	/// `on_invoke` either.
+	/// Implemented for CEF and WebkitGTK.
+	#[cfg(feature = "threadsafe")]
+	pub fn bind<X>(&self, name: &str, handler: X)
+	where
+		X: FnMut(&BrowserWindowHandle, MessageEventArgs) -> Result<JsValue, JsValue>
+			+ Send
+			+ 'static,
+	{
+		self.0.bind_handler(name, invoke_handler(handler));
+	}
+
+	/// Like `bind`, but with an 'async closure'.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn bind_async<X, F>(&self, name: &str, handler: X)
+	where
+		X: FnMut(BrowserWindow, MessageEventArgs) -> F + 'static,
+		F: Future<Output = Result<JsValue, JsValue>> + 'static,
+	{
+		self.0.bind_handler(name, invoke_handler_async(handler));
+	}
+
+	/// Like `bind`, but with an 'async closure'.
+	#[cfg(feature = "threadsafe")]
+	pub fn bind_async<X, F>(&self, name: &str, handler: X)
+	where
+		X: FnMut(BrowserWindow, MessageEventArgs) -> F + Send + 'static,
+		F: Future<Output = Result<JsValue, JsValue>> + 'static,
+	{
+		self.0.bind_handler(name, invoke_handler_async(handler));
+	}
+
+	/// Invokes the given handler whenever the shortcut is pressed, like
+	/// `"Ctrl+Shift+P"`, whether or not the page has focus. The page doesn't
+	/// get to see the keys of the shortcut.
+	/// See [`Shortcut`] for how the shortcut is written. Adding a shortcut
+	/// that has already been added replaces its handler.
+	/// Implemented for CEF and WebkitGTK.
+	///
+	/// ```
+	/// use browser_window::browser::*;
+	///
+	/// fn example(bw: BrowserWindow) {
+	/// 	bw.add_shortcut("Ctrl+Shift+P", |bw, _| {
+	/// 		bw.exec_js("openCommandPalette()");
+	/// 	})
+	/// 	.unwrap();
+	/// }
+	/// ```
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn add_shortcut<X>(&self, shortcut: &str, handler: X) -> Result<(), ParseShortcutError>
+	where
+		X: FnMut(&BrowserWindowHandle, KeyEventArgs) + 'static,
+	{
+		BrowserWindowOwner::add_shortcut_handler(
+			&self.0,
+			shortcut.parse()?,
+			EventHandler::Sync(Box::new(handler)),
+		);
+		Ok(())
+	}
+
+	/// Invokes the given handler whenever the shortcut is pressed, like
+	/// `"Ctrl+Shift+P"`, whether or not the page has focus. The page doesn't
+	/// get to see the keys of the shortcut.
+	/// See [`Shortcut`] for how the shortcut is written. Adding a shortcut
+	/// that has already been added replaces its handler.
+	/// Implemented for CEF and WebkitGTK.
+	#[cfg(feature = "threadsafe")]
+	pub fn add_shortcut<X>(&self, shortcut: &str, handler: X) -> Result<(), ParseShortcutError>
+	where
+		X: FnMut(&BrowserWindowHandle, KeyEventArgs) + Send + 'static,
+	{
+		BrowserWindowOwner::add_shortcut_handler(
+			&self.0,
+			shortcut.parse()?,
+			EventHandler::Sync(Box::new(handler)),
+		);
+		Ok(())
+	}
+
+	/// Removes the shortcut that has been added with `add_shortcut`.
+	/// Returns whether the shortcut had been added.
+	pub fn remove_shortcut(&self, shortcut: &str) -> Result<bool, ParseShortcutError> {
+		let shortcut: Shortcut = shortcut.parse()?;
+		let mut handlers = self.0.1.borrow_mut();
+		let count = handlers.shortcuts.len();
+		handlers.shortcuts.retain(|(s, _)| *s != shortcut);
+		Ok(handlers.shortcuts.len() != count)
+	}
+
+	/// Removes the JavaScript function that has been defined with `bind`, from
+	/// the current page and from every page that is loaded afterwards.
+	/// Returns whether the name was bound.
+	pub fn unbind(&self, name: &str) -> bool {
+		let removed = self.0.1.borrow_mut().bindings.remove(name).is_some();
+		if removed {
+			self.0.inner.unbind(name);
+		}
+		removed
+	}
+
 	/// Whenver the address URI changes
 	pub fn on_address_changed(&self) -> AddressChangedEvent {
 		self.0.0.inner.on_address_changed(Rc::downgrade(&self.0))
@@ -152,16 +632,53 @@ impl BrowserWindow {
 			.on_loading_progress_changed(Rc::downgrade(&self.0))
 	}
 
+	/// Like `on_message`, but the value returned by the handler will settle the
+	/// promise that `invoke_extern` returned in JavaScript.
+	/// `Ok` resolves the promise, `Err` rejects it.
+	/// Only the first handler is able to settle the promise. The handlers of
+	/// `on_message` are invoked before it, but they can't settle the promise
+	/// as long as there are handlers for `on_invoke`.
+	/// This event is implemented for _all_ browser frameworks.
+	///
+	/// ```
+	/// use browser_window::{browser::*, prelude::*};
+	///
+	/// fn example(bw: BrowserWindow) {
+	/// 	// In JavaScript: `let version = await invoke_extern('version');`
+	/// 	bw.on_invoke().register(|_, e| match e.cmd.as_str() {
+	/// 		"version" => Ok(JsValue::String("1.0".into())),
+	/// 		_ => Err(JsValue::String("unknown command".into())),
+	/// 	});
+	/// }
+	/// ```
+	pub fn on_invoke(&self) -> InvokeEvent { InvokeEvent::new(Rc::downgrade(&self.0)) }
+
 	/// The event that will fire whenever `invoke_extern` is called with JS on
 	/// the client side.
+	/// Unless there are handlers for `on_invoke`, the promise returned by
+	/// `invoke_extern` will resolve to `undefined` once the first handler is
+	/// done with the event.
 	/// This event is implemented for _all_ browser frameworks.
-	pub fn on_message(&self) -> MessageEvent { self.0.0.inner.on_message(Rc::downgrade(&self.0)) }
+	pub fn on_message(&self) -> MessageEvent { MessageEvent::new(Rc::downgrade(&self.0)) }
 
 	/// Whenever navigation has finished and the page has loaded.
 	pub fn on_navigation_end(&self) -> NavigationEndEvent {
 		self.0.0.inner.on_navigation_end(Rc::downgrade(&self.0))
 	}
 
+	/// Right before a page or iframe navigates somewhere else, including when
+	/// going back or reloading.
+	/// Any handler can change what happens with the navigation by calling
+	/// [`set_policy`](NavigationRequestEventArgs::set_policy). If the URL's
+	/// origin hasn't been allowed with
+	/// [`BrowserWindowBuilder::allow_origin`], the policy of a main frame
+	/// navigation starts out as `Cancel`. Only the handlers that are registered
+	/// with `register` can change the policy.
+	/// Implemented for CEF and WebkitGTK.
+	pub fn on_navigation_request(&self) -> NavigationRequestEvent {
+		NavigationRequestEvent::new(Rc::downgrade(&self.0))
+	}
+
 	/// Whenever navigation to a new link happens.
 	pub fn on_navigation_start(&self) -> NavigationStartEvent {
 		self.0.0.inner.on_navigation_start(Rc::downgrade(&self.0))
@@ -172,61 +689,227 @@ impl BrowserWindow {
 		self.0.0.inner.on_page_title_changed(Rc::downgrade(&self.0))
 	}
 
+	/// Right before the page sends a request, including the one for the page
+	/// itself.
+	/// Any handler can change the headers of the request, or change what
+	/// happens with it by calling
+	/// [`set_action`](struct.RequestEventArgs.html#method.set_action).
+	/// Only the handlers that are registered with `register` can change the
+	/// request.
+	/// Implemented for CEF and WebkitGTK, although WebkitGTK only lets you
+	/// look at the request: the methods that change it don't exist there.
+	pub fn on_request(&self) -> RequestEvent { RequestEvent::new(Rc::downgrade(&self.0)) }
+
+	/// Whenever the response to a request has been received completely.
+	/// Implemented for CEF and WebkitGTK.
+	pub fn on_response(&self) -> ResponseEvent {
+		self.0.0.inner.on_response(Rc::downgrade(&self.0))
+	}
+
+	/// Whenever the browser wants to show a status message, like the address
+	/// of the link that the mouse hovers over.
 	pub fn on_status_message(&self) -> StatusMessageEvent {
 		self.0.0.inner.on_status_message(Rc::downgrade(&self.0))
 	}
 
 	/// Whenever the browser is about to show a tooltip
+	///
+	/// On WebkitGTK, this only covers the titles of links, and is fired with an
+	/// empty string once the mouse leaves the link. The `title` attribute of
+	/// other elements doesn't fire it.
 	pub fn on_tooltip(&self) -> TooltipEvent { self.0.0.inner.on_tooltip(Rc::downgrade(&self.0)) }
 
-	/// Not implemented yet.
+	/// Same as [`Window::on_close_requested`](../window/struct.Window.html).
+	pub fn on_close_requested(&self) -> CloseRequestedEvent { self.0.2.on_close_requested() }
+
+	/// Same as [`Window::on_focus_changed`](../window/struct.Window.html).
+	pub fn on_focus_changed(&self) -> FocusChangedEvent { self.0.2.on_focus_changed() }
+
+	/// Same as [`Window::on_maximized_changed`](../window/struct.Window.html).
+	pub fn on_maximized_changed(&self) -> MaximizedChangedEvent { self.0.2.on_maximized_changed() }
+
+	/// Same as [`Window::on_minimized_changed`](../window/struct.Window.html).
+	pub fn on_minimized_changed(&self) -> MinimizedChangedEvent { self.0.2.on_minimized_changed() }
+
+	/// Same as [`Window::on_moved`](../window/struct.Window.html).
+	pub fn on_moved(&self) -> MovedEvent { self.0.2.on_moved() }
+
+	/// Same as [`Window::on_resized`](../window/struct.Window.html).
+	pub fn on_resized(&self) -> ResizedEvent { self.0.2.on_resized() }
+
+	/// Same as [`Window::on_visibility_changed`](../window/struct.Window.html).
+	pub fn on_visibility_changed(&self) -> VisibilityChangedEvent {
+		self.0.2.on_visibility_changed()
+	}
+
+	/// Whenever a server or proxy asks for a username and password.
+	/// The credentials returned by the first handler are used to authenticate,
+	/// and `None` cancels the request, which is also what happens without any
+	/// handlers.
+	/// Implemented for CEF and WebkitGTK.
+	///
+	/// ```
+	/// use browser_window::{browser::*, prelude::*};
+	///
+	/// fn example(bw: BrowserWindow) {
+	/// 	bw.on_auth_credentials().register_async(|_, e| async move {
+	/// 		if e.host == "example.com" {
+	/// 			Some(Credentials::new("user", "secret"))
+	/// 		} else {
+	/// 			None
+	/// 		}
+	/// 	});
+	/// }
+	/// ```
+	///
+	/// The first handler gets to answer, so every handler of this event has to
+	/// give an answer. That is why it can only be registered to with `register`
+	/// and `register_async`, and has no `stream`, which couldn't answer:
+	/// ```compile_fail
+	/// use browser_window::{browser::*, event::EventExt};
+	///
+	/// fn example(bw: BrowserWindow) { let _ = bw.on_auth_credentials().stream(); }
+	/// ```
+	/// The same goes for the other events that ask for an answer:
+	/// `on_certificate_error`, `on_download_started`, `on_file_dialog` and
+	/// `on_select_client_certificate`.
 	pub fn on_auth_credentials(&self) -> AuthCredentialsEvent {
-		unimplemented!();
+		AuthCredentialsEvent::new(Rc::downgrade(&self.0))
 	}
 
-	/// Not implemented yet.
+	/// Whenever a server presents a certificate that isn't trusted.
+	/// The first handler decides whether to trust it anyway by returning
+	/// `true`. Without any handlers, the certificate isn't trusted.
+	/// Certificates that are trusted by the
+	/// [`ApplicationSettings`](../application/struct.ApplicationSettings.html)
+	/// don't fire this event.
+	/// Implemented for CEF and WebkitGTK, although WebkitGTK only fires it for
+	/// the page itself.
 	pub fn on_certificate_error(&self) -> CertificateErrorEvent {
-		unimplemented!();
+		CertificateErrorEvent::new(Rc::downgrade(&self.0))
 	}
 
-	/// Not implemented yet.
+	/// Whenever a download has completed, failed or has been cancelled.
+	/// Implemented for CEF and WebkitGTK.
+	pub fn on_download_finished(&self) -> DownloadFinishedEvent {
+		DownloadFinishedEvent::new(Rc::downgrade(&self.0))
+	}
+
+	/// Whenever more data of a download has been received.
+	/// Implemented for CEF and WebkitGTK.
 	pub fn on_download_progress(&self) -> DownloadProgressEvent {
-		unimplemented!();
+		DownloadProgressEvent::new(Rc::downgrade(&self.0))
 	}
 
-	/// Not implemented yet.
+	/// Whenever the page starts a download.
+	/// The first handler decides where the file is saved by returning its path,
+	/// or cancels the download by returning `None`. Without any handlers, the
+	/// file is saved in the user's download directory.
+	/// Downloads started by `download` don't fire this event.
+	/// Implemented for CEF and WebkitGTK.
+	///
+	/// # Example
+	/// ```
+	/// use std::path::PathBuf;
+	///
+	/// use browser_window::browser::*;
+	///
+	/// fn save_pdfs_only(bw: &BrowserWindow) {
+	/// 	bw.on_download_started().register(|_, e| {
+	/// 		if e.mime_type == "application/pdf" {
+	/// 			Some(PathBuf::from("downloads").join(&e.suggested_filename))
+	/// 		} else {
+	/// 			None
+	/// 		}
+	/// 	});
+	/// }
+	/// ```
 	pub fn on_download_started(&self) -> DownloadStartedEvent {
-		unimplemented!();
+		DownloadStartedEvent::new(Rc::downgrade(&self.0))
 	}
 
-	/// Not implemented yet.
+	/// Whenever the favicon of the page changes, with the URL of the new
+	/// favicon.
+	///
+	/// On WebkitGTK, WebKit only loads favicons when its favicon database is
+	/// enabled. Subscribing to this event enables it for the whole application,
+	/// and it stays enabled. Without a directory set for it beforehand, it is
+	/// kept in WebKit's default cache directory.
 	pub fn on_favicon_changed(&self) -> FaviconChangedEvent {
-		unimplemented!();
-	}
-
-	/// Not implemented yet.
-	pub fn on_file_dialog(&self) -> FileDialogEvent {
-		unimplemented!();
-	}
-
-	/// Not implemented yet.
-	pub fn on_key_press(&self) -> KeyPressEvent {
-		unimplemented!();
+		self.0.0.inner.on_favicon_changed(Rc::downgrade(&self.0))
 	}
 
-	/// Not implemented yet.
-	pub fn on_key_pressed(&self) -> KeyPressedEvent {
-		unimplemented!();
-	}
+	/// Whenever the page wants to show a file dialog, for an
+	/// `<input type="file">` element for example.
+	/// The first handler chooses the files instead of the user, and returning
+	/// none cancels the dialog. Without any handlers, the browser shows its own
+	/// dialog.
+	/// Dialogs shown with `open_file`, `pick_folder` or `save_file` don't fire
+	/// this event.
+	/// Implemented for CEF and WebkitGTK.
+	///
+	/// # Example
+	/// ```
+	/// use std::path::PathBuf;
+	///
+	/// use browser_window::browser::*;
+	///
+	/// fn upload_report(bw: &BrowserWindow) {
+	/// 	bw.on_file_dialog()
+	/// 		.register(|_, _| vec![PathBuf::from("report.pdf")]);
+	/// }
+	/// ```
+	pub fn on_file_dialog(&self) -> FileDialogEvent { FileDialogEvent::new(Rc::downgrade(&self.0)) }
+
+	/// Whenever a key is pressed while the browser window has focus, before the
+	/// page gets to see it.
+	/// Any handler can keep the key from the page by calling
+	/// [`consume`](struct.KeyEventArgs.html#method.consume). Only the handlers
+	/// that are registered with `register` can consume the key.
+	/// Keys of the shortcuts that have been added with `add_shortcut` don't
+	/// fire this event.
+	/// Implemented for CEF and WebkitGTK.
+	pub fn on_key_press(&self) -> KeyPressEvent { KeyPressEvent::new(Rc::downgrade(&self.0)) }
+
+	/// Whenever a key has been pressed that the page didn't handle.
+	/// Any handler can keep the key from the browser's own key bindings by
+	/// calling [`consume`](struct.KeyEventArgs.html#method.consume).
+	/// Implemented for CEF and WebkitGTK.
+	pub fn on_key_pressed(&self) -> KeyPressedEvent { KeyPressedEvent::new(Rc::downgrade(&self.0)) }
 
 	/// Not implemented yet.
 	pub fn on_scroll_offset_changed(&self) -> ScrollOffsetChangedEvent {
 		unimplemented!();
 	}
 
-	/// Not implemented yet.
+	/// Whenever a server asks for a certificate to identify the browser window
+	/// with, for mutual TLS.
+	/// The first handler picks one of the candidates, or supplies its own
+	/// certificate. If it returns `None`, or if there are no handlers, no
+	/// certificate is sent.
+	/// Implemented for CEF and WebkitGTK, although CEF only asks when the
+	/// system has any certificates that the server accepts, and can only use
+	/// those. Use
+	/// [`check`](struct.SelectClientCertificateEventArgs.html#method.check) to
+	/// find out whether a certificate can be used.
+	///
+	/// # Example
+	/// ```
+	/// use browser_window::{browser::*, certificate::ClientCertificate};
+	///
+	/// fn use_client_certificate(bw: &BrowserWindow) {
+	/// 	bw.on_select_client_certificate().register(|_, e| {
+	/// 		if e.host == "intranet.local" {
+	/// 			let certificate = ClientCertificate::from_pem_files("client.pem", "client.key");
+	/// 			certificate.and_then(|c| e.check(c)).ok()
+	/// 		} else {
+	/// 			None
+	/// 		}
+	/// 	});
+	/// }
+	/// ```
 	pub fn on_select_client_certificate(&self) -> SelectClientCertificateEvent {
-		unimplemented!();
+		SelectClientCertificateEvent::new(Rc::downgrade(&self.0))
 	}
 
 	/// Not implemented yet.
@@ -250,6 +933,10 @@ impl HasHandle<ApplicationHandle> for BrowserWindow {
 	fn handle(&self) -> &ApplicationHandle { &self.app }
 }
 
+impl HasHandle<BrowserWindowHandle> for BrowserWindow {
+	fn handle(&self) -> &BrowserWindowHandle { &self.0.0 }
+}
+
 impl HasHandle<WindowHandle> for BrowserWindow {
 	fn handle(&self) -> &WindowHandle { &self.window }
 }
@@ -257,7 +944,13 @@ impl HasHandle<WindowHandle> for BrowserWindow {
 // Core browser window functions
 impl BrowserWindowHandle {
 	/// Returns the application handle associated with this browser window.
-	pub fn app(&self) -> ApplicationHandle { ApplicationHandle::new(self.inner.window().app()) }
+	pub fn app(&self) -> ApplicationHandle { ApplicationHandle::new(self.app.inner.clone()) }
+
+	/// Whether or not there is a page to go back to.
+	pub fn can_go_back(&self) -> bool { self.inner.can_go_back() }
+
+	/// Whether or not there is a page to go forward to.
+	pub fn can_go_forward(&self) -> bool { self.inner.can_go_forward() }
 
 	pub fn close(self) {
 		// The window isn't actually destroyed until the reference count of the owner
@@ -265,6 +958,41 @@ impl BrowserWindowHandle {
 		self.inner.window().hide();
 	}
 
+	/// Downloads the file at `url` to `path`, and resolves when the file has
+	/// been saved.
+	/// The download fires `on_download_progress` and `on_download_finished`,
+	/// but not `on_download_started`. If it fails before it has started, like
+	/// when the server can't be reached, it only fires `on_download_finished`.
+	/// Implemented for CEF and WebkitGTK. Fails right away for Edge WebView2.
+	pub async fn download(&self, url: &str, path: impl Into<PathBuf>) -> Result<(), DownloadError> {
+		let path = path.into();
+		let owner = match self.owner() {
+			Some(owner) => owner,
+			None => return Err(DownloadError::Cancelled),
+		};
+
+		let (tx, rx) = oneshot::channel();
+		let request = {
+			let mut handlers = owner.1.borrow_mut();
+			let downloads = &mut handlers.downloads;
+			downloads.last_request += 1;
+			downloads
+				.pending
+				.insert(downloads.last_request, (path.clone(), tx));
+			downloads.last_request
+		};
+		if !self.inner.download(url, &path, request) {
+			owner.1.borrow_mut().downloads.pending.remove(&request);
+			return Err(DownloadError::Failed(
+				"the browser framework could not start the download".to_owned(),
+			));
+		}
+
+		// If the browser window gets closed before the download has finished, the
+		// sender is dropped.
+		rx.await.unwrap_or(Err(DownloadError::Cancelled))
+	}
+
 	/// Executes the given javascript code and returns the output as a string.
 	/// If you don't need the result, see `exec_js`.
 	///
@@ -308,9 +1036,30 @@ impl BrowserWindowHandle {
 	/// Executes the given javascript code without waiting on it to finish.
 	pub fn exec_js(&self, js: &str) { self._eval_js(js, |_, _| {}); }
 
+	/// Navigates to the previous page in the history, if there is one.
+	pub fn go_back(&self) { self.inner.go_back() }
+
+	/// Navigates to the next page in the history, if there is one.
+	pub fn go_forward(&self) { self.inner.go_forward() }
+
+	/// The back/forward list, from the oldest to the newest page.
+	/// The entry of the current page has `current` set.
+	///
+	/// *Note:* Always empty with Edge WebView2.
+	pub fn history(&self) -> Vec<HistoryEntry> { self.inner.history() }
+
 	/// Causes the browser to navigate to the given url.
 	pub fn navigate(&self, url: &str) { self.inner.navigate(url) }
 
+	/// Reloads the current page.
+	pub fn reload(&self) { self.inner.reload(false) }
+
+	/// Reloads the current page without using anything from the cache.
+	pub fn reload_ignore_cache(&self) { self.inner.reload(true) }
+
+	/// Stops loading the current page.
+	pub fn stop(&self) { self.inner.stop() }
+
 	pub fn url<'a>(&'a self) -> Cow<'a, str> { self.inner.url() }
 
 	pub fn window(&self) -> &WindowHandle { &self.window }
@@ -329,7 +1078,7 @@ impl HasHandle<WindowHandle> for BrowserWindowHandle {
 impl BrowserWindowThreaded {
 	/// The thread-safe application handle associated with this browser window.
 	pub fn app(&self) -> ApplicationHandleThreaded {
-		ApplicationHandleThreaded::from_core_handle(self.0.inner.window().app())
+		ApplicationHandleThreaded::from_core_handle(self.0.app.inner.clone())
 	}
 
 	/// Closes the browser.
@@ -421,6 +1170,18 @@ impl BrowserWindowHandle {
 			inner: self.inner.clone(),
 		}
 	}
+
+	/// The owner of the browser window, which the user data of its window
+	/// keeps alive for as long as the window exists.
+	/// Handles are also created from just the backend's handle, so this is the
+	/// way for them to get to the owner.
+	fn owner(&self) -> Option<&BrowserWindowOwner> {
+		let user_data = self.window.0.user_data() as *const WindowUserData;
+		if user_data.is_null() {
+			return None;
+		}
+		unsafe { &*user_data }._owner.downcast_ref()
+	}
 }
 
 impl Deref for BrowserWindowHandle {
@@ -434,9 +1195,361 @@ impl HasAppHandle for BrowserWindowHandle {
 }
 
 impl BrowserWindowOwner {
-	fn cleanup(handle: &BrowserWindowHandle) {
-		handle.inner.free();
-		handle.inner.window().free();
+	pub(super) fn new(handle: BrowserWindowHandle, allowed_origins: Vec<String>) -> Self {
+		let window = Window::new_unattached(handle.inner.window());
+		Self(
+			ManuallyDrop::new(handle),
+			ManuallyDrop::new(RefCell::new(Handlers {
+				auth_credentials: Rc::new(EventHandlers::new()),
+				certificate_errors: Rc::new(EventHandlers::new()),
+				client_certificates: Rc::new(EventHandlers::new()),
+				downloads_finished: Rc::new(EventHandlers::new()),
+				download_progress: Rc::new(EventHandlers::new()),
+				downloads_started: Rc::new(EventHandlers::new()),
+				downloads: Downloads::default(),
+				file_dialogs: Rc::new(EventHandlers::new()),
+				bindings: HashMap::new(),
+				invocations: Rc::new(EventHandlers::new()),
+				key_presses: Rc::new(EventHandlers::new()),
+				keys_pressed: Rc::new(EventHandlers::new()),
+				shortcuts: Vec::new(),
+				messages: Rc::new(EventHandlers::new()),
+				allowed_origins,
+				navigation_requests: Rc::new(EventHandlers::new()),
+				requests: Rc::new(EventHandlers::new()),
+				events: Vec::new(),
+			})),
+			ManuallyDrop::new(window),
+		)
+	}
+
+	fn bind_handler(&self, name: &str, handler: BrowserWindowEventHandler<MessageEventArgs>) {
+		let previous = self
+			.1
+			.borrow_mut()
+			.bindings
+			.insert(name.to_owned(), HandlerCell::new(handler));
+		if previous.is_none() {
+			self.inner.bind(name);
+		}
+	}
+
+	fn add_shortcut_handler(
+		this: &Rc<Self>, shortcut: Shortcut, handler: BrowserWindowEventHandler<KeyEventArgs>,
+	) {
+		let handler = HandlerCell::new(handler);
+		{
+			let mut handlers = this.1.borrow_mut();
+			match handlers.shortcuts.iter_mut().find(|(s, _)| *s == shortcut) {
+				Some((_, h)) => *h = handler,
+				None => handlers.shortcuts.push((shortcut, handler)),
+			}
+		}
+		this.inner.listen_for_keys(Rc::downgrade(this));
+	}
+
+	/// Creates an empty list of handlers for an event, that lives as long as
+	/// this owner does.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new_event_handlers<A>(
+		&self,
+	) -> SharedEventHandlers<BrowserWindowHandle, BrowserWindow, A>
+	where
+		A: 'static,
+	{
+		let handlers = Rc::new(EventHandlers::new());
+		self.1.borrow_mut().events.push(handlers.clone());
+		handlers
+	}
+
+	/// The window itself is freed by its own owner, which gets dropped after
+	/// this one.
+	fn cleanup(handle: &BrowserWindowHandle) { handle.inner.free(); }
+
+	/// Passes a message from `invoke_extern` on to the handler of the function
+	/// that is bound to its command, or otherwise to the handlers of
+	/// `on_message` and `on_invoke`.
+	pub(crate) fn dispatch_message(owner: &Weak<Self>, args: MessageEventArgs) {
+		let this = match owner.upgrade() {
+			Some(this) => this,
+			None => return,
+		};
+		let (binding, invocations, messages) = {
+			let handlers = this.1.borrow();
+			(
+				handlers.bindings.get(&args.cmd).cloned(),
+				handlers.invocations.clone(),
+				handlers.messages.clone(),
+			)
+		};
+
+		// Without any handler, the responder settles the promise with `undefined`.
+		if let Some(handler) = binding {
+			Self::fire_handler(&this, &handler, args);
+		} else if invocations.is_empty() {
+			Self::fire_event(owner, &messages, args);
+		} else {
+			// The handlers of `on_message` only get to look at the message.
+			Self::fire_event(owner, &messages, args.clone());
+			Self::fire_event(owner, &invocations, args);
+		}
+	}
+
+	/// Invokes the handler of the shortcut of the key, or otherwise the
+	/// handlers of `on_key_press`.
+	/// Returns whether or not the key has been consumed, in which case the page
+	/// shouldn't get to see it.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn key_press(owner: &Weak<Self>, args: KeyEventArgs) -> bool {
+		let this = match owner.upgrade() {
+			Some(this) => this,
+			None => return false,
+		};
+		let (shortcut, handlers) = {
+			let handlers = this.1.borrow();
+			let shortcut = handlers
+				.shortcuts
+				.iter()
+				.find(|(s, _)| s.matches(args.key_code, args.modifiers))
+				.map(|(_, h)| h.clone());
+			(shortcut, handlers.key_presses.clone())
+		};
+
+		if let Some(handler) = shortcut {
+			Self::fire_handler(&this, &handler, args);
+			return true;
+		}
+		Self::fire_event(owner, &handlers, args.clone());
+		args.is_consumed()
+	}
+
+	/// Invokes the handlers of `on_key_pressed`.
+	/// Returns whether or not the key has been consumed.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn key_pressed(owner: &Weak<Self>, args: KeyEventArgs) -> bool {
+		let handlers = match owner.upgrade() {
+			Some(this) => this.1.borrow().keys_pressed.clone(),
+			None => return false,
+		};
+		Self::fire_event(owner, &handlers, args.clone());
+		args.is_consumed()
+	}
+
+	/// Decides what happens with a navigation, by checking the allowed origins
+	/// and invoking the handlers of `on_navigation_request`.
+	/// Returns whether or not the navigation should be cancelled.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn request_navigation(owner: &Weak<Self>, args: NavigationRequestEventArgs) -> bool {
+		let this = match owner.upgrade() {
+			Some(this) => this,
+			None => return false,
+		};
+		let handlers = {
+			let handlers = this.1.borrow();
+			if args.is_main_frame && !is_origin_allowed(&handlers.allowed_origins, &args.url) {
+				args.set_policy(NavigationPolicy::Cancel);
+			}
+			handlers.navigation_requests.clone()
+		};
+
+		Self::fire_event(owner, &handlers, args.clone());
+		match args.policy() {
+			NavigationPolicy::Allow => false,
+			NavigationPolicy::Cancel => true,
+			NavigationPolicy::OpenExternally => {
+				// The failure ends up in the page's console, and with that in
+				// `on_console_message`.
+				if let Err(e) = open_externally(&args.url) {
+					let message = format!("unable to open {} externally: {}", args.url, e);
+					this.0
+						.exec_js(&format!("console.error({})", JsValue::String(message)));
+				}
+				true
+			}
+		}
+	}
+
+	/// Invokes the handlers of `on_auth_credentials`, of which the first one
+	/// answers the request.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn request_auth_credentials(owner: &Weak<Self>, args: AuthCredentialsEventArgs) {
+		let handlers = match owner.upgrade() {
+			Some(this) => this.1.borrow().auth_credentials.clone(),
+			None => return,
+		};
+		Self::fire_event(owner, &handlers, args);
+	}
+
+	/// Decides where a download is saved: at the path given to `download` if it
+	/// has been started by it, and by the handlers of `on_download_started`
+	/// otherwise.
+	/// `request` is the one that the backend has been given by `download`, if
+	/// the download has been started by it.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn start_download(
+		owner: &Weak<Self>, request: Option<u32>, mut args: DownloadStartedEventArgs,
+	) {
+		let this = match owner.upgrade() {
+			Some(this) => this,
+			None => return,
+		};
+
+		let pending = request.and_then(|r| this.1.borrow_mut().downloads.pending.remove(&r));
+		let answer = match pending {
+			Some((path, tx)) => {
+				this.1.borrow_mut().downloads.active.insert(args.id, tx);
+				Some(path)
+			}
+			None => {
+				let handlers = this.1.borrow().downloads_started.clone();
+				if !handlers.is_empty() {
+					Self::fire_event(owner, &handlers, args);
+					return;
+				}
+				// An empty path stands for the user's download directory.
+				Some(PathBuf::new())
+			}
+		};
+		if let Some(a) = args.answer.take() {
+			a.give(answer);
+		}
+	}
+
+	/// Invokes the handlers of `on_file_dialog`, of which the first one chooses
+	/// the files.
+	/// Returns whether or not the dialog has been handled. If not, the answer
+	/// is withdrawn, so that the browser can show its own dialog.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn open_file_dialog(owner: &Weak<Self>, mut args: FileDialogEventArgs) -> bool {
+		let handlers = match owner.upgrade() {
+			Some(this) => this.1.borrow().file_dialogs.clone(),
+			None => return false,
+		};
+		if handlers.is_empty() {
+			if let Some(a) = args.answer.take() {
+				a.withdraw();
+			}
+			return false;
+		}
+		Self::fire_event(owner, &handlers, args);
+		true
+	}
+
+	/// Invokes the handlers of `on_download_progress`.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn download_progress(owner: &Weak<Self>, args: DownloadProgressEventArgs) {
+		let handlers = match owner.upgrade() {
+			Some(this) => this.1.borrow().download_progress.clone(),
+			None => return,
+		};
+		Self::fire_event(owner, &handlers, args);
+	}
+
+	/// Resolves the future of `download` if the download has been started by
+	/// it, and invokes the handlers of `on_download_finished`.
+	/// `request` is the one that the backend has been given by `download`, if
+	/// the download has been started by it. A download that fails before it
+	/// has started is only known by its request.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn finish_download(
+		owner: &Weak<Self>, request: Option<u32>, args: DownloadFinishedEventArgs,
+	) {
+		let this = match owner.upgrade() {
+			Some(this) => this,
+			None => return,
+		};
+
+		let tx = {
+			let downloads = &mut this.1.borrow_mut().downloads;
+			downloads.active.remove(&args.id).or_else(|| {
+				request
+					.and_then(|r| downloads.pending.remove(&r))
+					.map(|(_, tx)| tx)
+			})
+		};
+		if let Some(tx) = tx {
+			let _ = tx.send(args.result.clone());
+		}
+
+		let handlers = this.1.borrow().downloads_finished.clone();
+		Self::fire_event(owner, &handlers, args);
+	}
+
+	/// Invokes the handlers of `on_select_client_certificate`, of which the
+	/// first one answers the request.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn select_client_certificate(
+		owner: &Weak<Self>, args: SelectClientCertificateEventArgs,
+	) {
+		let handlers = match owner.upgrade() {
+			Some(this) => this.1.borrow().client_certificates.clone(),
+			None => return,
+		};
+		Self::fire_event(owner, &handlers, args);
+	}
+
+	/// Trusts the certificate if the application settings say so, and invokes
+	/// the handlers of `on_certificate_error` otherwise.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn handle_certificate_error(
+		owner: &Weak<Self>, mut args: CertificateErrorEventArgs,
+	) {
+		let this = match owner.upgrade() {
+			Some(this) => this,
+			None => return,
+		};
+		// Pinning a certificate only vouches for who has issued it.
+		if args.error == CertificateError::UnknownAuthority
+			&& this
+				.0
+				.app
+				.inner
+				.trust()
+				.is_pinned(url_host(&args.url), &args.certificate.der)
+		{
+			if let Some(a) = args.answer.take() {
+				a.give(true);
+			}
+			return;
+		}
+
+		let handlers = this.1.borrow().certificate_errors.clone();
+		Self::fire_event(owner, &handlers, args);
+	}
+
+	/// Invokes the handlers of `on_request`, which leave their changes in
+	/// `args`.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn intercept_request(owner: &Weak<Self>, args: RequestEventArgs) {
+		let handlers = match owner.upgrade() {
+			Some(this) => this.1.borrow().requests.clone(),
+			None => return,
+		};
+		Self::fire_event(owner, &handlers, args);
+	}
+
+	/// Invokes the handlers of an event in the order in which they have been
+	/// registered.
+	/// The first handler receives the argument itself, the others receive a
+	/// clone of it.
+	pub(crate) fn fire_event<A>(
+		owner: &Weak<Self>, handlers: &SharedEventHandlers<BrowserWindowHandle, BrowserWindow, A>,
+		arg: A,
+	) where
+		A: Clone,
+	{
+		let this = match owner.upgrade() {
+			Some(this) => this,
+			None => return,
+		};
+		handlers.fire(&this.0, &|| BrowserWindow(this.clone()), &this.0.app, arg);
+	}
+
+	fn fire_handler<A>(
+		this: &Rc<Self>, handler: &SharedEventHandler<BrowserWindowHandle, BrowserWindow, A>,
+		arg: A,
+	) {
+		handler.invoke(&this.0, &|| BrowserWindow(this.clone()), &this.0.app, arg);
 	}
 }
 
@@ -448,27 +1561,1079 @@ impl Deref for BrowserWindowOwner {
 
 impl Drop for BrowserWindowOwner {
 	fn drop(&mut self) {
+		// The fields hold on to objects of the GUI framework, so they are moved to the
+		// GUI thread to be dropped there.
+		let (handle, handlers, window) = unsafe {
+			(
+				ManuallyDrop::take(&mut self.0),
+				ManuallyDrop::take(&mut self.1),
+				ManuallyDrop::take(&mut self.2),
+			)
+		};
 		#[cfg(not(feature = "threadsafe"))]
-		Self::cleanup(&self.0);
+		{
+			Self::cleanup(&handle);
+			drop((handlers, handle, window));
+		}
 		#[cfg(feature = "threadsafe")]
 		{
-			let bw = unsafe { UnsafeSend::new(self.0.clone()) };
-			self.app().into_threaded().dispatch(|_| {
-				Self::cleanup(&bw.unwrap());
+			let threaded = handle.app().into_threaded();
+			let fields = UnsafeSend::new((handle, handlers, window));
+			threaded.dispatch(move |_| {
+				let (handle, handlers, window) = fields.unwrap();
+				Self::cleanup(&handle);
+				drop((handlers, handle, window));
 			});
 		}
 	}
 }
 
-fn eval_js_callback<H>(
-	_handle: BrowserWindowImpl, cb_data: *mut (), result: Result<JsValue, JsEvaluationError>,
-) where
-	H: FnOnce(&BrowserWindowHandle, Result<JsValue, JsEvaluationError>),
-{
-	let data_ptr = cb_data as *mut H;
-	let data = unsafe { Box::from_raw(data_ptr) };
-
-	let handle = BrowserWindowHandle::new(_handle);
+impl AuthCredentialsEvent {
+	/// Register a closure to be invoked for this event.
+	/// The credentials it returns are used to authenticate, `None` cancels.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, AuthCredentialsEventArgs) -> Option<Credentials> + 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
 
-	(*data)(&handle, result);
+	/// Register a closure to be invoked for this event.
+	/// The credentials it returns are used to authenticate, `None` cancels.
+	#[cfg(feature = "threadsafe")]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, AuthCredentialsEventArgs) -> Option<Credentials>
+			+ Send
+			+ 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The credentials that the future outputs are used to authenticate, `None`
+	/// cancels.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, AuthCredentialsEventArgs) -> F + 'static,
+		F: Future<Output = Option<Credentials>> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The credentials that the future outputs are used to authenticate, `None`
+	/// cancels.
+	#[cfg(feature = "threadsafe")]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, AuthCredentialsEventArgs) -> F + Send + 'static,
+		F: Future<Output = Option<Credentials>> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	fn register_handler(
+		&mut self, handler: BrowserWindowEventHandler<AuthCredentialsEventArgs>,
+	) -> Subscription {
+		match self.owner.upgrade() {
+			Some(owner) => {
+				// Until there is a handler, the browser is left to ask for credentials itself.
+				owner.inner.listen_for_auth_credentials(self.owner.clone());
+				EventHandlers::add(&owner.1.borrow().auth_credentials, handler)
+			}
+			None => Subscription::none(),
+		}
+	}
+}
+
+impl CertificateErrorEvent {
+	/// Register a closure to be invoked for this event.
+	/// If it returns `true`, the certificate is trusted anyway.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, CertificateErrorEventArgs) -> bool + 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register a closure to be invoked for this event.
+	/// If it returns `true`, the certificate is trusted anyway.
+	#[cfg(feature = "threadsafe")]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, CertificateErrorEventArgs) -> bool + Send + 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// If the future outputs `true`, the certificate is trusted anyway.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, CertificateErrorEventArgs) -> F + 'static,
+		F: Future<Output = bool> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// If the future outputs `true`, the certificate is trusted anyway.
+	#[cfg(feature = "threadsafe")]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, CertificateErrorEventArgs) -> F + Send + 'static,
+		F: Future<Output = bool> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	fn register_handler(
+		&mut self, handler: BrowserWindowEventHandler<CertificateErrorEventArgs>,
+	) -> Subscription {
+		match self.owner.upgrade() {
+			Some(owner) => EventHandlers::add(&owner.1.borrow().certificate_errors, handler),
+			None => Subscription::none(),
+		}
+	}
+}
+
+impl DownloadStartedEvent {
+	/// Register a closure to be invoked for this event.
+	/// The file is saved at the path it returns, `None` cancels the download.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, DownloadStartedEventArgs) -> Option<PathBuf> + 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register a closure to be invoked for this event.
+	/// The file is saved at the path it returns, `None` cancels the download.
+	#[cfg(feature = "threadsafe")]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, DownloadStartedEventArgs) -> Option<PathBuf>
+			+ Send
+			+ 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The file is saved at the path that the future outputs, `None` cancels
+	/// the download.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, DownloadStartedEventArgs) -> F + 'static,
+		F: Future<Output = Option<PathBuf>> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The file is saved at the path that the future outputs, `None` cancels
+	/// the download.
+	#[cfg(feature = "threadsafe")]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, DownloadStartedEventArgs) -> F + Send + 'static,
+		F: Future<Output = Option<PathBuf>> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	fn register_handler(
+		&mut self, handler: BrowserWindowEventHandler<DownloadStartedEventArgs>,
+	) -> Subscription {
+		match self.owner.upgrade() {
+			Some(owner) => EventHandlers::add(&owner.1.borrow().downloads_started, handler),
+			None => Subscription::none(),
+		}
+	}
+}
+
+impl FileDialogEvent {
+	/// Register a closure to be invoked for this event.
+	/// The paths it returns are chosen, none cancels the dialog.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, FileDialogEventArgs) -> Vec<PathBuf> + 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register a closure to be invoked for this event.
+	/// The paths it returns are chosen, none cancels the dialog.
+	#[cfg(feature = "threadsafe")]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, FileDialogEventArgs) -> Vec<PathBuf> + Send + 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The paths that the future outputs are chosen, none cancels the dialog.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, FileDialogEventArgs) -> F + 'static,
+		F: Future<Output = Vec<PathBuf>> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The paths that the future outputs are chosen, none cancels the dialog.
+	#[cfg(feature = "threadsafe")]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, FileDialogEventArgs) -> F + Send + 'static,
+		F: Future<Output = Vec<PathBuf>> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	fn register_handler(
+		&mut self, handler: BrowserWindowEventHandler<FileDialogEventArgs>,
+	) -> Subscription {
+		match self.owner.upgrade() {
+			Some(owner) => {
+				// Until there is a handler, the browser shows its own dialogs.
+				owner.inner.listen_for_file_dialogs(self.owner.clone());
+				EventHandlers::add(&owner.1.borrow().file_dialogs, handler)
+			}
+			None => Subscription::none(),
+		}
+	}
+}
+
+impl SelectClientCertificateEvent {
+	/// Register a closure to be invoked for this event.
+	/// The certificate it returns is sent to the server, `None` sends none.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(
+				&BrowserWindowHandle,
+				SelectClientCertificateEventArgs,
+			) -> Option<ClientCertificate>
+			+ 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register a closure to be invoked for this event.
+	/// The certificate it returns is sent to the server, `None` sends none.
+	#[cfg(feature = "threadsafe")]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(
+				&BrowserWindowHandle,
+				SelectClientCertificateEventArgs,
+			) -> Option<ClientCertificate>
+			+ Send
+			+ 'static,
+	{
+		self.register_handler(answer_handler(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The certificate that the future outputs is sent to the server, `None`
+	/// sends none.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, SelectClientCertificateEventArgs) -> F + 'static,
+		F: Future<Output = Option<ClientCertificate>> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The certificate that the future outputs is sent to the server, `None`
+	/// sends none.
+	#[cfg(feature = "threadsafe")]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, SelectClientCertificateEventArgs) -> F + Send + 'static,
+		F: Future<Output = Option<ClientCertificate>> + 'static,
+	{
+		self.register_handler(answer_handler_async(handler))
+	}
+
+	fn register_handler(
+		&mut self, handler: BrowserWindowEventHandler<SelectClientCertificateEventArgs>,
+	) -> Subscription {
+		match self.owner.upgrade() {
+			Some(owner) => {
+				// Until there is a handler, the browser decides by itself.
+				owner
+					.inner
+					.listen_for_client_certificates(self.owner.clone());
+				EventHandlers::add(&owner.1.borrow().client_certificates, handler)
+			}
+			None => Subscription::none(),
+		}
+	}
+}
+
+impl InvokeEvent {
+	/// Register a closure to be invoked for this event.
+	/// Its result is sent back to JavaScript.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, MessageEventArgs) -> Result<JsValue, JsValue> + 'static,
+	{
+		self.register_handler(invoke_handler(handler))
+	}
+
+	/// Register a closure to be invoked for this event.
+	/// Its result is sent back to JavaScript.
+	#[cfg(feature = "threadsafe")]
+	pub fn register<X>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(&BrowserWindowHandle, MessageEventArgs) -> Result<JsValue, JsValue>
+			+ Send
+			+ 'static,
+	{
+		self.register_handler(invoke_handler(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The output of the future is sent back to JavaScript.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, MessageEventArgs) -> F + 'static,
+		F: Future<Output = Result<JsValue, JsValue>> + 'static,
+	{
+		self.register_handler(invoke_handler_async(handler))
+	}
+
+	/// Register an 'async closure' to be invoked for this event.
+	/// The output of the future is sent back to JavaScript.
+	#[cfg(feature = "threadsafe")]
+	pub fn register_async<X, F>(&mut self, handler: X) -> Subscription
+	where
+		X: FnMut(BrowserWindow, MessageEventArgs) -> F + Send + 'static,
+		F: Future<Output = Result<JsValue, JsValue>> + 'static,
+	{
+		self.register_handler(invoke_handler_async(handler))
+	}
+
+	fn register_handler(
+		&mut self, handler: BrowserWindowEventHandler<MessageEventArgs>,
+	) -> Subscription {
+		match self.owner.upgrade() {
+			Some(owner) => EventHandlers::add(&owner.1.borrow().invocations, handler),
+			None => Subscription::none(),
+		}
+	}
+}
+
+def_browser_event!(DownloadFinishedEvent<DownloadFinishedEventArgs>(&mut self, handler) {
+	match self.owner.upgrade() {
+		Some(owner) => EventHandlers::add(&owner.1.borrow().downloads_finished, handler),
+		None => Subscription::none(),
+	}
+});
+
+def_browser_event!(DownloadProgressEvent<DownloadProgressEventArgs>(&mut self, handler) {
+	match self.owner.upgrade() {
+		Some(owner) => EventHandlers::add(&owner.1.borrow().download_progress, handler),
+		None => Subscription::none(),
+	}
+});
+
+def_browser_event!(KeyPressEvent<KeyEventArgs>(&mut self, handler) {
+	match self.owner.upgrade() {
+		Some(owner) => {
+			owner.inner.listen_for_keys(self.owner.clone());
+			EventHandlers::add(&owner.1.borrow().key_presses, handler)
+		}
+		None => Subscription::none(),
+	}
+});
+
+def_browser_event!(KeyPressedEvent<KeyEventArgs>(&mut self, handler) {
+	match self.owner.upgrade() {
+		Some(owner) => {
+			owner.inner.listen_for_keys(self.owner.clone());
+			EventHandlers::add(&owner.1.borrow().keys_pressed, handler)
+		}
+		None => Subscription::none(),
+	}
+});
+
+impl KeyEventArgs {
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new(
+		key_code: u32, modifiers: Modifiers, is_repeat: bool, character: Option<char>,
+	) -> Self {
+		Self {
+			key_code,
+			modifiers,
+			is_repeat,
+			character,
+			consumed: Arc::new(Mutex::new(false)),
+		}
+	}
+
+	/// Keeps the key from being handled any further.
+	pub fn consume(&self) { *self.consumed.lock().unwrap() = true; }
+
+	/// Whether or not a handler has consumed the key so far.
+	pub fn is_consumed(&self) -> bool { *self.consumed.lock().unwrap() }
+}
+
+def_browser_event!(MessageEvent<MessageEventArgs>(&mut self, handler) {
+	match self.owner.upgrade() {
+		Some(owner) => EventHandlers::add(&owner.1.borrow().messages, handler),
+		None => Subscription::none(),
+	}
+});
+
+def_browser_event!(NavigationRequestEvent<NavigationRequestEventArgs>(&mut self, handler) {
+	match self.owner.upgrade() {
+		Some(owner) => EventHandlers::add(&owner.1.borrow().navigation_requests, handler),
+		None => Subscription::none(),
+	}
+});
+
+impl NavigationRequestEventArgs {
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new(
+		url: String, is_main_frame: bool, user_gesture: bool, navigation_type: NavigationType,
+	) -> Self {
+		Self {
+			url,
+			is_main_frame,
+			user_gesture,
+			navigation_type,
+			policy: Arc::new(Mutex::new(NavigationPolicy::Allow)),
+		}
+	}
+
+	/// What is going to happen with the navigation, as decided so far.
+	pub fn policy(&self) -> NavigationPolicy { *self.policy.lock().unwrap() }
+
+	pub fn set_policy(&self, policy: NavigationPolicy) { *self.policy.lock().unwrap() = policy; }
+}
+
+def_browser_event!(RequestEvent<RequestEventArgs>(&mut self, handler) {
+	match self.owner.upgrade() {
+		Some(owner) => {
+			// Requests are only intercepted once someone is interested in them.
+			owner.inner.listen_for_requests(self.owner.clone());
+			EventHandlers::add(&owner.1.borrow().requests, handler)
+		}
+		None => Subscription::none(),
+	}
+});
+
+/// A clone has the same information, but it can't answer the request.
+impl Clone for AuthCredentialsEventArgs {
+	fn clone(&self) -> Self {
+		Self {
+			host: self.host.clone(),
+			port: self.port,
+			realm: self.realm.clone(),
+			scheme: self.scheme.clone(),
+			is_proxy: self.is_proxy,
+			is_retry: self.is_retry,
+			answer: None,
+		}
+	}
+}
+
+impl AuthCredentialsEventArgs {
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new(
+		host: String, port: u16, realm: String, scheme: String, is_proxy: bool, is_retry: bool,
+		answer: impl FnOnce(Option<Credentials>) + 'static,
+	) -> Self {
+		Self {
+			host,
+			port,
+			realm,
+			scheme,
+			is_proxy,
+			is_retry,
+			answer: Some(Answer::new(answer)),
+		}
+	}
+}
+
+impl Answerable for AuthCredentialsEventArgs {
+	type Answer = Option<Credentials>;
+
+	fn take_answer(&mut self) -> Option<Answer<Self::Answer>> { self.answer.take() }
+}
+
+/// A clone has the same information, but it can't decide whether to trust the
+/// certificate.
+impl Clone for CertificateErrorEventArgs {
+	fn clone(&self) -> Self {
+		Self {
+			url: self.url.clone(),
+			error: self.error,
+			certificate: self.certificate.clone(),
+			answer: None,
+		}
+	}
+}
+
+impl CertificateErrorEventArgs {
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new(
+		url: String, error: CertificateError, certificate: Certificate,
+		answer: impl FnOnce(bool) + 'static,
+	) -> Self {
+		Self {
+			url,
+			error,
+			certificate,
+			answer: Some(Answer::new(answer)),
+		}
+	}
+}
+
+impl Answerable for CertificateErrorEventArgs {
+	type Answer = bool;
+
+	fn take_answer(&mut self) -> Option<Answer<Self::Answer>> { self.answer.take() }
+}
+
+/// A clone has the same information, but it can't answer the request.
+impl Clone for SelectClientCertificateEventArgs {
+	fn clone(&self) -> Self {
+		Self {
+			host: self.host.clone(),
+			port: self.port,
+			is_proxy: self.is_proxy,
+			candidates: self.candidates.clone(),
+			answer: None,
+		}
+	}
+}
+
+impl SelectClientCertificateEventArgs {
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new(
+		host: String, port: u16, is_proxy: bool, candidates: Vec<Certificate>,
+		answer: impl FnOnce(Option<ClientCertificate>) + 'static,
+	) -> Self {
+		Self {
+			host,
+			port,
+			is_proxy,
+			candidates,
+			answer: Some(Answer::new(answer)),
+		}
+	}
+
+	/// Returns the given certificate if the browser window is able to identify
+	/// itself with it.
+	/// CEF can only use the candidates, so on CEF any other certificate gives
+	/// an error of kind `Unsupported`. Without this check, such a certificate
+	/// would make CEF send no certificate at all.
+	pub fn check(&self, certificate: ClientCertificate) -> io::Result<ClientCertificate> {
+		if cfg!(feature = "cef") {
+			let ders: Vec<Vec<u8>> = self.candidates.iter().map(|c| c.der.clone()).collect();
+			if certificate.candidate_index(&ders).is_none() {
+				return Err(io::Error::new(
+					io::ErrorKind::Unsupported,
+					"CEF can only use one of the candidates as a client certificate",
+				));
+			}
+		}
+		Ok(certificate)
+	}
+}
+
+impl Answerable for SelectClientCertificateEventArgs {
+	type Answer = Option<ClientCertificate>;
+
+	fn take_answer(&mut self) -> Option<Answer<Self::Answer>> { self.answer.take() }
+}
+
+impl fmt::Display for DownloadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Cancelled => write!(f, "download cancelled"),
+			Self::Failed(message) => write!(f, "download failed: {}", message),
+		}
+	}
+}
+
+impl StdError for DownloadError {}
+
+/// A clone has the same information, but it can't decide where the file is
+/// saved.
+impl Clone for DownloadStartedEventArgs {
+	fn clone(&self) -> Self {
+		Self {
+			id: self.id,
+			url: self.url.clone(),
+			suggested_filename: self.suggested_filename.clone(),
+			mime_type: self.mime_type.clone(),
+			total_size: self.total_size,
+			answer: None,
+		}
+	}
+}
+
+impl DownloadStartedEventArgs {
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new(
+		id: u32, url: String, suggested_filename: String, mime_type: String,
+		total_size: Option<u64>, answer: impl FnOnce(Option<PathBuf>) + 'static,
+	) -> Self {
+		Self {
+			id,
+			url,
+			suggested_filename,
+			mime_type,
+			total_size,
+			answer: Some(Answer::new(answer)),
+		}
+	}
+}
+
+impl Answerable for DownloadStartedEventArgs {
+	type Answer = Option<PathBuf>;
+
+	fn take_answer(&mut self) -> Option<Answer<Self::Answer>> { self.answer.take() }
+}
+
+/// A clone has the same information, but it can't choose the files.
+impl Clone for FileDialogEventArgs {
+	fn clone(&self) -> Self {
+		Self {
+			mode: self.mode,
+			title: self.title.clone(),
+			default_path: self.default_path.clone(),
+			accept: self.accept.clone(),
+			answer: None,
+		}
+	}
+}
+
+impl FileDialogEventArgs {
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new(
+		mode: FileDialogMode, title: String, default_path: Option<PathBuf>, accept: Vec<String>,
+		answer: impl FnOnce(Vec<PathBuf>) + 'static,
+	) -> Self {
+		Self {
+			mode,
+			title,
+			default_path,
+			accept,
+			answer: Some(Answer::new(answer)),
+		}
+	}
+}
+
+impl Answerable for FileDialogEventArgs {
+	type Answer = Vec<PathBuf>;
+
+	fn take_answer(&mut self) -> Option<Answer<Self::Answer>> { self.answer.take() }
+}
+
+impl<T: Default> Answer<T> {
+	pub(crate) fn new(answer: impl FnOnce(T) + 'static) -> Self { Self(Some(Box::new(answer))) }
+
+	/// Drops the answer without giving one, not even the default value.
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	fn withdraw(mut self) { self.0 = None; }
+
+	fn give(mut self, value: T) {
+		if let Some(answer) = self.0.take() {
+			answer(value);
+		}
+	}
+}
+
+impl<T: Default> Drop for Answer<T> {
+	fn drop(&mut self) {
+		if let Some(answer) = self.0.take() {
+			answer(T::default());
+		}
+	}
+}
+
+impl Credentials {
+	pub fn new(username: &str, password: &str) -> Self {
+		Self {
+			username: username.to_owned(),
+			password: password.to_owned(),
+		}
+	}
+}
+
+impl RequestEventArgs {
+	#[cfg_attr(feature = "edge2", allow(dead_code))]
+	pub(crate) fn new(url: String, method: String, headers: Vec<(String, String)>) -> Self {
+		Self {
+			url,
+			method,
+			headers,
+			changes: Arc::new(Mutex::new(RequestChanges {
+				action: RequestAction::Continue,
+				headers: Vec::new(),
+			})),
+		}
+	}
+
+	/// What is going to happen with the request, as decided so far.
+	pub fn action(&self) -> RequestAction { self.changes.lock().unwrap().action.clone() }
+
+	/// Not available on WebkitGTK, which doesn't let requests be changed.
+	#[cfg(not(feature = "webkitgtk"))]
+	pub fn set_action(&self, action: RequestAction) {
+		self.changes.lock().unwrap().action = action;
+	}
+
+	/// Returns the value of the first header with the given name, if any.
+	/// The name is matched case-insensitively, and the changes made by the
+	/// handlers are taken into account.
+	pub fn header(&self, name: &str) -> Option<String> {
+		let changes = self.changes.lock().unwrap();
+		match changes
+			.headers
+			.iter()
+			.rev()
+			.find(|(n, _)| n.eq_ignore_ascii_case(name))
+		{
+			Some((_, value)) => value.clone(),
+			None => find_header(&self.headers, name).map(str::to_owned),
+		}
+	}
+
+	/// The header changes that the handlers have made, in the order in which
+	/// they have been made.
+	#[cfg_attr(any(feature = "gtk", feature = "edge2"), allow(dead_code))]
+	pub(crate) fn header_changes(&self) -> Vec<(String, Option<String>)> {
+		self.changes.lock().unwrap().headers.clone()
+	}
+
+	/// Removes all headers with the given name from the request.
+	/// Not available on WebkitGTK.
+	#[cfg(not(feature = "webkitgtk"))]
+	pub fn remove_header(&self, name: &str) {
+		self.changes
+			.lock()
+			.unwrap()
+			.headers
+			.push((name.to_owned(), None));
+	}
+
+	/// Sets a header of the request, replacing any header with the same name.
+	/// Not available on WebkitGTK.
+	#[cfg(not(feature = "webkitgtk"))]
+	pub fn set_header(&self, name: &str, value: &str) {
+		self.changes
+			.lock()
+			.unwrap()
+			.headers
+			.push((name.to_owned(), Some(value.to_owned())));
+	}
+}
+
+impl ResponseEventArgs {
+	/// Returns the value of the first header with the given name, if any.
+	/// The name is matched case-insensitively.
+	pub fn header(&self, name: &str) -> Option<&str> { find_header(&self.headers, name) }
+}
+
+/// A clone has the same command and arguments, but it can't settle the promise
+/// returned by `invoke_extern`.
+impl Clone for MessageEventArgs {
+	fn clone(&self) -> Self {
+		Self {
+			cmd: self.cmd.clone(),
+			args: self.args.clone(),
+			responder: None,
+		}
+	}
+}
+
+impl MessageEventArgs {
+	pub(crate) fn new(
+		owner: Weak<BrowserWindowOwner>, id: u32, cmd: String, args: Vec<JsValue>,
+	) -> Self {
+		Self {
+			cmd,
+			args,
+			responder: Some(Responder {
+				owner,
+				id,
+				settled: false,
+			}),
+		}
+	}
+
+	/// Converts the argument at the given index into `T`.
+	/// A missing argument is treated as `undefined`, just like in JavaScript.
+	/// If the conversion fails, the error tells you which part of the argument
+	/// was wrong, e.g. `args[2].user.id: expected an integer, found 1.5`.
+	pub fn arg<T>(&self, index: usize) -> Result<T, JsConversionError>
+	where
+		T: TryFrom<JsValue, Error = JsConversionError>,
+	{
+		let value = self.args.get(index).cloned().unwrap_or(JsValue::Undefined);
+		T::try_from(value).map_err(|e| e.at_index(index).at_variable("args"))
+	}
+}
+
+impl Responder {
+	fn respond(mut self, result: Result<JsValue, JsValue>) { self.settle(result); }
+
+	fn settle(&mut self, result: Result<JsValue, JsValue>) {
+		if self.settled {
+			return;
+		}
+		self.settled = true;
+
+		if let Some(owner) = self.owner.upgrade() {
+			let (ok, value) = match result {
+				Ok(v) => (true, v),
+				Err(v) => (false, v),
+			};
+			let js = format!(
+				"window.__bw_settle && window.__bw_settle({}, {}, {})",
+				self.id, ok, value
+			);
+			#[cfg(not(feature = "threadsafe"))]
+			owner
+				.inner
+				.eval_js(&js, ignore_eval_js_result, ptr::null_mut());
+			#[cfg(feature = "threadsafe")]
+			owner
+				.inner
+				.eval_js_threadsafe(&js, ignore_eval_js_result, ptr::null_mut());
+		}
+	}
+}
+
+impl Drop for Responder {
+	fn drop(&mut self) { self.settle(Ok(JsValue::Undefined)); }
+}
+
+/// Wraps the handler of `on_invoke` or `bind`, so that its result settles the
+/// promise that has been returned in JavaScript.
+#[cfg(not(feature = "threadsafe"))]
+fn invoke_handler<X>(mut handler: X) -> BrowserWindowEventHandler<MessageEventArgs>
+where
+	X: FnMut(&BrowserWindowHandle, MessageEventArgs) -> Result<JsValue, JsValue> + 'static,
+{
+	EventHandler::Sync(Box::new(move |h, mut e| {
+		let responder = e.responder.take();
+		let result = handler(h, e);
+		if let Some(r) = responder {
+			r.respond(result);
+		}
+	}))
+}
+
+/// Wraps the handler of `on_invoke` or `bind`, so that its result settles the
+/// promise that has been returned in JavaScript.
+#[cfg(feature = "threadsafe")]
+fn invoke_handler<X>(mut handler: X) -> BrowserWindowEventHandler<MessageEventArgs>
+where
+	X: FnMut(&BrowserWindowHandle, MessageEventArgs) -> Result<JsValue, JsValue> + Send + 'static,
+{
+	EventHandler::Sync(Box::new(move |h, mut e| {
+		let responder = e.responder.take();
+		let result = handler(h, e);
+		if let Some(r) = responder {
+			r.respond(result);
+		}
+	}))
+}
+
+/// Like `invoke_handler`, but the output of the future settles the promise.
+#[cfg(not(feature = "threadsafe"))]
+fn invoke_handler_async<X, F>(mut handler: X) -> BrowserWindowEventHandler<MessageEventArgs>
+where
+	X: FnMut(BrowserWindow, MessageEventArgs) -> F + 'static,
+	F: Future<Output = Result<JsValue, JsValue>> + 'static,
+{
+	EventHandler::Async(Box::new(move |bw, mut e| {
+		let responder = e.responder.take();
+		let future = handler(bw, e);
+		Box::pin(async move {
+			let result = future.await;
+			if let Some(r) = responder {
+				r.respond(result);
+			}
+		})
+	}))
+}
+
+/// Like `invoke_handler`, but the output of the future settles the promise.
+#[cfg(feature = "threadsafe")]
+fn invoke_handler_async<X, F>(mut handler: X) -> BrowserWindowEventHandler<MessageEventArgs>
+where
+	X: FnMut(BrowserWindow, MessageEventArgs) -> F + Send + 'static,
+	F: Future<Output = Result<JsValue, JsValue>> + 'static,
+{
+	EventHandler::Async(Box::new(move |bw, mut e| {
+		let responder = e.responder.take();
+		let future = handler(bw, e);
+		Box::pin(async move {
+			let result = future.await;
+			if let Some(r) = responder {
+				r.respond(result);
+			}
+		})
+	}))
+}
+
+/// Wraps a handler of which the result answers the question of the browser.
+fn answer_handler<A, X>(mut handler: X) -> BrowserWindowEventHandler<A>
+where
+	A: Answerable + 'static,
+	X: FnMut(&BrowserWindowHandle, A) -> A::Answer + MaybeSend + 'static,
+{
+	EventHandler::Sync(Box::new(move |h, mut e| {
+		let answer = e.take_answer();
+		let value = handler(h, e);
+		if let Some(a) = answer {
+			a.give(value);
+		}
+	}))
+}
+
+/// Like `answer_handler`, but the output of the future is the answer.
+fn answer_handler_async<A, X, F>(mut handler: X) -> BrowserWindowEventHandler<A>
+where
+	A: Answerable + 'static,
+	X: FnMut(BrowserWindow, A) -> F + MaybeSend + 'static,
+	F: Future<Output = A::Answer> + 'static,
+{
+	EventHandler::Async(Box::new(move |bw, mut e| {
+		let answer = e.take_answer();
+		let future = handler(bw, e);
+		Box::pin(async move {
+			let value = future.await;
+			if let Some(a) = answer {
+				a.give(value);
+			}
+		})
+	}))
+}
+
+fn ignore_eval_js_result(
+	_handle: BrowserWindowImpl, _cb_data: *mut (), _result: Result<JsValue, JsEvaluationError>,
+) {
+}
+
+fn eval_js_callback<H>(
+	_handle: BrowserWindowImpl, cb_data: *mut (), result: Result<JsValue, JsEvaluationError>,
+) where
+	H: FnOnce(&BrowserWindowHandle, Result<JsValue, JsEvaluationError>),
+{
+	let data_ptr = cb_data as *mut H;
+	let data = unsafe { Box::from_raw(data_ptr) };
+
+	let handle = BrowserWindowHandle::new(_handle);
+
+	(*data)(&handle, result);
+}
+
+/// Opens the URL with the program that the system uses for it, which usually is
+/// the default browser.
+#[cfg_attr(feature = "edge2", allow(dead_code))]
+fn open_externally(url: &str) -> io::Result<()> {
+	// The URL comes from the page, so it must not pass through a shell like
+	// `cmd`, which would interpret characters like `&` in it.
+	#[cfg(target_os = "windows")]
+	let mut child = Command::new("rundll32")
+		.arg("url.dll,FileProtocolHandler")
+		.arg(url)
+		.spawn()?;
+	#[cfg(target_os = "macos")]
+	let mut child = Command::new("open").arg(url).spawn()?;
+	#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+	let mut child = Command::new("xdg-open").arg(url).spawn()?;
+
+	// The process is waited on in the background, so that it doesn't linger
+	// around as a zombie.
+	thread::spawn(move || child.wait());
+	Ok(())
+}
+
+/// Whether the main frame may navigate to `url`, given the origins that have
+/// been allowed with `BrowserWindowBuilder::allow_origin`.
+/// `about:` and `data:` URLs are always allowed, as the window loads its own
+/// HTML sources with them.
+#[cfg_attr(feature = "edge2", allow(dead_code))]
+pub(crate) fn is_origin_allowed(allowed_origins: &[String], url: &str) -> bool {
+	if allowed_origins.is_empty() {
+		return true;
+	}
+	match url_origin(url) {
+		Some(origin) => allowed_origins.contains(&origin),
+		None => {
+			let url = url.to_ascii_lowercase();
+			url.starts_with("about:") || url.starts_with("data:")
+		}
+	}
+}
+
+/// The host of a URL, e.g. `example.com` for
+/// `https://user@example.com:8080/index.html`.
+#[cfg_attr(feature = "edge2", allow(dead_code))]
+pub(crate) fn url_host(url: &str) -> &str { url_parts(url).0 }
+
+/// The origin of a URL, normalized so that it can be compared: the scheme and
+/// host are lowercased, and the default port is left out.
+/// E.g. `https://example.com` for `HTTPS://user@Example.com:443/index.html`.
+/// Returns `None` for URLs without an origin, like `about:blank`.
+pub(crate) fn url_origin(url: &str) -> Option<String> {
+	let scheme = &url[..url.find("://")?];
+	let (host, port) = url_parts(url);
+	let scheme = scheme.to_ascii_lowercase();
+	let default_port = match scheme.as_str() {
+		"http" | "ws" => Some("80"),
+		"https" | "wss" => Some("443"),
+		_ => None,
+	};
+	let mut origin = format!("{}://{}", scheme, host.to_ascii_lowercase());
+	if let Some(port) = port.filter(|p| Some(*p) != default_port) {
+		origin.push(':');
+		origin.push_str(port);
+	}
+	Some(origin)
+}
+
+/// The host and port of a URL.
+/// The URL is taken to start with its authority if it doesn't have a scheme.
+fn url_parts(url: &str) -> (&str, Option<&str>) {
+	let start = url.find("://").map(|i| i + 3).unwrap_or(0);
+	let authority = match url[start..].find(['/', '?', '#']) {
+		Some(end) => &url[start..(start + end)],
+		None => &url[start..],
+	};
+	let host = match authority.rfind('@') {
+		Some(i) => &authority[(i + 1)..],
+		None => authority,
+	};
+	// An IPv6 address contains colons as well, but ends with a bracket.
+	match host.rfind(':') {
+		Some(i) if !host.ends_with(']') => (&host[..i], Some(&host[(i + 1)..])),
+		_ => (host, None),
+	}
 }
@@ -32,12 +32,27 @@ pub enum Source {
 /// let bw = bwb.build( app );
 /// ```
 pub struct BrowserWindowBuilder {
+	allowed_origins: Vec<String>,
 	dev_tools: bool,
 	source: Source,
 	window: WindowBuilder,
 }
 
 impl BrowserWindowBuilder {
+	/// Allows the main frame to navigate to pages of the given origin, e.g.
+	/// `https://example.com` or `http://localhost:8080`.
+	/// Once an origin has been allowed, navigations to any other origin start
+	/// out with policy `Cancel`. See `BrowserWindow::on_navigation_request`.
+	/// Origins are compared case-insensitively, and without their default
+	/// port. `about:` and `data:` URLs are always allowed.
+	/// As WebkitGTK doesn't tell whether an iframe or the main frame navigates,
+	/// the allowed origins apply to iframes too on WebkitGTK.
+	pub fn allow_origin(&mut self, origin: &str) -> &mut Self {
+		self.allowed_origins
+			.push(url_origin(origin).unwrap_or_else(|| origin.to_owned()));
+		self
+	}
+
 	/// Sets whether or not an extra window with developer tools will be opened
 	/// together with this browser. When in debug mode the default is `true`.
 	/// When in release mode the default is `false`.
@@ -52,6 +67,7 @@ impl BrowserWindowBuilder {
 	/// * `source` - The content that will be displayed in the browser window.
 	pub fn new(source: Source) -> Self {
 		Self {
+			allowed_origins: Vec::new(),
 			dev_tools: false,
 			source,
 			window: WindowBuilder::new(),
@@ -70,13 +86,14 @@ impl BrowserWindowBuilder {
 	pub async fn build_async(self, app: &ApplicationHandle) -> BrowserWindow {
 		let (tx, rx) = oneshot::channel::<BrowserWindowHandle>();
 
+		let allowed_origins = self.allowed_origins.clone();
 		self._build(app, move |handle| {
 			if let Err(_) = tx.send(handle) {
 				panic!("Unable to send browser handle back")
 			}
 		});
 
-		Self::prepare_handle(rx.await.unwrap())
+		Self::prepare_handle(rx.await.unwrap(), allowed_origins)
 	}
 
 	/// Creates the browser window.
@@ -93,39 +110,65 @@ impl BrowserWindowBuilder {
 	pub async fn build_threaded(
 		self, app: &ApplicationHandleThreaded,
 	) -> Result<BrowserWindowThreaded, DelegateError> {
-		let (tx, rx) = oneshot::channel::<UnsafeSend<BrowserWindowHandle>>();
+		let (tx, rx) = oneshot::channel::<UnsafeSend<BrowserWindow>>();
 
-		// We need to dispatch the spawning of the browser to the GUI thread
+		// We need to dispatch the spawning of the browser to the GUI thread. The handle
+		// is prepared over there as well, because it touches the window.
+		let allowed_origins = self.allowed_origins.clone();
 		app.delegate(|app_handle| {
 			self._build(&*app_handle, |inner_handle| {
-				if let Err(_) = tx.send(UnsafeSend::new(inner_handle)) {
+				let handle = Self::prepare_handle(inner_handle, allowed_origins);
+				if let Err(_) = tx.send(UnsafeSend::new(handle)) {
 					panic!("Unable to send browser handle back")
 				}
 			});
 		})
 		.await?;
 
-		Ok(BrowserWindowThreaded(Self::prepare_handle(
-			rx.await.unwrap().unwrap(),
-		)))
+		Ok(BrowserWindowThreaded(rx.await.unwrap().unwrap()))
 	}
 
-	fn prepare_handle(handle: BrowserWindowHandle) -> BrowserWindow {
+	fn prepare_handle(handle: BrowserWindowHandle, allowed_origins: Vec<String>) -> BrowserWindow {
 		// Put a reference counted handle in the user data of the window, so that there
 		// exists 'ownership' for as long as the window actually lives.
-		let owner = BrowserWindowOwner(handle);
+		let owner = BrowserWindowOwner::new(handle, allowed_origins);
 		let rc_handle = Rc::new(owner);
-		let user_data = Box::into_raw(Box::new(BrowserUserData {
-			_handle: rc_handle.clone(),
+		let user_data = Box::into_raw(Box::new(WindowUserData {
+			_owner: rc_handle.clone(),
 		}));
 		rc_handle.0.window().0.set_user_data(user_data as _);
 
+		// Messages from `invoke_extern` are always listened for, so that bound
+		// functions work even when `on_message` is never used.
+		rc_handle
+			.0
+			.inner
+			.listen_for_messages(Rc::downgrade(&rc_handle));
+		// The same goes for navigation requests and the allowed origins.
+		rc_handle
+			.0
+			.inner
+			.listen_for_navigation_requests(Rc::downgrade(&rc_handle));
+		// And for certificate errors, because pinned certificates are trusted even
+		// without any handlers.
+		rc_handle
+			.0
+			.inner
+			.listen_for_certificate_errors(Rc::downgrade(&rc_handle));
+		// Downloads started by `download` need to be recognized, even without any
+		// handlers.
+		rc_handle
+			.0
+			.inner
+			.listen_for_downloads(Rc::downgrade(&rc_handle));
+
 		BrowserWindow(rc_handle)
 	}
 
 	pub fn build_sync(self, app: &ApplicationHandle, on_created: impl FnOnce(BrowserWindow)) {
+		let allowed_origins = self.allowed_origins.clone();
 		self._build(app, move |inner| {
-			let handle = Self::prepare_handle(inner);
+			let handle = Self::prepare_handle(inner, allowed_origins);
 			on_created(handle);
 		})
 	}
@@ -136,7 +179,10 @@ impl BrowserWindowBuilder {
 				source,
 				dev_tools,
 				window,
+				..
 			} => {
+				let window_options = window.window_options();
+
 				// Parent
 				let parent_handle = match window.parent {
 					None => WindowImpl::default(),
@@ -153,13 +199,6 @@ impl BrowserWindowBuilder {
 					Box::into_raw(Box::new(Box::new(on_created)));
 
 				// Convert options to FFI structs
-				let window_options = WindowOptions {
-					borders: window.borders,
-					decorated: window.decorated,
-					keep_above: window.keep_above,
-					minimizable: window.minimizable,
-					resizable: window.resizable,
-				};
 				let other_options = BrowserWindowOptions {
 					dev_tools: if dev_tools { 1 } else { 0 },
 					resource_path: "".into(),
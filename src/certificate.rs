@@ -0,0 +1,172 @@
+//! This module contains the types that describe the TLS certificates of the
+//! servers that a browser window connects to.
+//!
+//! Whenever a server presents a certificate that isn't trusted,
+//! [`on_certificate_error`](crate::browser::BrowserWindow::on_certificate_error)
+//! is fired, which lets you decide whether to trust it anyway. Certificates can
+//! also be trusted up front, by the application settings. This is useful for
+//! on-premise servers with self-signed certificates:
+//!
+//! ```no_run
+//! use browser_window::application::*;
+//!
+//! let mut settings = ApplicationSettings::default();
+//! let pem = std::fs::read_to_string("intranet.pem").unwrap();
+//! settings.pin_certificate("intranet.local", &pem);
+//! let app = Application::initialize(&settings).unwrap();
+//! ```
+//!
+//! Servers that require mutual TLS ask the browser window for a client
+//! certificate, which can be given to
+//! [`on_select_client_certificate`](crate::browser::BrowserWindow::on_select_client_certificate)
+//! as a [`ClientCertificate`].
+
+use std::{fs, io, path::Path, time::SystemTime};
+
+use crate::{application::ApplicationSettings, core::certificate::decode_base64};
+
+/// A certificate that has been presented by a server.
+#[derive(Clone, Debug)]
+pub struct Certificate {
+	pub subject: String,
+	pub issuer: String,
+	pub valid_from: Option<SystemTime>,
+	pub valid_until: Option<SystemTime>,
+	/// The DER encoding of the certificate.
+	pub der: Vec<u8>,
+}
+
+/// A certificate that a browser window can identify itself with.
+#[derive(Clone, Debug)]
+pub enum ClientCertificate {
+	/// One of the candidates that the event has been fired with, by index.
+	Candidate(usize),
+	/// A certificate with its private key, both in PEM format.
+	/// CEF can only use it if it is one of the candidates as well, see
+	/// [`check`](crate::browser::SelectClientCertificateEventArgs::check).
+	Pem { certificate: String, key: String },
+	/// A PKCS#12 archive, which contains the certificate and its private key.
+	/// WebkitGTK only: CEF can't import certificates.
+	Pkcs12 {
+		data: Vec<u8>,
+		password: Option<String>,
+	},
+}
+
+/// The reason why a certificate isn't trusted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CertificateError {
+	/// The certificate is meant for another host.
+	HostMismatch,
+	/// The certificate has expired, or isn't valid yet.
+	InvalidDate,
+	/// The certificate hasn't been issued by a trusted certificate authority.
+	UnknownAuthority,
+	Revoked,
+	/// The certificate is malformed, or uses an insecure algorithm.
+	Invalid,
+	Other,
+}
+
+impl ClientCertificate {
+	/// Reads a certificate and its private key from PEM files.
+	/// They may also be the same file.
+	pub fn from_pem_files(
+		certificate: impl AsRef<Path>, key: impl AsRef<Path>,
+	) -> io::Result<Self> {
+		Ok(Self::Pem {
+			certificate: fs::read_to_string(certificate)?,
+			key: fs::read_to_string(key)?,
+		})
+	}
+
+	/// Reads a PKCS#12 archive, usually with extension `.p12` or `.pfx`.
+	/// Gives an error of kind `Unsupported` on CEF, which can't use them.
+	pub fn from_pkcs12_file(path: impl AsRef<Path>, password: Option<&str>) -> io::Result<Self> {
+		if cfg!(feature = "cef") {
+			return Err(io::Error::new(
+				io::ErrorKind::Unsupported,
+				"CEF can't use PKCS#12 archives as client certificates",
+			));
+		}
+		Ok(Self::Pkcs12 {
+			data: fs::read(path)?,
+			password: password.map(|p| p.to_owned()),
+		})
+	}
+
+	/// The index of the candidate that is meant by this client certificate, if
+	/// any.
+	pub(crate) fn candidate_index(&self, candidates: &[Vec<u8>]) -> Option<usize> {
+		match self {
+			Self::Candidate(index) if *index < candidates.len() => Some(*index),
+			Self::Pem { certificate, .. } => {
+				let der = decode_pem(certificate).into_iter().next()?;
+				candidates.iter().position(|c| *c == der)
+			}
+			_ => None,
+		}
+	}
+}
+
+/// The certificates that are trusted by the application settings, which the
+/// application keeps.
+pub(crate) struct Trust {
+	/// The extra CA certificates, in PEM format.
+	#[cfg_attr(not(feature = "gtk"), allow(dead_code))]
+	pub ca_certificates: Vec<String>,
+	pinned_certificates: Vec<(String, Vec<u8>)>,
+}
+
+impl Trust {
+	pub fn new(settings: &ApplicationSettings) -> Self {
+		Self {
+			ca_certificates: settings.trusted_ca_certificates.clone(),
+			pinned_certificates: settings
+				.pinned_certificates
+				.iter()
+				.flat_map(|(host, pem)| {
+					decode_pem(pem)
+						.into_iter()
+						.map(move |der| (host.clone(), der))
+				})
+				.collect(),
+		}
+	}
+
+	/// Whether the certificate has been pinned for the given host.
+	pub fn is_pinned(&self, host: &str, der: &[u8]) -> bool {
+		self.pinned_certificates
+			.iter()
+			.any(|(h, d)| h.eq_ignore_ascii_case(host) && d == der)
+	}
+}
+
+/// Decodes all certificates in the given PEM data into DER.
+/// Anything that isn't a certificate is skipped.
+pub fn decode_pem(pem: &str) -> Vec<Vec<u8>> { decode_pem_blocks(pem, "CERTIFICATE") }
+
+/// Decodes the contents of all PEM blocks with the given label.
+pub(crate) fn decode_pem_blocks(pem: &str, label: &str) -> Vec<Vec<u8>> {
+	let begin = format!("-----BEGIN {}-----", label);
+	let end = format!("-----END {}-----", label);
+
+	let mut blocks = Vec::new();
+	let mut rest = pem;
+	while let Some(start) = rest.find(&begin) {
+		rest = &rest[(start + begin.len())..];
+		let stop = match rest.find(&end) {
+			Some(s) => s,
+			None => break,
+		};
+		let base64: String = rest[..stop]
+			.chars()
+			.filter(|c| !c.is_ascii_whitespace())
+			.collect();
+		if let Some(der) = decode_base64(&base64) {
+			blocks.push(der);
+		}
+		rest = &rest[(stop + end.len())..];
+	}
+	blocks
+}
@@ -18,6 +18,9 @@ pub struct CookieIterator<'a> {
 impl Cookie {
 	pub fn new(name: &str, value: &str) -> Self { Self(CookieImpl::new(name, value)) }
 
+	/// The time at which the cookie has been created.
+	/// WebkitGTK doesn't keep track of this, so for the cookies that it gives
+	/// this is always `UNIX_EPOCH`.
 	pub fn creation_time(&self) -> SystemTime { self.0.creation_time() }
 
 	pub fn expires(&self) -> Option<SystemTime> { self.0.expires() }
@@ -212,6 +215,7 @@ impl CookieJar {
 
 	/// Returns a `CookieIterator` that iterators over cookies asynchronously.
 	/// Like `iter`, but iterates over all cookies from any url.
+	/// On WebkitGTK, this needs at least version 2.42.
 	pub fn iter_all<'a>(&'a self) -> CookieIterator<'a> {
 		let inner = self.0.iterator_all();
 
@@ -1,5 +1,6 @@
 pub mod application;
 pub mod browser_window;
+pub mod certificate;
 pub mod cookie;
 pub mod error;
 pub mod prelude;
@@ -6,12 +6,18 @@
 use std::{
 	os::raw::{c_char, c_int, c_void},
 	path::PathBuf,
-	ptr,
+	ptr, slice,
 	time::Duration,
 };
 
 use super::{ApplicationExt, ApplicationSettings};
-use crate::{error::*, prelude::*};
+use crate::{
+	certificate::Trust,
+	core::browser_window::INVOKE_EXTERN_JS,
+	error::*,
+	prelude::*,
+	scheme::{Scheme, SchemeRequest},
+};
 
 #[derive(Clone, Copy)]
 pub struct ApplicationImpl {
@@ -63,11 +69,48 @@ impl ApplicationExt for ApplicationImpl {
 	fn initialize(
 		argc: c_int, argv: *mut *mut c_char, settings: &ApplicationSettings,
 	) -> Result<Self> {
+		// There is no way to check certificates against other CAs.
+		if !settings.trusted_ca_certificates.is_empty() {
+			return Err(Error::new(
+				"trusting extra CA certificates is only supported for WebkitGTK",
+			));
+		}
+		#[cfg(feature = "edge2")]
+		if !settings.schemes.is_empty() {
+			return Err(Error::new(
+				"custom schemes are not supported for Edge WebView2",
+			));
+		}
+		// Certificate errors aren't handled for Edge WebView2.
+		#[cfg(feature = "edge2")]
+		if !settings.pinned_certificates.is_empty() {
+			return Err(Error::new(
+				"pinned certificates are not supported for Edge WebView2",
+			));
+		}
+
 		let exec_path: &str = match settings.engine_seperate_executable_path.as_ref() {
 			None => "",
 			Some(path) => path.to_str().unwrap(),
 		};
 
+		// The schemes are leaked on purpose, because they need to live as long as the
+		// application does.
+		let schemes: Vec<cbw_Scheme> = settings
+			.schemes
+			.iter()
+			.map(|scheme| cbw_Scheme {
+				name: scheme.name.as_str().into(),
+				handler: Some(ffi_scheme_handler),
+				data: Box::into_raw(Box::new(scheme.clone())) as _,
+			})
+			.collect();
+		let invoke_extern_js: &'static str = Box::leak(
+			INVOKE_EXTERN_JS
+				.replace("POST_MESSAGE", "window.__bw_post_message")
+				.into_boxed_str(),
+		);
+
 		let c_settings = cbw_ApplicationSettings {
 			engine_seperate_executable_path: exec_path.into(),
 			resource_dir: settings
@@ -79,6 +122,9 @@ impl ApplicationExt for ApplicationImpl {
 				.as_ref()
 				.into(),
 			remote_debugging_port: settings.remote_debugging_port.unwrap_or(0),
+			schemes: schemes.as_ptr(),
+			scheme_count: schemes.len() as _,
+			invoke_extern_js: invoke_extern_js.into(),
 		};
 
 		let mut c_handle: *mut cbw_Application = ptr::null_mut();
@@ -86,6 +132,10 @@ impl ApplicationExt for ApplicationImpl {
 		if c_err.code != 0 {
 			return Err(c_err.into());
 		}
+		// Leaked on purpose as well, like the schemes.
+		unsafe {
+			(*c_handle).user_data = Box::into_raw(Box::new(Trust::new(settings))) as _;
+		}
 
 		Ok(Self { inner: c_handle })
 	}
@@ -105,6 +155,12 @@ impl ApplicationExt for ApplicationImpl {
 	}
 }
 
+impl ApplicationImpl {
+	/// The certificates that are trusted by the settings that the application
+	/// has been initialized with.
+	pub(crate) fn trust(&self) -> &Trust { unsafe { &*((*self.inner).user_data as *const Trust) } }
+}
+
 struct DispatchData {
 	func: unsafe fn(ApplicationImpl, *mut ()),
 	data: *mut (),
@@ -117,3 +173,44 @@ unsafe extern "C" fn invocation_handler(_handle: *mut cbw_Application, _data: *m
 
 	(data.func)(handle, data.data);
 }
+
+unsafe extern "C" fn ffi_scheme_handler(data: *mut c_void, c_request: *mut cbw_SchemeRequest) {
+	let scheme = &*(data as *const Scheme);
+	let r = &*c_request;
+
+	let request = SchemeRequest {
+		url: r.url.into(),
+		method: r.method.into(),
+		headers: c_slice(r.headers, r.header_count as _)
+			.iter()
+			.map(|header| (header.name.into(), header.value.into()))
+			.collect(),
+		body: c_slice(r.body, r.body_len as _).to_vec(),
+	};
+	let response = (scheme.handler)(&request);
+
+	let headers: Vec<cbw_HttpHeader> = response
+		.headers
+		.iter()
+		.map(|(name, value)| cbw_HttpHeader {
+			name: name.as_str().into(),
+			value: value.as_str().into(),
+		})
+		.collect();
+	let c_response = cbw_SchemeResponse {
+		status: response.status,
+		headers: headers.as_ptr(),
+		header_count: headers.len() as _,
+		body: response.body.as_ptr(),
+		body_len: response.body.len() as _,
+	};
+	cbw_SchemeRequest_respond(c_request, &c_response);
+}
+
+unsafe fn c_slice<'a, T>(data: *const T, len: usize) -> &'a [T] {
+	if len == 0 {
+		&[]
+	} else {
+		slice::from_raw_parts(data, len)
+	}
+}
@@ -4,52 +4,110 @@ use std::{
 		Arc,
 		atomic::{AtomicI32, Ordering},
 	},
+	thread::{self, ThreadId},
 	time::Duration,
 };
 
-use gtk::prelude::{ApplicationExt, ApplicationExtManual};
+use gtk::{
+	gio, glib,
+	prelude::{ApplicationExt, ApplicationExtManual, InputStreamExtManual, ObjectExt},
+};
+use unsafe_send_sync::UnsafeSend;
+use webkit2gtk::{
+	SecurityManagerExt, URISchemeRequest, URISchemeRequestExt, URISchemeResponse,
+	URISchemeResponseExt, WebContext, WebContextExt,
+};
 
 use super::{super::error::*, ApplicationSettings};
+use crate::{
+	certificate::Trust,
+	scheme::{Scheme, SchemeRequest},
+};
 
 #[derive(Clone)]
 pub struct ApplicationImpl {
 	pub inner: gtk::Application,
 	pub exit_code: Arc<AtomicI32>,
+	thread_id: ThreadId,
+	pub(crate) trust: Arc<Trust>,
 }
 
 impl super::ApplicationExt for ApplicationImpl {
-	fn assert_correct_thread(&self) { unimplemented!() }
+	fn assert_correct_thread(&self) {
+		assert!(
+			thread::current().id() == self.thread_id,
+			"browser-window used from a thread other than the GUI thread"
+		);
+	}
 
+	// The GLib functions that don't end in `_local` attach the work to the main
+	// context in a thread-safe manner. The work itself is always executed on the
+	// GUI thread, so the payload doesn't really get shared between threads.
 	fn dispatch(&self, work: fn(ApplicationImpl, *mut ()), data: *mut ()) -> bool {
-		let this = self.clone();
-		gtk::glib::source::idle_add_local_once(move || work(this, data));
+		let payload = UnsafeSend::new((self.clone(), data));
+		glib::source::idle_add_once(move || {
+			let (this, data) = payload.unwrap();
+			work(this, data)
+		});
 		true
 	}
 
 	fn dispatch_delayed(
 		&self, work: fn(ApplicationImpl, *mut ()), data: *mut (), delay: Duration,
 	) -> bool {
-		let this = self.clone();
-		gtk::glib::source::timeout_add_local_once(delay, move || work(this, data));
+		let payload = UnsafeSend::new((self.clone(), data));
+		glib::source::timeout_add_once(delay, move || {
+			let (this, data) = payload.unwrap();
+			work(this, data)
+		});
 		true
 	}
 
 	fn exit(&self, exit_code: i32) {
+		self.assert_correct_thread();
 		self.exit_code.store(exit_code, Ordering::Relaxed);
 		self.inner.quit();
 	}
 
-	fn exit_threadsafe(&self, exit_code: i32) { self.exit(exit_code); }
+	fn exit_threadsafe(&self, exit_code: i32) {
+		self.exit_code.store(exit_code, Ordering::Relaxed);
+		let inner = UnsafeSend::new(self.inner.clone());
+		glib::source::idle_add_once(move || inner.unwrap().quit());
+	}
 
 	fn free(&self) {}
 
 	fn initialize(
-		_argc: c_int, _argv: *mut *mut c_char, _settings: &ApplicationSettings,
+		_argc: c_int, _argv: *mut *mut c_char, settings: &ApplicationSettings,
 	) -> Result<Self> {
 		let inner = gtk::Application::builder().build();
+
+		// The web context is only available after GTK has started up, and the schemes
+		// need to be registered before any web view is created.
+		let schemes = settings.schemes.clone();
+		inner.connect_startup(move |_| {
+			let context = WebContext::default().expect("no default web context");
+			for scheme in &schemes {
+				register_scheme(&context, scheme);
+			}
+		});
+
+		// The exit code and the GUI thread are attached to the GTK application, so
+		// that the handles that are obtained from a GTK window share them.
+		let exit_code = Arc::new(AtomicI32::new(0));
+		let thread_id = thread::current().id();
+		let trust = Arc::new(Trust::new(settings));
+		unsafe {
+			inner.set_data("bw-exit-code", exit_code.clone());
+			inner.set_data("bw-thread-id", thread_id);
+			inner.set_data("bw-trust", trust.clone());
+		}
+
 		Ok(Self {
 			inner,
-			exit_code: Arc::new(AtomicI32::new(0)),
+			exit_code,
+			thread_id,
+			trust,
 		})
 	}
 
@@ -64,3 +122,85 @@ impl super::ApplicationExt for ApplicationImpl {
 		self.exit_code.load(Ordering::Relaxed)
 	}
 }
+
+impl ApplicationImpl {
+	/// Obtains the application handle for a GTK application that has been
+	/// initialized by us.
+	pub(crate) fn from_gtk(inner: gtk::Application) -> Self {
+		let exit_code = unsafe { inner.data::<Arc<AtomicI32>>("bw-exit-code") }
+			.map(|e| unsafe { e.as_ref() }.clone())
+			.expect("GTK application not initialized by browser-window");
+		let thread_id = unsafe { inner.data::<ThreadId>("bw-thread-id") }
+			.map(|t| *unsafe { t.as_ref() })
+			.expect("GTK application not initialized by browser-window");
+		let trust = unsafe { inner.data::<Arc<Trust>>("bw-trust") }
+			.map(|t| unsafe { t.as_ref() }.clone())
+			.expect("GTK application not initialized by browser-window");
+		Self {
+			inner,
+			exit_code,
+			thread_id,
+			trust,
+		}
+	}
+
+	/// The certificates that are trusted by the settings that the application
+	/// has been initialized with.
+	pub(crate) fn trust(&self) -> &Trust { &self.trust }
+}
+
+fn register_scheme(context: &WebContext, scheme: &Scheme) {
+	let handler = scheme.handler.clone();
+	context.register_uri_scheme(&scheme.name, move |request| {
+		let response = handler(&scheme_request(request));
+
+		let headers = soup::MessageHeaders::new(soup::MessageHeadersType::Response);
+		for (name, value) in &response.headers {
+			headers.append(name, value);
+		}
+		let content_type = response.header("Content-Type").map(|t| t.to_owned());
+
+		let body = glib::Bytes::from_owned(response.body);
+		let stream = gio::MemoryInputStream::from_bytes(&body);
+		let inner = URISchemeResponse::new(&stream, body.len() as _);
+		inner.set_status(response.status as _, None);
+		if let Some(content_type) = content_type {
+			inner.set_content_type(&content_type);
+		}
+		inner.set_http_headers(headers);
+		request.finish_with_response(&inner);
+	});
+
+	if let Some(security_manager) = context.security_manager() {
+		security_manager.register_uri_scheme_as_secure(&scheme.name);
+		security_manager.register_uri_scheme_as_cors_enabled(&scheme.name);
+	}
+}
+
+fn scheme_request(request: &URISchemeRequest) -> SchemeRequest {
+	let mut headers = Vec::new();
+	if let Some(h) = request.http_headers() {
+		h.foreach(|name, value| headers.push((name.to_owned(), value.to_owned())));
+	}
+
+	let mut body = Vec::new();
+	if let Some(stream) = request.http_body() {
+		let mut buffer = [0u8; 4096];
+		while let Ok(read) = stream.read(&mut buffer, None::<&gio::Cancellable>) {
+			if read == 0 {
+				break;
+			}
+			body.extend_from_slice(&buffer[..read]);
+		}
+	}
+
+	SchemeRequest {
+		url: request.uri().map(|u| u.to_string()).unwrap_or_default(),
+		method: request
+			.http_method()
+			.map(|m| m.to_string())
+			.unwrap_or_else(|| "GET".to_owned()),
+		headers,
+		body,
+	}
+}
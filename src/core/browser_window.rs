@@ -5,7 +5,7 @@ mod edge2;
 #[cfg(feature = "gtk")]
 mod webkit;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, path::Path};
 
 use browser_window_c::*;
 #[cfg(not(any(feature = "gtk", feature = "edge2")))]
@@ -16,7 +16,6 @@ pub use edge2::{BrowserWindowImpl, JsEvaluationError};
 pub use webkit::{BrowserWindowImpl, JsEvaluationError};
 
 use super::{
-	super::event::*,
 	application::ApplicationImpl,
 	cookie::CookieJarImpl,
 	window::{WindowImpl, WindowOptions},
@@ -28,16 +27,91 @@ use crate::{browser::*, prelude::JsValue, rc::*};
 
 pub type BrowserWindowOptions = cbw_BrowserWindowOptions;
 
-/// The data that is passed to the C FFI handler function
-pub(crate) struct BrowserUserData {
-	pub(crate) _handle: Rc<BrowserWindowOwner>,
-}
+/// Defines `invoke_extern`, which returns a promise that gets settled by
+/// `__bw_settle` once the application has responded.
+/// It also defines `__bw_bind` and `__bw_unbind`, which (un)define the global
+/// functions that are bound with `BrowserWindow::bind`.
+/// `POST_MESSAGE` needs to be replaced by the function that passes the message
+/// on to the application.
+pub(crate) const INVOKE_EXTERN_JS: &str = r#"
+	(function() {
+		var next_id = 0;
+		var pending = {};
+		window.invoke_extern = function(cmd) {
+			var args = [].slice.call(arguments, 1);
+			return new Promise(function(resolve, reject) {
+				var id = next_id++;
+				pending[id] = [resolve, reject];
+				POST_MESSAGE([id, cmd].concat(args));
+			});
+		};
+		window.__bw_settle = function(id, ok, value) {
+			var p = pending[id];
+			if (p === undefined) { return; }
+			delete pending[id];
+			p[ok ? 0 : 1](value);
+		};
+		window.__bw_bind = function(name) {
+			window[name] = function() {
+				return window.invoke_extern.apply(null, [name].concat([].slice.call(arguments)));
+			};
+		};
+		window.__bw_unbind = function(name) {
+			delete window[name];
+		};
+	})();
+"#;
 
 pub type CreationCallbackFn = fn(bw: BrowserWindowImpl, data: *mut ());
 pub type EvalJsCallbackFn =
 	fn(bw: BrowserWindowImpl, data: *mut (), result: Result<JsValue, JsEvaluationError>);
 
 pub trait BrowserWindowEventExt {
+	/// Starts passing the messages sent by `invoke_extern` on to
+	/// `BrowserWindowOwner::dispatch_message`.
+	/// Only called once, right after the browser window has been created.
+	fn listen_for_messages(&self, owner: Weak<BrowserWindowOwner>);
+	/// Starts passing requests for credentials on to
+	/// `BrowserWindowOwner::request_auth_credentials`.
+	/// Called whenever a handler is registered for `on_auth_credentials`, so it
+	/// should only start listening the first time.
+	fn listen_for_auth_credentials(&self, _owner: Weak<BrowserWindowOwner>) {}
+	/// Starts passing untrusted certificates on to
+	/// `BrowserWindowOwner::handle_certificate_error`.
+	/// Only called once, right after the browser window has been created.
+	fn listen_for_certificate_errors(&self, _owner: Weak<BrowserWindowOwner>) {}
+	/// Starts passing requests for client certificates on to
+	/// `BrowserWindowOwner::select_client_certificate`.
+	/// Called whenever a handler is registered for
+	/// `on_select_client_certificate`, so it should only start listening the
+	/// first time.
+	fn listen_for_client_certificates(&self, _owner: Weak<BrowserWindowOwner>) {}
+	/// Starts passing downloads on to `BrowserWindowOwner::start_download`,
+	/// `BrowserWindowOwner::download_progress` and
+	/// `BrowserWindowOwner::finish_download`.
+	/// Only called once, right after the browser window has been created.
+	fn listen_for_downloads(&self, _owner: Weak<BrowserWindowOwner>) {}
+	/// Starts passing the file dialogs that the page wants to show on to
+	/// `BrowserWindowOwner::open_file_dialog`.
+	/// Called whenever a handler is registered for `on_file_dialog`, so it
+	/// should only start listening the first time.
+	fn listen_for_file_dialogs(&self, _owner: Weak<BrowserWindowOwner>) {}
+	/// Starts passing the keys that are pressed on to
+	/// `BrowserWindowOwner::key_press` and `BrowserWindowOwner::key_pressed`.
+	/// Called whenever a handler is registered for `on_key_press` or
+	/// `on_key_pressed`, or a shortcut is added, so it should only start
+	/// listening the first time.
+	fn listen_for_keys(&self, _owner: Weak<BrowserWindowOwner>) {}
+	/// Starts asking `BrowserWindowOwner::request_navigation` whether
+	/// navigations are allowed to happen.
+	/// Only called once, right after the browser window has been created.
+	fn listen_for_navigation_requests(&self, _owner: Weak<BrowserWindowOwner>) {}
+	/// Starts passing the requests that the page makes on to
+	/// `BrowserWindowOwner::intercept_request`.
+	/// Called whenever a handler is registered for `on_request`, so it should
+	/// only start listening the first time.
+	fn listen_for_requests(&self, _owner: Weak<BrowserWindowOwner>) {}
+
 	fn on_address_changed(&self, _handle: Weak<BrowserWindowOwner>) -> AddressChangedEvent {
 		unimplemented!();
 	}
@@ -57,7 +131,6 @@ pub trait BrowserWindowEventExt {
 	) -> LoadingProgressChangedEvent {
 		unimplemented!();
 	}
-	fn on_message(&self, _handle: Weak<BrowserWindowOwner>) -> MessageEvent;
 	fn on_navigation_end(&self, _handle: Weak<BrowserWindowOwner>) -> NavigationEndEvent {
 		unimplemented!();
 	}
@@ -67,6 +140,9 @@ pub trait BrowserWindowEventExt {
 	fn on_page_title_changed(&self, _handle: Weak<BrowserWindowOwner>) -> PageTitleChangedEvent {
 		unimplemented!();
 	}
+	fn on_response(&self, _handle: Weak<BrowserWindowOwner>) -> ResponseEvent {
+		unimplemented!();
+	}
 	fn on_status_message(&self, _handle: Weak<BrowserWindowOwner>) -> StatusMessageEvent {
 		unimplemented!();
 	}
@@ -76,8 +152,26 @@ pub trait BrowserWindowEventExt {
 }
 
 pub trait BrowserWindowExt: BrowserWindowEventExt + Clone {
+	/// Defines the global JavaScript function `name`, which calls
+	/// `invoke_extern` with `name` as its command.
+	/// The function has to be defined for the current page, and for every page
+	/// that gets loaded afterwards.
+	fn bind(&self, _name: &str) {}
+
+	fn can_go_back(&self) -> bool;
+	fn can_go_forward(&self) -> bool;
+
 	fn cookie_jar(&self) -> Option<CookieJarImpl>;
 
+	/// Starts downloading the file at `url` to `path`.
+	/// `request` has to be passed on to `BrowserWindowOwner::start_download` if
+	/// the backend lets it decide on the destination, and to
+	/// `BrowserWindowOwner::finish_download`, also when the download fails
+	/// before it has started.
+	/// Returns whether or not the download has been started, which it never is
+	/// if downloads aren't supported.
+	fn download(&self, _url: &str, _path: &Path, _request: u32) -> bool { false }
+
 	/// Executes the given JavaScript string.
 	/// The result will be provided by invoking the callback function.
 	fn eval_js(&self, js: &str, callback: EvalJsCallbackFn, callback_data: *mut ());
@@ -87,9 +181,21 @@ pub trait BrowserWindowExt: BrowserWindowEventExt + Clone {
 
 	fn free(&self);
 
+	fn go_back(&self);
+	fn go_forward(&self);
+
+	/// The entries of the back/forward list, from the oldest to the newest.
+	fn history(&self) -> Vec<HistoryEntry> { Vec::new() }
+
 	/// Causes the browser to navigate to the given URI.
 	fn navigate(&self, uri: &str);
 
+	fn reload(&self, ignore_cache: bool);
+	fn stop(&self);
+
+	/// Removes the global JavaScript function that has been defined by `bind`.
+	fn unbind(&self, _name: &str) {}
+
 	fn url<'a>(&'a self) -> Cow<'a, str>;
 
 	/// Gives a handle to the underlying window.
@@ -102,12 +208,3 @@ pub trait BrowserWindowExt: BrowserWindowEventExt + Clone {
 		callback_data: *mut (),
 	);
 }
-
-impl BrowserWindowImpl {
-	pub(crate) fn free_user_data(user_data: *mut ()) {
-		let ptr = user_data as *mut BrowserUserData;
-		unsafe {
-			let _ = Box::from_raw(ptr);
-		}
-	}
-}
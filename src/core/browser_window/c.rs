@@ -1,5 +1,12 @@
 use std::{
-	error::Error as StdError, ffi::CStr, fmt, mem::MaybeUninit, os::raw::*, ptr, slice, str,
+	error::Error as StdError,
+	ffi::CStr,
+	fmt,
+	mem::MaybeUninit,
+	os::raw::*,
+	path::{Path, PathBuf},
+	ptr, slice, str,
+	time::{Duration, SystemTime},
 };
 
 use browser_window_c::*;
@@ -8,7 +15,14 @@ use super::{
 	super::{error::Error, window::WindowImpl},
 	*,
 };
-use crate::{def_browser_event, def_event, rc::*};
+use crate::{
+	certificate::{Certificate, CertificateError},
+	def_browser_event, def_event,
+	dialog::FileDialogMode,
+	event::{EventHandlers, Subscription},
+	key::Modifiers,
+	rc::*,
+};
 
 #[derive(Clone)]
 pub struct BrowserWindowImpl {
@@ -34,26 +48,74 @@ pub struct JsEvaluationError {
 
 struct EventData<C, A> {
 	owner: Weak<BrowserWindowOwner>,
-	handler: BrowserWindowEventHandler<A>,
+	// The handlers are owned by the `BrowserWindowOwner`, so that they get dropped
+	// together with it.
+	handlers: Weak<EventHandlers<BrowserWindowHandle, BrowserWindow, A>>,
 	converter: unsafe fn(&C) -> A,
 }
 
+/// Messages are dispatched by the owner, which also needs to be given to the
+/// message arguments.
+struct MessageEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
+/// Requests for credentials are answered by the handlers that the owner keeps.
+struct AuthCredentialsEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
+/// Certificate errors are decided by the owner too.
+struct CertificateErrorEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
+/// Requests for client certificates are answered by the owner's handlers.
+struct ClientCertificateEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
+/// Downloads are kept track of by the owner as well.
+struct DownloadEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
+/// File dialogs are answered by the owner's handlers as well.
+struct FileDialogEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
+/// Whether keys are consumed is decided by the owner too.
+struct KeyEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
+/// Navigation requests are decided by the owner as well.
+struct NavigationRequestEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
+/// And so are the requests that the page makes.
+struct RequestEventData {
+	owner: Weak<BrowserWindowOwner>,
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! def_browser_event_c {
 	($name:ident<$carg_type:ty, $rarg_type:ty> => $converter:ident => $c_event_name:ident) => {
 		def_browser_event!($name<$rarg_type>(&mut self, handler) {
-			if let Some(upgraded) = self.owner.upgrade() {
-				let c_ptr = unsafe { &mut *upgraded.0.inner.inner };
-				// Free the previous event data if overwriting
-				if c_ptr.events.$c_event_name.callback.is_some() {
-					unsafe { let _ = Box::from_raw(c_ptr.events.$c_event_name.data as *mut EventData<$carg_type, $rarg_type>); }
-				}
-
-				// Store the new event data
+			let upgraded = match self.owner.upgrade() {
+				Some(u) => u,
+				None => return Subscription::none(),
+			};
+			let c_ptr = unsafe { &mut *{ upgraded.0.inner.inner } };
+
+			// The event data with the list of handlers is created for the first handler
+			if c_ptr.events.$c_event_name.callback.is_none() {
 				let event_data = EventData::<$carg_type, $rarg_type> {
 					owner: self.owner.clone(),
-					handler,
+					handlers: Rc::downgrade(&upgraded.new_event_handlers()),
 					converter: $converter,
 				};
 				let event_data_ptr = Box::into_raw(Box::new(event_data));
@@ -62,17 +124,41 @@ macro_rules! def_browser_event_c {
 					data: event_data_ptr as _
 				};
 			}
+
+			let event_data = unsafe { &*(c_ptr.events.$c_event_name.data as *const EventData<$carg_type, $rarg_type>) };
+			match event_data.handlers.upgrade() {
+				Some(handlers) => EventHandlers::add(&handlers, handler),
+				None => Subscription::none(),
+			}
 		});
 	}
 }
 
 impl BrowserWindowExt for BrowserWindowImpl {
+	fn bind(&self, name: &str) { unsafe { cbw_BrowserWindow_bind(self.inner, name.into()) } }
+
 	fn cookie_jar(&self) -> Option<CookieJarImpl> {
 		let inner = unsafe { cbw_CookieJar_newGlobal() };
 
 		Some(CookieJarImpl(inner))
 	}
 
+	fn can_go_back(&self) -> bool { unsafe { cbw_BrowserWindow_canGoBack(self.inner) != 0 } }
+
+	fn can_go_forward(&self) -> bool { unsafe { cbw_BrowserWindow_canGoForward(self.inner) != 0 } }
+
+	fn download(&self, url: &str, path: &Path, request: u32) -> bool {
+		unsafe {
+			cbw_BrowserWindow_download(
+				self.inner,
+				url.into(),
+				path.to_string_lossy().as_ref().into(),
+				request,
+			)
+		};
+		true
+	}
+
 	fn eval_js(&self, js: &str, callback: EvalJsCallbackFn, callback_data: *mut ()) {
 		let data = Box::new(EvalJsCallbackData {
 			callback,
@@ -108,10 +194,95 @@ impl BrowserWindowExt for BrowserWindowImpl {
 		}
 	}
 
-	fn free(&self) { unsafe { cbw_BrowserWindow_free(self.inner) } }
+	fn free(&self) {
+		unsafe {
+			let c_ptr = &mut *self.inner;
+			if c_ptr.events.on_auth_credentials.callback.is_some() {
+				let _ = Box::from_raw(
+					c_ptr.events.on_auth_credentials.data as *mut AuthCredentialsEventData,
+				);
+				c_ptr.events.on_auth_credentials.callback = None;
+			}
+			if c_ptr.events.on_certificate_error.callback.is_some() {
+				let _ = Box::from_raw(
+					c_ptr.events.on_certificate_error.data as *mut CertificateErrorEventData,
+				);
+				c_ptr.events.on_certificate_error.callback = None;
+			}
+			for event in [
+				&mut c_ptr.events.on_download_finished,
+				&mut c_ptr.events.on_download_progress,
+				&mut c_ptr.events.on_download_started,
+			] {
+				if event.callback.is_some() {
+					let _ = Box::from_raw(event.data as *mut DownloadEventData);
+					event.callback = None;
+				}
+			}
+			if c_ptr.events.on_file_dialog.callback.is_some() {
+				let _ = Box::from_raw(c_ptr.events.on_file_dialog.data as *mut FileDialogEventData);
+				c_ptr.events.on_file_dialog.callback = None;
+			}
+			for event in [
+				&mut c_ptr.events.on_key_press,
+				&mut c_ptr.events.on_key_pressed,
+			] {
+				if event.callback.is_some() {
+					let _ = Box::from_raw(event.data as *mut KeyEventData);
+					event.callback = None;
+				}
+			}
+			if c_ptr.events.on_message.callback.is_some() {
+				let _ = Box::from_raw(c_ptr.events.on_message.data as *mut MessageEventData);
+				c_ptr.events.on_message.callback = None;
+			}
+			if c_ptr.events.on_navigation_request.callback.is_some() {
+				let _ = Box::from_raw(
+					c_ptr.events.on_navigation_request.data as *mut NavigationRequestEventData,
+				);
+				c_ptr.events.on_navigation_request.callback = None;
+			}
+			if c_ptr.events.on_request.callback.is_some() {
+				let _ = Box::from_raw(c_ptr.events.on_request.data as *mut RequestEventData);
+				c_ptr.events.on_request.callback = None;
+			}
+			if c_ptr.events.on_select_client_certificate.callback.is_some() {
+				let _ = Box::from_raw(
+					c_ptr.events.on_select_client_certificate.data
+						as *mut ClientCertificateEventData,
+				);
+				c_ptr.events.on_select_client_certificate.callback = None;
+			}
+			cbw_BrowserWindow_free(self.inner)
+		}
+	}
+
+	fn go_back(&self) { unsafe { cbw_BrowserWindow_goBack(self.inner) } }
+
+	fn go_forward(&self) { unsafe { cbw_BrowserWindow_goForward(self.inner) } }
+
+	fn history(&self) -> Vec<HistoryEntry> {
+		let mut entries = Vec::new();
+		unsafe {
+			cbw_BrowserWindow_getHistory(
+				self.inner,
+				Some(ffi_history_callback),
+				&mut entries as *mut Vec<HistoryEntry> as _,
+			)
+		};
+		entries
+	}
 
 	fn navigate(&self, uri: &str) { unsafe { cbw_BrowserWindow_navigate(self.inner, uri.into()) }; }
 
+	fn reload(&self, ignore_cache: bool) {
+		unsafe { cbw_BrowserWindow_reload(self.inner, ignore_cache as _) }
+	}
+
+	fn stop(&self) { unsafe { cbw_BrowserWindow_stop(self.inner) } }
+
+	fn unbind(&self, name: &str) { unsafe { cbw_BrowserWindow_unbind(self.inner, name.into()) } }
+
 	fn url<'a>(&'a self) -> Cow<'a, str> {
 		let owned;
 		let slice;
@@ -206,6 +377,112 @@ impl BrowserWindowExt for BrowserWindowImpl {
 }
 
 impl BrowserWindowEventExt for BrowserWindowImpl {
+	fn listen_for_messages(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		let event_data = MessageEventData { owner };
+		c_ptr.events.on_message = cbw_Event {
+			callback: Some(ffi_message_event_callback),
+			data: Box::into_raw(Box::new(event_data)) as _,
+		};
+	}
+
+	fn listen_for_auth_credentials(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		if c_ptr.events.on_auth_credentials.callback.is_some() {
+			return;
+		}
+		let event_data = AuthCredentialsEventData { owner };
+		c_ptr.events.on_auth_credentials = cbw_Event {
+			callback: Some(ffi_auth_credentials_callback),
+			data: Box::into_raw(Box::new(event_data)) as _,
+		};
+	}
+
+	fn listen_for_certificate_errors(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		let event_data = CertificateErrorEventData { owner };
+		c_ptr.events.on_certificate_error = cbw_Event {
+			callback: Some(ffi_certificate_error_callback),
+			data: Box::into_raw(Box::new(event_data)) as _,
+		};
+	}
+
+	fn listen_for_client_certificates(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		if c_ptr.events.on_select_client_certificate.callback.is_some() {
+			return;
+		}
+		let event_data = ClientCertificateEventData { owner };
+		c_ptr.events.on_select_client_certificate = cbw_Event {
+			callback: Some(ffi_client_certificate_callback),
+			data: Box::into_raw(Box::new(event_data)) as _,
+		};
+	}
+
+	fn listen_for_downloads(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		let new_event = |callback, owner| cbw_Event {
+			callback: Some(callback),
+			data: Box::into_raw(Box::new(DownloadEventData { owner })) as _,
+		};
+		c_ptr.events.on_download_finished =
+			new_event(ffi_download_finished_callback, owner.clone());
+		c_ptr.events.on_download_progress =
+			new_event(ffi_download_progress_callback, owner.clone());
+		c_ptr.events.on_download_started = new_event(ffi_download_started_callback, owner);
+	}
+
+	fn listen_for_file_dialogs(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		if c_ptr.events.on_file_dialog.callback.is_some() {
+			return;
+		}
+		let event_data = FileDialogEventData { owner };
+		c_ptr.events.on_file_dialog = cbw_Event {
+			callback: Some(ffi_file_dialog_callback),
+			data: Box::into_raw(Box::new(event_data)) as _,
+		};
+	}
+
+	fn listen_for_keys(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		if c_ptr.events.on_key_press.callback.is_some() {
+			return;
+		}
+		c_ptr.events.on_key_press = cbw_Event {
+			callback: Some(ffi_key_press_callback),
+			data: Box::into_raw(Box::new(KeyEventData {
+				owner: owner.clone(),
+			})) as _,
+		};
+		c_ptr.events.on_key_pressed = cbw_Event {
+			callback: Some(ffi_key_pressed_callback),
+			data: Box::into_raw(Box::new(KeyEventData { owner })) as _,
+		};
+	}
+
+	fn listen_for_navigation_requests(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		let event_data = NavigationRequestEventData { owner };
+		c_ptr.events.on_navigation_request = cbw_Event {
+			callback: Some(ffi_navigation_request_callback),
+			data: Box::into_raw(Box::new(event_data)) as _,
+		};
+	}
+
+	fn listen_for_requests(&self, owner: Weak<BrowserWindowOwner>) {
+		let c_ptr = unsafe { &mut *self.inner };
+		if c_ptr.events.on_request.callback.is_some() {
+			return;
+		}
+		let event_data = RequestEventData { owner };
+		c_ptr.events.on_request = cbw_Event {
+			callback: Some(ffi_request_callback),
+			data: Box::into_raw(Box::new(event_data)) as _,
+		};
+		unsafe { cbw_BrowserWindow_listenForRequests(self.inner) };
+	}
+
 	fn on_address_changed(&self, handle: Weak<BrowserWindowOwner>) -> AddressChangedEvent {
 		AddressChangedEvent::new(handle)
 	}
@@ -230,10 +507,6 @@ impl BrowserWindowEventExt for BrowserWindowImpl {
 		LoadingProgressChangedEvent::new(handle)
 	}
 
-	fn on_message(&self, handle: Weak<BrowserWindowOwner>) -> MessageEvent {
-		MessageEvent::new(handle)
-	}
-
 	fn on_navigation_end(&self, handle: Weak<BrowserWindowOwner>) -> NavigationEndEvent {
 		NavigationEndEvent::new(handle)
 	}
@@ -246,6 +519,13 @@ impl BrowserWindowEventExt for BrowserWindowImpl {
 		PageTitleChangedEvent::new(handle)
 	}
 
+	fn on_response(&self, handle: Weak<BrowserWindowOwner>) -> ResponseEvent {
+		// The responses are looked at on another thread, which can't tell whether the
+		// event has a handler.
+		unsafe { cbw_BrowserWindow_listenForRequests(self.inner) };
+		ResponseEvent::new(handle)
+	}
+
 	fn on_status_message(&self, handle: Weak<BrowserWindowOwner>) -> StatusMessageEvent {
 		StatusMessageEvent::new(handle)
 	}
@@ -260,9 +540,9 @@ def_browser_event_c!(ConsoleMessageEvent<cbw_CStrSlice, String> => str_converter
 def_browser_event_c!(FaviconChangedEvent<cbw_CStrSlice, String> => str_converter => on_favicon_changed);
 def_browser_event_c!(FullscreenModeChangedEvent<c_int, bool> => bool_converter => on_fullscreen_mode_changed);
 def_browser_event_c!(LoadingProgressChangedEvent<c_double, f64> => f64_converter => on_loading_progress_changed);
-def_browser_event_c!(MessageEvent<cbw_BrowserWindowMessageArgs, MessageEventArgs> => message_args_converter => on_message);
 def_browser_event_c!(NavigationStartEvent<(), ()> => no_converter => on_navigation_start);
 def_browser_event_c!(NavigationEndEvent<cbw_Err, Result<(), Error>> => error_converter => on_navigation_end);
+def_browser_event_c!(ResponseEvent<cbw_BrowserWindowResponse, ResponseEventArgs> => response_converter => on_response);
 def_browser_event_c!(PageTitleChangedEvent<cbw_CStrSlice, String> => str_converter => on_page_title_changed);
 def_browser_event_c!(StatusMessageEvent<cbw_CStrSlice, String> => str_converter => on_status_message);
 def_browser_event_c!(TooltipEvent<cbw_CStrSlice, String> => str_converter => on_tooltip);
@@ -315,6 +595,18 @@ unsafe extern "C" fn ffi_eval_js_callback_handler(
 	(data.callback)(handle, data.data, result);
 }
 
+unsafe extern "C" fn ffi_history_callback(
+	data: *mut c_void, index: c_uint, url: cbw_StrSlice, title: cbw_StrSlice, current: cBOOL,
+) {
+	let entries = &mut *(data as *mut Vec<HistoryEntry>);
+	entries.push(HistoryEntry {
+		index: index as _,
+		title: title.into(),
+		url: url.into(),
+		current: current != 0,
+	});
+}
+
 /// Processes the result received from the C function, and returns it in a Rust
 /// Result.
 unsafe fn ffi_eval_js_callback_result(
@@ -339,31 +631,337 @@ unsafe fn ffi_eval_js_callback_result(
 
 unsafe extern "C" fn ffi_browser_window_event_callback<C, A>(
 	handler_data: *mut c_void, arg_ptr: *mut c_void,
-) -> i32 {
-	let event_data_ptr = handler_data as *mut EventData<C, A>;
-	let event_data = &mut *event_data_ptr;
+) -> i32
+where
+	A: Clone,
+{
+	let event_data_ptr = handler_data as *const EventData<C, A>;
+	let event_data = &*event_data_ptr;
 	let arg_ptr2 = arg_ptr as *mut C;
 	let carg = &*arg_ptr2;
 
 	// Convert C type to Rust type
 	let rarg = (event_data.converter)(carg);
 
-	// Run the event handler
-	let rc_handle = event_data
-		.owner
-		.upgrade()
-		.expect("browser window handle is gone");
-	match &mut event_data.handler {
-		EventHandler::Sync(callback) => {
-			(callback)(&*rc_handle, rarg);
+	if let Some(handlers) = event_data.handlers.upgrade() {
+		BrowserWindowOwner::fire_event(&event_data.owner, &handlers, rarg);
+	}
+	return 0;
+}
+
+unsafe extern "C" fn ffi_auth_credentials_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const AuthCredentialsEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowAuthRequest);
+
+	let callback = carg.callback;
+	let rarg = AuthCredentialsEventArgs::new(
+		str_converter(&carg.host),
+		carg.port,
+		str_converter(&carg.realm),
+		str_converter(&carg.scheme).to_lowercase(),
+		carg.is_proxy != 0,
+		carg.is_retry != 0,
+		move |credentials| match credentials {
+			Some(c) => cbw_AuthCallback_continue(
+				callback,
+				c.username.as_str().into(),
+				c.password.as_str().into(),
+			),
+			None => cbw_AuthCallback_cancel(callback),
+		},
+	);
+
+	BrowserWindowOwner::request_auth_credentials(&event_data.owner, rarg);
+	0
+}
+
+#[allow(non_upper_case_globals)]
+unsafe extern "C" fn ffi_certificate_error_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const CertificateErrorEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowCertificateError);
+
+	let error = match carg.error {
+		cBW_CERTIFICATE_ERROR_HOST_MISMATCH => CertificateError::HostMismatch,
+		cBW_CERTIFICATE_ERROR_INVALID_DATE => CertificateError::InvalidDate,
+		cBW_CERTIFICATE_ERROR_UNKNOWN_AUTHORITY => CertificateError::UnknownAuthority,
+		cBW_CERTIFICATE_ERROR_REVOKED => CertificateError::Revoked,
+		cBW_CERTIFICATE_ERROR_INVALID => CertificateError::Invalid,
+		_ => CertificateError::Other,
+	};
+	let callback = carg.callback;
+	let rarg = CertificateErrorEventArgs::new(
+		str_converter(&carg.url),
+		error,
+		certificate_converter(&carg.certificate),
+		move |allow| {
+			if allow {
+				cbw_CertificateCallback_allow(callback)
+			} else {
+				cbw_CertificateCallback_deny(callback)
+			}
+		},
+	);
+
+	BrowserWindowOwner::handle_certificate_error(&event_data.owner, rarg);
+	0
+}
+
+unsafe extern "C" fn ffi_client_certificate_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const ClientCertificateEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowClientCertificateRequest);
+
+	let candidates: Vec<Certificate> = if carg.candidate_count > 0 {
+		slice::from_raw_parts(carg.candidates, carg.candidate_count)
+			.iter()
+			.map(|c| certificate_converter(c))
+			.collect()
+	} else {
+		Vec::new()
+	};
+	// CEF can only select one of the candidates.
+	let candidate_ders: Vec<Vec<u8>> = candidates.iter().map(|c| c.der.clone()).collect();
+
+	let callback = carg.callback;
+	let rarg = SelectClientCertificateEventArgs::new(
+		str_converter(&carg.host),
+		carg.port,
+		carg.is_proxy != 0,
+		candidates,
+		move |certificate| match certificate.and_then(|c| c.candidate_index(&candidate_ders)) {
+			Some(index) => cbw_ClientCertificateCallback_select(callback, index),
+			None => cbw_ClientCertificateCallback_cancel(callback),
+		},
+	);
+
+	BrowserWindowOwner::select_client_certificate(&event_data.owner, rarg);
+	0
+}
+
+#[allow(non_upper_case_globals)]
+unsafe extern "C" fn ffi_download_finished_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const DownloadEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowDownloadFinished);
+
+	let result = match carg.result {
+		cBW_DOWNLOAD_RESULT_SUCCESS => Ok(()),
+		cBW_DOWNLOAD_RESULT_CANCELLED => Err(DownloadError::Cancelled),
+		_ => Err(DownloadError::Failed(str_converter(&carg.error))),
+	};
+	let rarg = DownloadFinishedEventArgs {
+		id: carg.id,
+		url: str_converter(&carg.url),
+		path: str_converter(&carg.path).into(),
+		result,
+	};
+
+	let request = if carg.request != 0 {
+		Some(carg.request)
+	} else {
+		None
+	};
+	BrowserWindowOwner::finish_download(&event_data.owner, request, rarg);
+	0
+}
+
+unsafe extern "C" fn ffi_download_progress_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const DownloadEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowDownloadProgress);
+
+	let rarg = DownloadProgressEventArgs {
+		id: carg.id,
+		url: str_converter(&carg.url),
+		received: carg.received.max(0) as u64,
+		total_size: size_converter(carg.total_size),
+	};
+
+	BrowserWindowOwner::download_progress(&event_data.owner, rarg);
+	0
+}
+
+unsafe extern "C" fn ffi_download_started_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const DownloadEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowDownload);
+
+	let callback = carg.callback;
+	let rarg = DownloadStartedEventArgs::new(
+		carg.id,
+		str_converter(&carg.url),
+		str_converter(&carg.suggested_name),
+		str_converter(&carg.mime_type),
+		size_converter(carg.total_size),
+		move |path| match path {
+			Some(p) => cbw_DownloadCallback_continue(callback, p.to_string_lossy().as_ref().into()),
+			None => cbw_DownloadCallback_cancel(callback),
+		},
+	);
+
+	// Downloads of `download` don't go through this event on CEF.
+	BrowserWindowOwner::start_download(&event_data.owner, None, rarg);
+	0
+}
+
+/// Returns 1 if the handlers choose the files, and 0 if the browser should
+/// show its own dialog.
+#[allow(non_upper_case_globals)]
+unsafe extern "C" fn ffi_file_dialog_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const FileDialogEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowFileDialog);
+
+	let mode = match carg.mode {
+		cBW_FILE_DIALOG_OPEN_MULTIPLE => FileDialogMode::OpenMultiple,
+		cBW_FILE_DIALOG_OPEN_FOLDER => FileDialogMode::OpenFolder,
+		cBW_FILE_DIALOG_SAVE => FileDialogMode::Save,
+		_ => FileDialogMode::Open,
+	};
+	let default_path = str_converter(&carg.default_path);
+	let accept = if carg.accept_count > 0 {
+		slice::from_raw_parts(carg.accept, carg.accept_count)
+			.iter()
+			.map(|a| str_converter(a))
+			.collect()
+	} else {
+		Vec::new()
+	};
+
+	let callback = carg.callback;
+	let rarg = FileDialogEventArgs::new(
+		mode,
+		str_converter(&carg.title),
+		if default_path.is_empty() {
+			None
+		} else {
+			Some(PathBuf::from(default_path))
+		},
+		accept,
+		move |paths| {
+			if paths.is_empty() {
+				cbw_FileDialogCallback_cancel(callback);
+			} else {
+				let strings: Vec<String> = paths
+					.iter()
+					.map(|p| p.to_string_lossy().into_owned())
+					.collect();
+				let slices: Vec<cbw_CStrSlice> =
+					strings.iter().map(|s| s.as_str().into()).collect();
+				cbw_FileDialogCallback_continue(callback, slices.as_ptr(), slices.len());
+			}
+		},
+	);
+
+	BrowserWindowOwner::open_file_dialog(&event_data.owner, rarg) as _
+}
+
+unsafe extern "C" fn ffi_key_press_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const KeyEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowKeyEvent);
+
+	BrowserWindowOwner::key_press(&event_data.owner, key_event_converter(carg)) as _
+}
+
+unsafe extern "C" fn ffi_key_pressed_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const KeyEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowKeyEvent);
+
+	BrowserWindowOwner::key_pressed(&event_data.owner, key_event_converter(carg)) as _
+}
+
+unsafe extern "C" fn ffi_message_event_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const MessageEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowMessageArgs);
+
+	let rarg = message_args_converter(&event_data.owner, carg);
+
+	BrowserWindowOwner::dispatch_message(&event_data.owner, rarg);
+	return 0;
+}
+
+#[allow(non_upper_case_globals)]
+unsafe extern "C" fn ffi_navigation_request_callback(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const NavigationRequestEventData);
+	let carg = &*(arg_ptr as *const cbw_BrowserWindowNavigationRequest);
+
+	let navigation_type = match carg.type_ {
+		cBW_NAVIGATION_TYPE_LINK => NavigationType::Link,
+		cBW_NAVIGATION_TYPE_FORM_SUBMISSION => NavigationType::FormSubmission,
+		cBW_NAVIGATION_TYPE_BACK_FORWARD => NavigationType::BackForward,
+		cBW_NAVIGATION_TYPE_RELOAD => NavigationType::Reload,
+		_ => NavigationType::Other,
+	};
+	let rarg = NavigationRequestEventArgs::new(
+		str_converter(&carg.url),
+		carg.is_main_frame != 0,
+		carg.user_gesture != 0,
+		navigation_type,
+	);
+
+	BrowserWindowOwner::request_navigation(&event_data.owner, rarg) as _
+}
+
+unsafe extern "C" fn ffi_request_callback(handler_data: *mut c_void, arg_ptr: *mut c_void) -> i32 {
+	let event_data = &*(handler_data as *const RequestEventData);
+	let carg = &mut *(arg_ptr as *mut cbw_BrowserWindowRequest);
+
+	let rarg = RequestEventArgs::new(
+		str_converter(&carg.url),
+		str_converter(&carg.method),
+		headers_converter(carg.headers, carg.header_count as _),
+	);
+	BrowserWindowOwner::intercept_request(&event_data.owner, rarg.clone());
+
+	for (name, value) in rarg.header_changes() {
+		match value {
+			Some(v) =>
+				cbw_BrowserWindowRequest_setHeader(carg, name.as_str().into(), v.as_str().into()),
+			None => cbw_BrowserWindowRequest_removeHeader(carg, name.as_str().into()),
 		}
-		EventHandler::Async(callback) => {
-			let app = rc_handle.0.app();
-			let future = (callback)(BrowserWindow(rc_handle.clone()), rarg);
-			app.spawn(future);
+	}
+	match rarg.action() {
+		RequestAction::Continue => {}
+		RequestAction::Block => cbw_BrowserWindowRequest_block(carg),
+		RequestAction::Redirect(url) =>
+			cbw_BrowserWindowRequest_redirect(carg, url.as_str().into()),
+		RequestAction::Respond(response) => {
+			let headers: Vec<cbw_HttpHeader> = response
+				.headers
+				.iter()
+				.map(|(name, value)| cbw_HttpHeader {
+					name: name.as_str().into(),
+					value: value.as_str().into(),
+				})
+				.collect();
+			let c_response = cbw_SchemeResponse {
+				status: response.status,
+				headers: headers.as_ptr(),
+				header_count: headers.len() as _,
+				body: response.body.as_ptr(),
+				body_len: response.body.len() as _,
+			};
+			cbw_BrowserWindowRequest_respond(carg, &c_response);
 		}
 	}
-	return 0;
+	0
 }
 
 unsafe fn no_converter(_input: &()) -> () { () }
@@ -387,7 +985,73 @@ unsafe fn str_converter(input: &cbw_CStrSlice) -> String {
 	string.to_string()
 }
 
-unsafe fn message_args_converter(input: &cbw_BrowserWindowMessageArgs) -> MessageEventArgs {
+unsafe fn certificate_converter(input: &cbw_Certificate) -> Certificate {
+	let der = if input.der_len > 0 {
+		slice::from_raw_parts(input.der, input.der_len).to_vec()
+	} else {
+		Vec::new()
+	};
+	Certificate {
+		subject: str_converter(&input.subject),
+		issuer: str_converter(&input.issuer),
+		valid_from: unix_time_converter(input.valid_from),
+		valid_until: unix_time_converter(input.valid_until),
+		der,
+	}
+}
+
+fn key_event_converter(input: &cbw_BrowserWindowKeyEvent) -> KeyEventArgs {
+	let modifiers = Modifiers {
+		ctrl: input.modifiers & cBW_KEY_MODIFIER_CTRL != 0,
+		shift: input.modifiers & cBW_KEY_MODIFIER_SHIFT != 0,
+		alt: input.modifiers & cBW_KEY_MODIFIER_ALT != 0,
+		meta: input.modifiers & cBW_KEY_MODIFIER_META != 0,
+	};
+	KeyEventArgs::new(
+		input.key_code,
+		modifiers,
+		input.is_repeat != 0,
+		char::from_u32(input.character).filter(|c| *c != '\0'),
+	)
+}
+
+/// A negative size means that the size is unknown.
+fn size_converter(size: i64) -> Option<u64> { if size < 0 { None } else { Some(size as u64) } }
+
+/// A timestamp of 0 means that the time is unknown.
+fn unix_time_converter(timestamp: i64) -> Option<SystemTime> {
+	if timestamp == 0 {
+		return None;
+	}
+	if timestamp > 0 {
+		SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(timestamp as u64))
+	} else {
+		SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(timestamp.unsigned_abs()))
+	}
+}
+
+unsafe fn headers_converter(headers: *const cbw_HttpHeader, count: usize) -> Vec<(String, String)> {
+	if count == 0 {
+		return Vec::new();
+	}
+	slice::from_raw_parts(headers, count)
+		.iter()
+		.map(|header| (str_converter(&header.name), str_converter(&header.value)))
+		.collect()
+}
+
+unsafe fn response_converter(input: &cbw_BrowserWindowResponse) -> ResponseEventArgs {
+	ResponseEventArgs {
+		url: str_converter(&input.url),
+		method: str_converter(&input.method),
+		status: input.status,
+		headers: headers_converter(input.headers, input.header_count as _),
+	}
+}
+
+unsafe fn message_args_converter(
+	owner: &Weak<BrowserWindowOwner>, input: &cbw_BrowserWindowMessageArgs,
+) -> MessageEventArgs {
 	// Convert the command and args to a String and `Vec<&str>`
 	let cmd_string = str::from_utf8_unchecked(slice::from_raw_parts(
 		input.cmd.data as *const u8,
@@ -398,10 +1062,7 @@ unsafe fn message_args_converter(input: &cbw_BrowserWindowMessageArgs) -> Messag
 		args_vec.push(JsValue::from_string((*input.args.add(i as usize)).into()));
 	}
 
-	MessageEventArgs {
-		cmd: cmd_string.to_string(),
-		args: args_vec,
-	}
+	MessageEventArgs::new(owner.clone(), input.id, cmd_string.to_string(), args_vec)
 }
 
 #[allow(non_snake_case)]
@@ -410,7 +1071,7 @@ extern "C" fn bw_Window_freeUserData(w: *mut c_void) {
 	let w_ptr = w as *mut cbw_Window;
 	unsafe {
 		if (*w_ptr).user_data != ptr::null_mut() {
-			BrowserWindowImpl::free_user_data((*w_ptr).user_data as _);
+			WindowImpl::free_user_data((*w_ptr).user_data as _);
 			(*w_ptr).user_data = ptr::null_mut();
 		}
 	}
@@ -1,6 +1,5 @@
 use std::{
 	borrow::Cow,
-	cell::Cell,
 	ffi::{c_int, c_void},
 	ptr,
 	sync::atomic::{AtomicBool, Ordering},
@@ -10,10 +9,7 @@ use webview2::Environment;
 use winapi::{shared::windef, um::winuser};
 
 use super::{super::window::WindowImpl, *};
-use crate::{
-	def_browser_event, def_event,
-	prelude::{ApplicationExt, WindowExt},
-};
+use crate::prelude::{ApplicationExt, WindowExt};
 
 #[derive(Clone)]
 pub struct BrowserWindowImpl {
@@ -47,6 +43,10 @@ impl BrowserWindowImpl {
 }
 
 impl BrowserWindowExt for BrowserWindowImpl {
+	fn can_go_back(&self) -> bool { self.webview().get_can_go_back().unwrap_or(false) }
+
+	fn can_go_forward(&self) -> bool { self.webview().get_can_go_forward().unwrap_or(false) }
+
 	fn cookie_jar(&self) -> Option<CookieJarImpl> { None }
 
 	fn eval_js(&self, js: &str, callback: EvalJsCallbackFn, callback_data: *mut ()) {
@@ -75,8 +75,17 @@ impl BrowserWindowExt for BrowserWindowImpl {
 		}
 	}
 
+	fn go_back(&self) { let _ = self.webview().go_back(); }
+
+	fn go_forward(&self) { let _ = self.webview().go_forward(); }
+
 	fn navigate(&self, uri: &str) { self.webview().navigate(uri); }
 
+	// WebView2 doesn't have a way to bypass the cache when reloading.
+	fn reload(&self, _ignore_cache: bool) { let _ = self.webview().reload(); }
+
+	fn stop(&self) { let _ = self.webview().stop(); }
+
 	fn new(
 		app: ApplicationImpl, parent: WindowImpl, source: Source, title: &str, width: Option<u32>,
 		height: Option<u32>, window_options: &WindowOptions,
@@ -138,11 +147,8 @@ impl BrowserWindowExt for BrowserWindowImpl {
 					result.expect("unable to navigate to source");
 
 					webview.execute_script(
-						r#"
-						function invoke_extern(...args) {
-							window.chrome.webview.postMessage([].slice.call(args));
-						}
-					"#,
+						&INVOKE_EXTERN_JS
+							.replace("POST_MESSAGE", "window.chrome.webview.postMessage"),
 						move |_| Ok(()),
 					);
 
@@ -179,54 +185,34 @@ impl BrowserWindowExt for BrowserWindowImpl {
 }
 
 impl BrowserWindowEventExt for BrowserWindowImpl {
-	fn on_message(&self, handle: Weak<BrowserWindowOwner>) -> MessageEvent {
-		MessageEvent::new(handle)
+	fn listen_for_messages(&self, owner: Weak<BrowserWindowOwner>) {
+		self.webview()
+			.add_web_message_received(move |_, msg| {
+				let string = msg
+					.get_web_message_as_json()
+					.expect("unable to get web message as json");
+
+				let (id, command, args2) = match JsValue::from_json(&string) {
+					JsValue::Array(args) => {
+						let id = match &args[0] {
+							JsValue::Number(id) => id.to_u64().unwrap_or_default() as u32,
+							_ => panic!("unexpected id received from invoke_extern"),
+						};
+						let command = args[1].to_string_unenclosed().to_string();
+						let command_args = args[2..].to_vec();
+						(id, command, command_args)
+					}
+					_ => panic!("unexpected JavaScript value received from Edge WebView2"),
+				};
+
+				let e = MessageEventArgs::new(owner.clone(), id, command, args2);
+				BrowserWindowOwner::dispatch_message(&owner, e);
+				Ok(())
+			})
+			.expect("unable to register message handler");
 	}
 }
 
-def_browser_event!(MessageEvent<MessageEventArgs>(&mut self, handler) {
-
-	// Register the message handler
-	let owner = self.owner.clone();
-	let h = Rc::new(Cell::new(handler));
-	let inner = &owner.upgrade().unwrap().inner;
-	inner.webview().add_web_message_received(move |_, msg| {
-		if let Some(this) = owner.upgrade() {
-			let string = msg
-				.get_web_message_as_json()
-				.expect("unable to get web message as json");
-
-			let(command, args2) = match JsValue::from_json(&string) {
-				JsValue::Array(args) => {
-					let command = args[0].to_string_unenclosed().to_string();
-					let command_args = args[1..].to_vec();
-					(command, command_args)
-				}
-				_ => panic!(
-					"unexpected JavaScript value received from Edge WebView2"
-				),
-			};
-
-			let e = MessageEventArgs {
-				cmd: command,
-				args: args2
-			};
-			match unsafe { &mut *h.as_ptr() } {
-				EventHandler::Sync(callback) => {
-					(callback)(&*this, e);
-				}
-				EventHandler::Async(callback) => {
-					let app = this.0.app();
-					let future = (callback)(BrowserWindow(this.clone()), e);
-					app.spawn(future);
-				}
-			}
-		}
-		Ok(())
-	})
-	.expect("unable to register message handler");
-});
-
 fn dispatch_eval_js(_app: ApplicationImpl, dispatch_data: *mut ()) {
 	let data_ptr = dispatch_data as *mut EvalJsCallbackData;
 	let data = unsafe { Box::from_raw(data_ptr) };
@@ -249,7 +235,7 @@ extern "C" fn bw_Window_freeUserData(w: *mut c_void) {
 	let w_ptr = w as *mut cbw_Window;
 	unsafe {
 		if (*w_ptr).user_data != ptr::null_mut() {
-			BrowserWindowImpl::free_user_data((*w_ptr).user_data as _);
+			WindowImpl::free_user_data((*w_ptr).user_data as _);
 			(*w_ptr).user_data = ptr::null_mut();
 		}
 	}
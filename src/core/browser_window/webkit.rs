@@ -1,19 +1,39 @@
 use std::{
 	borrow::Cow,
-	cell::Cell,
-	collections::HashMap,
-	sync::atomic::{AtomicBool, Ordering},
+	cell::{Cell, RefCell},
+	collections::{HashMap, HashSet},
+	path::{Path, PathBuf},
+	sync::atomic::{AtomicBool, AtomicU32, Ordering},
+	time::{Duration, SystemTime},
 };
 
-use gtk::{gio::Cancellable, glib::CastNone, prelude::*};
+use gtk::{
+	gdk::{self, EventKey, ModifierType},
+	gio::{Cancellable, TlsCertificate, TlsCertificateFlags},
+	glib::{CastNone, DateTime, translate::ToGlibPtr},
+	prelude::*,
+};
 use javascriptcore::ValueExt;
-use webkit2gtk::{LoadEvent, Settings, SettingsExt, UserContentManagerExt, WebViewExt};
+use webkit2gtk::{
+	AuthenticationRequestExt, AuthenticationScheme, BackForwardListExt, BackForwardListItemExt,
+	Credential, CredentialPersistence, Download, DownloadExt, FaviconDatabaseExt,
+	FileChooserRequestExt, HitTestResultExt, LoadEvent, NavigationPolicyDecision,
+	NavigationPolicyDecisionExt, PolicyDecisionExt, PolicyDecisionType, Settings, SettingsExt,
+	URIRequest, URIRequestExt, URIResponseExt, UserContentInjectedFrames, UserContentManagerExt,
+	UserScript, UserScriptInjectionTime, WebContextExt, WebResourceExt, WebView, WebViewExt,
+};
 
-use super::{super::window::WindowImpl, *};
+use super::{
+	super::{error::Error, window::WindowImpl},
+	*,
+};
 use crate::{
-	def_browser_event, def_event,
+	certificate::{Certificate, CertificateError, ClientCertificate, Trust},
+	def_browser_event, def_event, def_event_gtk,
+	dialog::FileDialogMode,
+	event::Subscription,
+	key::{self, Modifiers},
 	prelude::{ApplicationExt, WindowExt},
-	rc::Rc,
 };
 
 #[derive(Clone)]
@@ -31,9 +51,65 @@ struct EvalJsCallbackData {
 /// An error that may occur when evaluating or executing JavaScript code.
 pub type JsEvaluationError = webkit2gtk::Error;
 
+/// Passes console messages on to the `bw_console` message handler.
+const CONSOLE_JS: &str = r#"
+	(function() {
+		['debug', 'error', 'info', 'log', 'warn'].forEach(function(level) {
+			var original = console[level];
+			console[level] = function() {
+				window.webkit.messageHandlers.bw_console.postMessage(
+					[].map.call(arguments, String).join(' ')
+				);
+				return original.apply(console, arguments);
+			};
+		});
+	})();
+"#;
+
+/// Defines an event that connects to the signals of the web view once its first
+/// handler gets registered.
+macro_rules! def_browser_event_webkit {
+	($name:ident<$arg_type:ty> => $connector:ident) => {
+		def_event_gtk!(def_browser_event, BrowserWindowOwner, $name<$arg_type> => $connector, |owner| &owner.0.inner.inner);
+	};
+}
+
 impl BrowserWindowExt for BrowserWindowImpl {
+	fn bind(&self, name: &str) {
+		let js = format!("window.__bw_bind({});", JsValue::String(name.to_owned()));
+
+		// A user script defines the function for every page that gets loaded from now
+		// on. It is stored on the web view, so that `unbind` can remove it again.
+		let script = user_script(&js);
+		self.inner
+			.user_content_manager()
+			.unwrap()
+			.add_script(&script);
+		unsafe {
+			self.inner.set_data(&binding_key(name), script);
+		}
+
+		self.exec_js(&js);
+	}
+
+	fn can_go_back(&self) -> bool { self.inner.can_go_back() }
+
+	fn can_go_forward(&self) -> bool { self.inner.can_go_forward() }
+
 	fn cookie_jar(&self) -> Option<CookieJarImpl> { None }
 
+	fn download(&self, url: &str, _path: &Path, request: u32) -> bool {
+		// The download only decides on its destination later on, so it can still be
+		// marked here. The path is given to it by `BrowserWindowOwner::start_download`.
+		match self.inner.download_uri(url) {
+			Some(download) => {
+				unsafe { download.set_data(DOWNLOAD_REQUEST_KEY, request) };
+				true
+			}
+			None => false,
+		}
+	}
+
 	fn eval_js(&self, js: &str, callback: EvalJsCallbackFn, callback_data: *mut ()) {
 		let this = self.clone();
 		self.inner
@@ -65,8 +141,58 @@ impl BrowserWindowExt for BrowserWindowImpl {
 
 	fn free(&self) {}
 
+	fn go_back(&self) { self.inner.go_back(); }
+
+	fn go_forward(&self) { self.inner.go_forward(); }
+
+	fn history(&self) -> Vec<HistoryEntry> {
+		let list = match self.inner.back_forward_list() {
+			Some(l) => l,
+			None => return Vec::new(),
+		};
+
+		// The items are looked up relative to the current one, which has index 0.
+		let back_count = list.back_list().len() as i32;
+		let forward_count = list.forward_list().len() as i32;
+		(-back_count..=forward_count)
+			.filter_map(|i| list.nth_item(i).map(|item| (i, item)))
+			.enumerate()
+			.map(|(index, (i, item))| HistoryEntry {
+				index,
+				title: item.title().map(|t| t.to_string()).unwrap_or_default(),
+				url: item.uri().map(|u| u.to_string()).unwrap_or_default(),
+				current: i == 0,
+			})
+			.collect()
+	}
+
 	fn navigate(&self, uri: &str) { self.inner.load_uri(uri); }
 
+	fn reload(&self, ignore_cache: bool) {
+		if ignore_cache {
+			self.inner.reload_bypass_cache();
+		} else {
+			self.inner.reload();
+		}
+	}
+
+	fn stop(&self) { self.inner.stop_loading(); }
+
+	fn unbind(&self, name: &str) {
+		let script = unsafe { self.inner.steal_data::<UserScript>(&binding_key(name)) };
+		if let Some(script) = script {
+			self.inner
+				.user_content_manager()
+				.unwrap()
+				.remove_script(&script);
+		}
+
+		self.exec_js(&format!(
+			"window.__bw_unbind({});",
+			JsValue::String(name.to_owned())
+		));
+	}
+
 	fn new(
 		app: ApplicationImpl, parent: WindowImpl, source: Source, title: &str, width: Option<u32>,
 		height: Option<u32>, options: &WindowOptions,
@@ -83,8 +209,19 @@ impl BrowserWindowExt for BrowserWindowImpl {
 			inner: inner.clone(),
 		};
 
-		// Add the webview to the window
+		// Add the webview to the window. The window may already be visible if it was
+		// built that way, in which case the webview needs to be shown as well.
 		window.0.add(&inner);
+		inner.show();
+
+		// Create the global JS function `invoke_extern` before any of the page's own
+		// scripts run, for every page that will be loaded.
+		let user_content_manager = inner.user_content_manager().unwrap();
+		user_content_manager.register_script_message_handler("bw");
+		user_content_manager.add_script(&user_script(&INVOKE_EXTERN_JS.replace(
+			"POST_MESSAGE",
+			"window.webkit.messageHandlers.bw.postMessage",
+		)));
 
 		// Load the source
 		match source {
@@ -110,26 +247,9 @@ impl BrowserWindowExt for BrowserWindowImpl {
 		// view can not be loaded correctly.        Now we risk never notifying the
 		// future that is waiting on us.
 		let created = AtomicBool::new(false);
-		inner.connect_load_changed(move |i, e| {
-			if e == LoadEvent::Finished {
-				// Create the global JS function `invoke_extern`
-				i.evaluate_javascript(
-					r#"
-					function invoke_extern(...args) {
-						window.webkit.messageHandlers.bw.postMessage([].slice.call(args))
-					}
-				"#,
-					None,
-					None,
-					Option::<&Cancellable>::None,
-					|r| {
-						r.expect("invalid invoke_extern code");
-					},
-				);
-
-				if !created.swap(true, Ordering::Relaxed) {
-					creation_callback(this.clone(), callback_data);
-				}
+		inner.connect_load_changed(move |_, e| {
+			if e == LoadEvent::Finished && !created.swap(true, Ordering::Relaxed) {
+				creation_callback(this.clone(), callback_data);
 			}
 		});
 	}
@@ -146,45 +266,823 @@ impl BrowserWindowExt for BrowserWindowImpl {
 }
 
 impl BrowserWindowEventExt for BrowserWindowImpl {
-	fn on_message(&self, handle: Weak<BrowserWindowOwner>) -> MessageEvent {
-		MessageEvent::new(handle)
-	}
-}
-
-def_browser_event!(MessageEvent<MessageEventArgs>(&mut self, handler) {
-	// Register a message handler
-	let user_context_manager = self.owner.upgrade().unwrap().inner.inner.user_content_manager().unwrap();
-	user_context_manager.register_script_message_handler("bw");
-	let owner = self.owner.clone();
-	let h = Rc::new(Cell::new(handler));
-	user_context_manager.connect_script_message_received(Some("bw"), move |_, r| {
-		if let Some(this) = owner.upgrade() {
+	fn listen_for_messages(&self, owner: Weak<BrowserWindowOwner>) {
+		let user_content_manager = self.inner.user_content_manager().unwrap();
+		user_content_manager.connect_script_message_received(Some("bw"), move |_, r| {
 			let value = r
 				.js_value()
-				.map(|v| transform_js_value(v))
+				.map(transform_js_value)
 				.unwrap_or(JsValue::Undefined);
-			let (command, args) = match &value {
-				JsValue::Array(a) => (a[0].to_string_unenclosed(), a[1..].to_vec()),
+			let (id, command, args) = match &value {
+				JsValue::Array(a) => {
+					let id = match &a[0] {
+						JsValue::Number(id) => id.to_u64().unwrap_or_default() as u32,
+						_ => panic!("unexpected id received from invoke_extern"),
+					};
+					(id, a[1].to_string_unenclosed(), a[2..].to_vec())
+				}
 				_ => panic!("unexpected value type received from invoke_extern"),
 			};
 
-			let e = MessageEventArgs {
-				cmd: command.to_string(),
-				args
+			let e = MessageEventArgs::new(owner.clone(), id, command.to_string(), args);
+			BrowserWindowOwner::dispatch_message(&owner, e);
+		});
+	}
+
+	fn listen_for_auth_credentials(&self, owner: Weak<BrowserWindowOwner>) {
+		let key = "bw-listening-for-auth-credentials";
+		if unsafe { self.inner.data::<bool>(key) }.is_some() {
+			return;
+		}
+		unsafe { self.inner.set_data(key, true) };
+
+		self.inner.connect_authenticate(move |_, request| {
+			let scheme = match request.scheme() {
+				AuthenticationScheme::Default | AuthenticationScheme::HttpBasic => "basic",
+				AuthenticationScheme::HttpDigest => "digest",
+				AuthenticationScheme::HtmlForm => "form",
+				AuthenticationScheme::Ntlm => "ntlm",
+				AuthenticationScheme::Negotiate => "negotiate",
+				// Certificates aren't asked for with a username and password.
+				_ => return false,
 			};
-			match unsafe { &mut *h.as_ptr() } {
-				EventHandler::Sync(callback) => {
-					(callback)(&*this, e);
+
+			let r = request.clone();
+			let args = AuthCredentialsEventArgs::new(
+				request.host().map(|h| h.to_string()).unwrap_or_default(),
+				request.port() as _,
+				request.realm().map(|r| r.to_string()).unwrap_or_default(),
+				scheme.to_owned(),
+				request.is_for_proxy(),
+				request.is_retry(),
+				move |credentials| match credentials {
+					Some(c) => {
+						let credential = Credential::new(
+							&c.username,
+							&c.password,
+							CredentialPersistence::ForSession,
+						);
+						// The bindings are missing `authenticate`.
+						unsafe {
+							webkit2gtk::ffi::webkit_authentication_request_authenticate(
+								r.to_glib_none().0,
+								ToGlibPtr::<*const webkit2gtk::ffi::WebKitCredential>::to_glib_none(
+									&credential,
+								)
+								.0 as *mut _,
+							)
+						}
+					}
+					None => r.cancel(),
+				},
+			);
+			BrowserWindowOwner::request_auth_credentials(&owner, args);
+			true
+		});
+	}
+
+	fn listen_for_certificate_errors(&self, owner: Weak<BrowserWindowOwner>) {
+		let trust = self.window().app().trust;
+		self.inner
+			.connect_load_failed_with_tls_errors(move |view, failing_uri, cert, errors| {
+				let host = url_host(failing_uri).to_owned();
+				// Certificates issued by the extra CAs are trusted without asking.
+				if errors == TlsCertificateFlags::UNKNOWN_CA
+					&& is_issued_by_trusted_ca(&trust, cert)
+				{
+					allow_tls_certificate(view, cert, &host, failing_uri);
+					return true;
 				}
-				EventHandler::Async(callback) => {
-					let app = this.0.app();
-					let future = (callback)(BrowserWindow(this.clone()), e);
-					app.spawn(future);
+
+				// Without any handlers, the certificate is denied right away, in which case
+				// WebKitGTK shows its own error page.
+				let answered = Rc::new(Cell::new(None));
+				let answered2 = answered.clone();
+				let (v, c, uri) = (view.clone(), cert.clone(), failing_uri.to_owned());
+				let args = CertificateErrorEventArgs::new(
+					failing_uri.to_owned(),
+					certificate_error(errors),
+					certificate(cert),
+					move |allow| {
+						if allow {
+							allow_tls_certificate(&v, &c, &host, &uri);
+						}
+						answered2.set(Some(allow));
+					},
+				);
+				BrowserWindowOwner::handle_certificate_error(&owner, args);
+				answered.get() != Some(false)
+			});
+	}
+
+	fn listen_for_client_certificates(&self, owner: Weak<BrowserWindowOwner>) {
+		let key = "bw-listening-for-client-certificates";
+		if unsafe { self.inner.data::<bool>(key) }.is_some() {
+			return;
+		}
+		unsafe { self.inner.set_data(key, true) };
+
+		self.inner.connect_authenticate(move |_, request| {
+			if request.scheme() != AuthenticationScheme::ClientCertificateRequested {
+				return false;
+			}
+
+			let r = request.clone();
+			// WebKitGTK doesn't look for certificates by itself, so there are no
+			// candidates.
+			let args = SelectClientCertificateEventArgs::new(
+				request.host().map(|h| h.to_string()).unwrap_or_default(),
+				request.port() as _,
+				request.is_for_proxy(),
+				Vec::new(),
+				move |certificate| match certificate.and_then(|c| tls_certificate(&c)) {
+					Some(c) => {
+						let credential = Credential::for_certificate(
+							Some(&c),
+							CredentialPersistence::ForSession,
+						);
+						unsafe {
+							webkit2gtk::ffi::webkit_authentication_request_authenticate(
+								r.to_glib_none().0,
+								ToGlibPtr::<*const webkit2gtk::ffi::WebKitCredential>::to_glib_none(
+									&credential,
+								)
+								.0 as *mut _,
+							)
+						}
+					}
+					None => r.cancel(),
+				},
+			);
+			BrowserWindowOwner::select_client_certificate(&owner, args);
+			true
+		});
+	}
+
+	fn listen_for_downloads(&self, owner: Weak<BrowserWindowOwner>) {
+		let context = match self.inner.context() {
+			Some(c) => c,
+			None => return,
+		};
+
+		// Downloads are started on the web context, which may be shared with other
+		// web views.
+		let view = self.inner.downgrade();
+		let handler_id = context.connect_download_started(move |_, download| {
+			match (view.upgrade(), download.web_view()) {
+				(Some(v), Some(w)) if v == w => {}
+				_ => return,
+			}
+			connect_download(download, owner.clone());
+		});
+		let handler_id = RefCell::new(Some(handler_id));
+		self.inner.connect_destroy(move |_| {
+			if let Some(id) = handler_id.borrow_mut().take() {
+				context.disconnect(id);
+			}
+		});
+	}
+
+	fn listen_for_file_dialogs(&self, owner: Weak<BrowserWindowOwner>) {
+		let key = "bw-listening-for-file-dialogs";
+		if unsafe { self.inner.data::<bool>(key) }.is_some() {
+			return;
+		}
+		unsafe { self.inner.set_data(key, true) };
+
+		self.inner.connect_run_file_chooser(move |_, request| {
+			let mode = if request.selects_multiple() {
+				FileDialogMode::OpenMultiple
+			} else {
+				FileDialogMode::Open
+			};
+			let accept = request.mime_types().iter().map(|m| m.to_string()).collect();
+
+			let r = request.clone();
+			let args = FileDialogEventArgs::new(mode, String::new(), None, accept, move |paths| {
+				if paths.is_empty() {
+					r.cancel();
+				} else {
+					let paths: Vec<Cow<'_, str>> =
+						paths.iter().map(|p| p.to_string_lossy()).collect();
+					let paths: Vec<&str> = paths.iter().map(|p| p.as_ref()).collect();
+					r.select_files(&paths);
+				}
+			});
+			BrowserWindowOwner::open_file_dialog(&owner, args)
+		});
+	}
+
+	fn listen_for_keys(&self, owner: Weak<BrowserWindowOwner>) {
+		let key = "bw-listening-for-keys";
+		if unsafe { self.inner.data::<bool>(key) }.is_some() {
+			return;
+		}
+		unsafe { self.inner.set_data(key, true) };
+
+		// GTK doesn't tell whether a key is repeated, so the keys that are held down
+		// are kept track of.
+		let held_down = Rc::new(RefCell::new(HashSet::new()));
+		// WebKitGTK dispatches the keys that the page hasn't handled once more, so
+		// their repeat state is remembered for the second time.
+		let passed_to_page = Rc::new(Cell::new(None));
+
+		let window = self.window().0;
+		let h = held_down.clone();
+		window.connect_key_press_event(move |w, event| {
+			let id = (event.time(), event.hardware_keycode());
+			let consumed = match passed_to_page.take() {
+				Some((passed_id, is_repeat)) if passed_id == id =>
+					BrowserWindowOwner::key_pressed(&owner, key_event_args(w, event, is_repeat)),
+				_ => {
+					let is_repeat = !h.borrow_mut().insert(event.hardware_keycode());
+					if BrowserWindowOwner::key_press(&owner, key_event_args(w, event, is_repeat)) {
+						true
+					} else {
+						passed_to_page.set(Some((id, is_repeat)));
+						false
+					}
 				}
+			};
+			if consumed {
+				gtk::glib::Propagation::Stop
+			} else {
+				gtk::glib::Propagation::Proceed
 			}
+		});
+		window.connect_key_release_event(move |_, event| {
+			held_down.borrow_mut().remove(&event.hardware_keycode());
+			gtk::glib::Propagation::Proceed
+		});
+	}
+
+	fn listen_for_navigation_requests(&self, owner: Weak<BrowserWindowOwner>) {
+		// WebKitGTK asks about navigations of all frames, and about new windows
+		// being opened, but it doesn't tell which frame navigates.
+		self.inner
+			.connect_decide_policy(move |_, decision, decision_type| {
+				match decision_type {
+					PolicyDecisionType::NavigationAction | PolicyDecisionType::NewWindowAction => {}
+					_ => return false,
+				}
+				let action = match decision
+					.downcast_ref::<NavigationPolicyDecision>()
+					.and_then(|d| d.navigation_action())
+				{
+					Some(a) => a,
+					None => return false,
+				};
+
+				let url = action
+					.request()
+					.and_then(|r| r.uri())
+					.map(|u| u.to_string())
+					.unwrap_or_default();
+				let navigation_type = match action.navigation_type() {
+					webkit2gtk::NavigationType::LinkClicked => NavigationType::Link,
+					webkit2gtk::NavigationType::FormSubmitted
+					| webkit2gtk::NavigationType::FormResubmitted => NavigationType::FormSubmission,
+					webkit2gtk::NavigationType::BackForward => NavigationType::BackForward,
+					webkit2gtk::NavigationType::Reload => NavigationType::Reload,
+					_ => NavigationType::Other,
+				};
+				// Assumed to be the main frame, see `is_main_frame`.
+				let args = NavigationRequestEventArgs::new(
+					url,
+					true,
+					action.is_user_gesture(),
+					navigation_type,
+				);
+
+				if BrowserWindowOwner::request_navigation(&owner, args) {
+					decision.ignore();
+					true
+				} else {
+					false
+				}
+			});
+	}
+
+	fn listen_for_requests(&self, owner: Weak<BrowserWindowOwner>) {
+		let key = "bw-listening-for-requests";
+		if unsafe { self.inner.data::<bool>(key) }.is_some() {
+			return;
 		}
+		unsafe { self.inner.set_data(key, true) };
+
+		// The requests are sent by the network process, so changing them from here
+		// would have no effect. That's why `RequestEventArgs` has no methods to
+		// change the request with on WebkitGTK.
+		self.inner
+			.connect_resource_load_started(move |_, _, request| {
+				let args = RequestEventArgs::new(
+					request.uri().map(|u| u.to_string()).unwrap_or_default(),
+					request_method(request),
+					request
+						.http_headers()
+						.map(|h| message_headers(&h))
+						.unwrap_or_default(),
+				);
+				BrowserWindowOwner::intercept_request(&owner, args);
+			});
+	}
+
+	fn on_address_changed(&self, handle: Weak<BrowserWindowOwner>) -> AddressChangedEvent {
+		AddressChangedEvent::new(handle)
+	}
+
+	fn on_console_message(&self, handle: Weak<BrowserWindowOwner>) -> ConsoleMessageEvent {
+		ConsoleMessageEvent::new(handle)
+	}
+
+	fn on_favicon_changed(&self, handle: Weak<BrowserWindowOwner>) -> FaviconChangedEvent {
+		FaviconChangedEvent::new(handle)
+	}
+
+	fn on_fullscreen_mode_changed(
+		&self, handle: Weak<BrowserWindowOwner>,
+	) -> FullscreenModeChangedEvent {
+		FullscreenModeChangedEvent::new(handle)
+	}
+
+	fn on_loading_progress_changed(
+		&self, handle: Weak<BrowserWindowOwner>,
+	) -> LoadingProgressChangedEvent {
+		LoadingProgressChangedEvent::new(handle)
+	}
+
+	fn on_navigation_end(&self, handle: Weak<BrowserWindowOwner>) -> NavigationEndEvent {
+		NavigationEndEvent::new(handle)
+	}
+
+	fn on_navigation_start(&self, handle: Weak<BrowserWindowOwner>) -> NavigationStartEvent {
+		NavigationStartEvent::new(handle)
+	}
+
+	fn on_page_title_changed(&self, handle: Weak<BrowserWindowOwner>) -> PageTitleChangedEvent {
+		PageTitleChangedEvent::new(handle)
+	}
+
+	fn on_response(&self, handle: Weak<BrowserWindowOwner>) -> ResponseEvent {
+		ResponseEvent::new(handle)
+	}
+
+	fn on_status_message(&self, handle: Weak<BrowserWindowOwner>) -> StatusMessageEvent {
+		StatusMessageEvent::new(handle)
+	}
+
+	fn on_tooltip(&self, handle: Weak<BrowserWindowOwner>) -> TooltipEvent {
+		TooltipEvent::new(handle)
+	}
+}
+
+def_browser_event_webkit!(AddressChangedEvent<String> => connect_address_changed);
+def_browser_event_webkit!(ConsoleMessageEvent<String> => connect_console_message);
+def_browser_event_webkit!(FaviconChangedEvent<String> => connect_favicon_changed);
+def_browser_event_webkit!(FullscreenModeChangedEvent<bool> => connect_fullscreen_mode_changed);
+def_browser_event_webkit!(LoadingProgressChangedEvent<f64> => connect_loading_progress_changed);
+def_browser_event_webkit!(NavigationStartEvent<()> => connect_navigation_start);
+def_browser_event_webkit!(NavigationEndEvent<Result<(), Error>> => connect_navigation_end);
+def_browser_event_webkit!(PageTitleChangedEvent<String> => connect_page_title_changed);
+def_browser_event_webkit!(ResponseEvent<ResponseEventArgs> => connect_response);
+def_browser_event_webkit!(StatusMessageEvent<String> => connect_status_message);
+def_browser_event_webkit!(TooltipEvent<String> => connect_tooltip);
+
+fn connect_address_changed(view: &WebView, fire: impl Fn(String) + 'static) {
+	view.connect_uri_notify(move |v| fire(v.uri().map(|u| u.to_string()).unwrap_or_default()));
+}
+
+fn connect_console_message(view: &WebView, fire: impl Fn(String) + 'static) {
+	// WebKitGTK has no signal for console messages, so the console functions are
+	// replaced by ones that also post their message to us.
+	let user_content_manager = view.user_content_manager().unwrap();
+	user_content_manager.register_script_message_handler("bw_console");
+	user_content_manager.connect_script_message_received(Some("bw_console"), move |_, r| {
+		fire(
+			r.js_value()
+				.map(|v| v.to_str().to_string())
+				.unwrap_or_default(),
+		);
+	});
+	user_content_manager.add_script(&user_script(CONSOLE_JS));
+	BrowserWindowImpl {
+		inner: view.clone(),
+	}
+	.exec_js(CONSOLE_JS);
+}
+
+fn connect_favicon_changed(view: &WebView, fire: impl Fn(String) + 'static) {
+	// Favicons are only loaded when the favicon database is enabled, which can only
+	// be done once.
+	let context = view.context().unwrap();
+	if context.favicon_database_directory().is_none() {
+		context.set_favicon_database_directory(None);
+	}
+
+	view.connect_favicon_notify(move |v| {
+		let uri = v
+			.uri()
+			.and_then(|page_uri| v.context()?.favicon_database()?.favicon_uri(&page_uri));
+		fire(uri.map(|u| u.to_string()).unwrap_or_default());
+	});
+}
+
+fn connect_fullscreen_mode_changed(view: &WebView, fire: impl Fn(bool) + 'static) {
+	let fire = Rc::new(fire);
+	let f = fire.clone();
+	view.connect_enter_fullscreen(move |_| {
+		f(true);
+		false
+	});
+	view.connect_leave_fullscreen(move |_| {
+		fire(false);
+		false
+	});
+}
+
+fn connect_loading_progress_changed(view: &WebView, fire: impl Fn(f64) + 'static) {
+	view.connect_estimated_load_progress_notify(move |v| fire(v.estimated_load_progress()));
+}
+
+fn connect_navigation_start(view: &WebView, fire: impl Fn(()) + 'static) {
+	view.connect_load_changed(move |_, e| {
+		if e == LoadEvent::Started {
+			fire(());
+		}
+	});
+}
+
+fn connect_navigation_end(view: &WebView, fire: impl Fn(Result<(), Error>) + 'static) {
+	// A failed load is followed by `LoadEvent::Finished` as well, which shouldn't
+	// be reported a second time.
+	let fire = Rc::new(fire);
+	let failed = Rc::new(Cell::new(false));
+	let (f, fl) = (fire.clone(), failed.clone());
+	view.connect_load_failed(move |_, _, _, error| {
+		fl.set(true);
+		f(Err(error.clone().into()));
+		false
+	});
+	view.connect_load_changed(move |_, e| {
+		if e == LoadEvent::Finished && !failed.replace(false) {
+			fire(Ok(()));
+		}
+	});
+}
+
+fn connect_page_title_changed(view: &WebView, fire: impl Fn(String) + 'static) {
+	view.connect_title_notify(move |v| fire(v.title().map(|t| t.to_string()).unwrap_or_default()));
+}
+
+fn connect_response(view: &WebView, fire: impl Fn(ResponseEventArgs) + 'static) {
+	let fire = Rc::new(fire);
+	view.connect_resource_load_started(move |_, resource, request| {
+		let (f, method) = (fire.clone(), request_method(request));
+		resource.connect_finished(move |r| {
+			if let Some(response) = r.response() {
+				f(ResponseEventArgs {
+					url: response.uri().map(|u| u.to_string()).unwrap_or_default(),
+					method: method.clone(),
+					status: response.status_code() as _,
+					headers: response
+						.http_headers()
+						.map(|h| message_headers(&h))
+						.unwrap_or_default(),
+				});
+			}
+		});
+	});
+}
+
+fn connect_status_message(view: &WebView, fire: impl Fn(String) + 'static) {
+	// Like other browsers, show the address of the link that is being hovered over.
+	connect_hit_test_changed(view, fire, |r| r.link_uri());
+}
+
+fn connect_tooltip(view: &WebView, fire: impl Fn(String) + 'static) {
+	connect_hit_test_changed(view, fire, |r| r.link_title());
+}
+
+/// Fires with the string that `get` takes from the element under the mouse,
+/// whenever it changes.
+fn connect_hit_test_changed(
+	view: &WebView, fire: impl Fn(String) + 'static,
+	get: fn(&webkit2gtk::HitTestResult) -> Option<gtk::glib::GString>,
+) {
+	let last = RefCell::new(String::new());
+	view.connect_mouse_target_changed(move |_, r, _| {
+		let text = get(r).map(|t| t.to_string()).unwrap_or_default();
+		if *last.borrow() != text {
+			*last.borrow_mut() = text.clone();
+			fire(text);
+		}
+	});
+}
+
+impl BrowserWindowImpl {
+	fn exec_js(&self, js: &str) {
+		self.inner
+			.evaluate_javascript(js, None, None, Option::<&Cancellable>::None, |_| {});
+	}
+}
+
+/// Passes the events of the download on to the owner.
+fn connect_download(download: &Download, owner: Weak<BrowserWindowOwner>) {
+	static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+	let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+	let url = download
+		.request()
+		.and_then(|r| r.uri())
+		.map(|u| u.to_string())
+		.unwrap_or_default();
+
+	let (o, u) = (owner.clone(), url.clone());
+	download.connect_decide_destination(move |d, suggested_filename| {
+		let response = d.response();
+		let d = d.clone();
+		let filename = suggested_filename.to_owned();
+		let request = download_request(&d);
+		let args = DownloadStartedEventArgs::new(
+			id,
+			u.clone(),
+			suggested_filename.to_owned(),
+			response
+				.as_ref()
+				.and_then(|r| r.mime_type())
+				.map(|m| m.to_string())
+				.unwrap_or_default(),
+			download_size(&d),
+			move |path| match path {
+				Some(p) => match download_destination(&p, &filename) {
+					Some(uri) => d.set_destination(&uri),
+					None => d.cancel(),
+				},
+				None => d.cancel(),
+			},
+		);
+		BrowserWindowOwner::start_download(&o, request, args);
+		true
 	});
-});
+
+	let (o, u) = (owner.clone(), url.clone());
+	download.connect_received_data(move |d, _| {
+		let args = DownloadProgressEventArgs {
+			id,
+			url: u.clone(),
+			received: d.received_data_length(),
+			total_size: download_size(d),
+		};
+		BrowserWindowOwner::download_progress(&o, args);
+	});
+
+	// The finished signal is emitted after the failed signal as well.
+	let failed = Rc::new(Cell::new(false));
+	let (o, u, f) = (owner.clone(), url.clone(), failed.clone());
+	download.connect_failed(move |d, error| {
+		f.set(true);
+		let result = if error.kind::<webkit2gtk::DownloadError>()
+			== Some(webkit2gtk::DownloadError::CancelledByUser)
+		{
+			Err(DownloadError::Cancelled)
+		} else {
+			Err(DownloadError::Failed(error.message().to_owned()))
+		};
+		let args = DownloadFinishedEventArgs {
+			id,
+			url: u.clone(),
+			path: download_path(d),
+			result,
+		};
+		// The download may fail before it has decided on its destination, while it
+		// is still only known by its request.
+		BrowserWindowOwner::finish_download(&o, download_request(d), args);
+	});
+	download.connect_finished(move |d| {
+		if failed.get() {
+			return;
+		}
+		let args = DownloadFinishedEventArgs {
+			id,
+			url: url.clone(),
+			path: download_path(d),
+			result: Ok(()),
+		};
+		BrowserWindowOwner::finish_download(&owner, download_request(d), args);
+	});
+}
+
+/// The request that `download` has marked the download with, if it has been
+/// started by it.
+fn download_request(download: &Download) -> Option<u32> {
+	unsafe { download.data::<u32>(DOWNLOAD_REQUEST_KEY) }.map(|r| unsafe { *r.as_ref() })
+}
+
+/// The URI to save a download at. An empty path stands for the user's
+/// download directory.
+fn download_destination(path: &Path, suggested_filename: &str) -> Option<String> {
+	let path = if path.as_os_str().is_empty() {
+		gtk::glib::user_special_dir(gtk::glib::UserDirectory::Downloads)?.join(suggested_filename)
+	} else {
+		std::env::current_dir().ok()?.join(path)
+	};
+	gtk::glib::filename_to_uri(path, None)
+		.ok()
+		.map(|u| u.to_string())
+}
+
+fn download_path(download: &Download) -> PathBuf {
+	download
+		.destination()
+		.and_then(|uri| gtk::glib::filename_from_uri(&uri).ok())
+		.map(|(path, _)| path)
+		.unwrap_or_default()
+}
+
+/// A content length of 0 means that the size is unknown.
+fn download_size(download: &Download) -> Option<u64> {
+	match download.response()?.content_length() {
+		0 => None,
+		length => Some(length),
+	}
+}
+
+fn key_event_args(widget: &gtk::Window, event: &EventKey, is_repeat: bool) -> KeyEventArgs {
+	let state = event.state();
+	let modifiers = Modifiers {
+		ctrl: state.contains(ModifierType::CONTROL_MASK),
+		shift: state.contains(ModifierType::SHIFT_MASK),
+		alt: state.contains(ModifierType::MOD1_MASK),
+		meta: state.intersects(ModifierType::SUPER_MASK | ModifierType::META_MASK),
+	};
+
+	// The key code shouldn't depend on the modifiers, so the key is looked up
+	// without them.
+	let keyval = gdk::Keymap::for_display(&widget.display())
+		.and_then(|k| {
+			k.translate_keyboard_state(
+				event.hardware_keycode() as _,
+				ModifierType::empty(),
+				event.group() as _,
+			)
+		})
+		.map(|(keyval, ..)| keyval)
+		.unwrap_or(*event.keyval());
+
+	KeyEventArgs::new(
+		key_code(keyval),
+		modifiers,
+		is_repeat,
+		event.keyval().to_unicode().filter(|c| !c.is_control()),
+	)
+}
+
+/// Converts a GDK key value to a Windows virtual-key code, or 0 if it doesn't
+/// have one.
+#[allow(non_upper_case_globals)]
+fn key_code(keyval: u32) -> u32 {
+	use gdk::ffi::*;
+
+	match keyval as i32 {
+		k @ GDK_KEY_a..=GDK_KEY_z => (k - GDK_KEY_a) as u32 + 'A' as u32,
+		k @ (GDK_KEY_A..=GDK_KEY_Z | GDK_KEY_0..=GDK_KEY_9) => k as u32,
+		k @ GDK_KEY_F1..=GDK_KEY_F24 => (k - GDK_KEY_F1) as u32 + key::F1,
+		GDK_KEY_BackSpace => key::BACKSPACE,
+		GDK_KEY_Tab | GDK_KEY_ISO_Left_Tab => key::TAB,
+		GDK_KEY_Return | GDK_KEY_KP_Enter => key::ENTER,
+		GDK_KEY_Escape => key::ESCAPE,
+		GDK_KEY_space => key::SPACE,
+		GDK_KEY_Page_Up => key::PAGE_UP,
+		GDK_KEY_Page_Down => key::PAGE_DOWN,
+		GDK_KEY_End => key::END,
+		GDK_KEY_Home => key::HOME,
+		GDK_KEY_Left => key::LEFT,
+		GDK_KEY_Up => key::UP,
+		GDK_KEY_Right => key::RIGHT,
+		GDK_KEY_Down => key::DOWN,
+		GDK_KEY_Insert => key::INSERT,
+		GDK_KEY_Delete => key::DELETE,
+		GDK_KEY_equal | GDK_KEY_plus => key::PLUS,
+		GDK_KEY_comma => key::COMMA,
+		GDK_KEY_minus => key::MINUS,
+		GDK_KEY_period => key::PERIOD,
+		GDK_KEY_Shift_L | GDK_KEY_Shift_R => 0x10,
+		GDK_KEY_Control_L | GDK_KEY_Control_R => 0x11,
+		GDK_KEY_Alt_L | GDK_KEY_Alt_R => 0x12,
+		GDK_KEY_Super_L | GDK_KEY_Super_R | GDK_KEY_Meta_L | GDK_KEY_Meta_R => 0x5B,
+		_ => 0,
+	}
+}
+
+/// Trusts the certificate for the host from now on, and loads the page again.
+fn allow_tls_certificate(view: &WebView, cert: &TlsCertificate, host: &str, uri: &str) {
+	if let Some(context) = view.context() {
+		context.allow_tls_certificate_for_host(cert, host);
+	}
+	view.load_uri(uri);
+}
+
+fn certificate(cert: &TlsCertificate) -> Certificate {
+	// These properties only exist since GLib 2.70.
+	let name = |property: &str| -> String {
+		if cert.find_property(property).is_none() {
+			return String::new();
+		}
+		cert.property::<Option<String>>(property)
+			.unwrap_or_default()
+	};
+	let time = |property: &str| -> Option<SystemTime> {
+		cert.find_property(property)?;
+		let timestamp = cert.property::<Option<DateTime>>(property)?.to_unix();
+		if timestamp >= 0 {
+			SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(timestamp as u64))
+		} else {
+			SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(timestamp.unsigned_abs()))
+		}
+	};
+
+	Certificate {
+		subject: name("subject-name"),
+		issuer: name("issuer-name"),
+		valid_from: time("not-valid-before"),
+		valid_until: time("not-valid-after"),
+		der: cert.certificate().map(|d| d.to_vec()).unwrap_or_default(),
+	}
+}
+
+fn certificate_error(errors: TlsCertificateFlags) -> CertificateError {
+	if errors.contains(TlsCertificateFlags::BAD_IDENTITY) {
+		CertificateError::HostMismatch
+	} else if errors.intersects(TlsCertificateFlags::EXPIRED | TlsCertificateFlags::NOT_ACTIVATED) {
+		CertificateError::InvalidDate
+	} else if errors.contains(TlsCertificateFlags::UNKNOWN_CA) {
+		CertificateError::UnknownAuthority
+	} else if errors.contains(TlsCertificateFlags::REVOKED) {
+		CertificateError::Revoked
+	} else if errors.contains(TlsCertificateFlags::INSECURE) {
+		CertificateError::Invalid
+	} else {
+		CertificateError::Other
+	}
+}
+
+/// Whether the certificate has been issued by one of the extra CAs that are
+/// trusted.
+fn is_issued_by_trusted_ca(trust: &Trust, cert: &TlsCertificate) -> bool {
+	trust
+		.ca_certificates
+		.iter()
+		.any(|pem| match TlsCertificate::from_pem(pem) {
+			Ok(ca) => cert
+				.verify(None::<&gtk::gio::SocketConnectable>, Some(&ca))
+				.is_empty(),
+			Err(_) => false,
+		})
+}
+
+/// Loads the client certificate together with its private key.
+fn tls_certificate(certificate: &ClientCertificate) -> Option<TlsCertificate> {
+	match certificate {
+		ClientCertificate::Candidate(_) => None,
+		ClientCertificate::Pem { certificate, key } =>
+			TlsCertificate::from_pem(&format!("{}\n{}", certificate, key)).ok(),
+		// This is why GLib 2.72 is required.
+		ClientCertificate::Pkcs12 { data, password } =>
+			TlsCertificate::from_pkcs12(data, password.as_deref()).ok(),
+	}
+}
+
+fn message_headers(headers: &soup::MessageHeaders) -> Vec<(String, String)> {
+	let mut result = Vec::new();
+	headers.foreach(|name, value| result.push((name.to_owned(), value.to_owned())));
+	result
+}
+
+/// WebkitGTK doesn't always know the method, in which case it is a GET request.
+fn request_method(request: &URIRequest) -> String {
+	request
+		.http_method()
+		.map(|m| m.to_string())
+		.unwrap_or_else(|| "GET".to_owned())
+}
+
+/// The key under which the user script of a bound function is stored on the
+/// web view.
+fn binding_key(name: &str) -> String { format!("bw-binding:{}", name) }
+
+/// The key of the request that a download has been started with by
+/// `BrowserWindowExt::download`.
+const DOWNLOAD_REQUEST_KEY: &str = "bw-download-request";
+
+/// A script that runs in the main frame, before the page's own scripts do.
+fn user_script(source: &str) -> UserScript {
+	UserScript::new(
+		source,
+		UserContentInjectedFrames::TopFrame,
+		UserScriptInjectionTime::Start,
+		&[],
+		&[],
+	)
+}
 
 fn transform_js_value(v: javascriptcore::Value) -> JsValue {
 	if v.is_array() {
@@ -0,0 +1,9 @@
+#[cfg(not(feature = "webkitgtk"))]
+mod c;
+#[cfg(feature = "webkitgtk")]
+mod webkit;
+
+#[cfg(not(feature = "webkitgtk"))]
+pub use c::*;
+#[cfg(feature = "webkitgtk")]
+pub use webkit::*;
@@ -0,0 +1,13 @@
+use crate::prelude::*;
+
+/// Decodes base64 without any whitespace in it.
+/// Always fails for Edge WebView2.
+pub fn decode_base64(input: &str) -> Option<Vec<u8>> {
+	let mut output = vec![0u8; input.len() / 4 * 3 + 3];
+	let mut len = output.len();
+	if unsafe { cbw_decodeBase64(input.into(), output.as_mut_ptr(), &mut len) } == 0 {
+		return None;
+	}
+	output.truncate(len);
+	Some(output)
+}
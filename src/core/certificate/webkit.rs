@@ -0,0 +1,4 @@
+use glib::base64_decode;
+
+/// Decodes base64 without any whitespace in it.
+pub fn decode_base64(input: &str) -> Option<Vec<u8>> { Some(base64_decode(input)) }
@@ -1,14 +1,14 @@
 #[cfg(not(feature = "webkitgtk"))]
 mod c;
 #[cfg(feature = "webkitgtk")]
-mod unsupported;
+mod webkit;
 
 use std::{borrow::Cow, error::Error, fmt, time::SystemTime};
 
 #[cfg(not(feature = "webkitgtk"))]
 pub use c::*;
 #[cfg(feature = "webkitgtk")]
-pub use unsupported::*;
+pub use webkit::*;
 
 pub type CookieStorageCallbackFn =
 	unsafe fn(cj: CookieJarImpl, data: *mut (), Result<(), CookieStorageError>);
@@ -1,9 +0,0 @@
-use super::*;
-
-pub struct CookieImpl {}
-pub struct CookieIteratorImpl {}
-pub struct CookieJarImpl {}
-
-impl CookieExt for CookieImpl {}
-impl CookieIteratorExt for CookieIteratorImpl {}
-impl CookieJarExt for CookieJarImpl {}
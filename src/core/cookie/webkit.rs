@@ -0,0 +1,308 @@
+// The callback data pointers are only passed on to their callbacks.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+use std::{
+	borrow::Cow,
+	cell::{Cell, RefCell},
+	collections::VecDeque,
+	ptr,
+	rc::Rc,
+	time::{Duration, SystemTime},
+};
+
+use gtk::{
+	gio::{self, Cancellable},
+	glib::{
+		self, DateTime, Uri, UriFlags,
+		translate::{FromGlibPtrContainer, ToGlibPtr},
+	},
+};
+use webkit2gtk::{
+	CookieManager, CookieManagerExt, WebContext, WebContextExt, WebsiteDataManager,
+	WebsiteDataManagerExt,
+};
+
+use super::*;
+
+pub struct CookieImpl {
+	// The getters of `soup::Cookie` take a mutable reference.
+	inner: RefCell<soup::Cookie>,
+	creation_time: SystemTime,
+}
+
+#[derive(Clone)]
+pub struct CookieJarImpl(WebsiteDataManager);
+
+#[derive(Clone)]
+pub struct CookieIteratorImpl {
+	data_manager: WebsiteDataManager,
+	url: String,
+	include_http_only: bool,
+	/// The cookies that are left, or `None` if they haven't been fetched yet.
+	cookies: Rc<RefCell<Option<VecDeque<soup::Cookie>>>>,
+}
+
+impl CookieExt for CookieImpl {
+	fn new(name: &str, value: &str) -> CookieImpl {
+		Self {
+			inner: RefCell::new(soup::Cookie::new(name, value, "", "", -1)),
+			creation_time: SystemTime::now(),
+		}
+	}
+
+	fn creation_time(&self) -> SystemTime { self.creation_time }
+
+	fn expires(&self) -> Option<SystemTime> {
+		let expires = self.inner.borrow_mut().expires()?;
+		Some(SystemTime::UNIX_EPOCH + Duration::from_secs(expires.to_unix().max(0) as u64))
+	}
+
+	fn domain<'a>(&'a self) -> Cow<'a, str> {
+		let domain = self.inner.borrow_mut().domain();
+		domain.map(|d| d.to_string()).unwrap_or_default().into()
+	}
+
+	fn is_http_only(&self) -> bool { self.inner.borrow_mut().is_http_only() }
+
+	fn name<'a>(&'a self) -> Cow<'a, str> { cookie_name(&mut self.inner.borrow_mut()).into() }
+
+	fn path<'a>(&'a self) -> Cow<'a, str> {
+		let path = self.inner.borrow_mut().path();
+		path.map(|p| p.to_string()).unwrap_or_default().into()
+	}
+
+	fn is_secure(&self) -> bool { self.inner.borrow_mut().is_secure() }
+
+	fn value<'a>(&'a self) -> Cow<'a, str> {
+		let value = self.inner.borrow_mut().value();
+		value.map(|v| v.to_string()).unwrap_or_default().into()
+	}
+
+	fn make_http_only(&mut self) { self.inner.get_mut().set_http_only(true); }
+
+	fn make_secure(&mut self) { self.inner.get_mut().set_secure(true); }
+
+	fn set_creation_time(&mut self, time: &SystemTime) { self.creation_time = *time; }
+
+	fn set_expires(&mut self, time: &SystemTime) {
+		// Times before 1970 are clamped, like `expires` does.
+		let timestamp = time
+			.duration_since(SystemTime::UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_secs();
+		if let Ok(expires) = DateTime::from_unix_utc(timestamp as _) {
+			self.inner.get_mut().set_expires(&expires);
+		}
+	}
+
+	fn set_domain(&mut self, domain: &str) { self.inner.get_mut().set_domain(domain); }
+
+	fn set_name(&mut self, name: &str) { self.inner.get_mut().set_name(name); }
+
+	fn set_path(&mut self, path: &str) { self.inner.get_mut().set_path(path); }
+
+	fn set_value(&mut self, value: &str) { self.inner.get_mut().set_value(value); }
+}
+
+impl From<soup::Cookie> for CookieImpl {
+	fn from(inner: soup::Cookie) -> Self {
+		Self {
+			inner: RefCell::new(inner),
+			// libsoup doesn't keep track of when a cookie has been created.
+			// WebkitGTK doesn't know when its cookies have been created.
+			creation_time: SystemTime::UNIX_EPOCH,
+		}
+	}
+}
+
+impl CookieJarExt for CookieJarImpl {
+	fn delete(
+		&mut self, url: &str, name: &str, complete_cb: CookieDeleteCallbackFn, cb_data: *mut (),
+	) {
+		let this = self.clone();
+		let name = name.to_owned();
+		fetch_cookies(&self.0, url, move |cookies| {
+			let matching: Vec<_> = cookies
+				.into_iter()
+				.filter_map(|mut c| (name.is_empty() || cookie_name(&mut c) == name).then_some(c))
+				.collect();
+			if matching.is_empty() {
+				unsafe { complete_cb(this, cb_data, 0) };
+				return;
+			}
+
+			// Report back once the last cookie has been deleted
+			let manager = this.cookie_manager();
+			let remaining = Rc::new(Cell::new(matching.len()));
+			let deleted = Rc::new(Cell::new(0));
+			for mut cookie in matching {
+				let (this, remaining, deleted) = (this.clone(), remaining.clone(), deleted.clone());
+				manager.delete_cookie(&mut cookie, Option::<&Cancellable>::None, move |r| {
+					if r.is_ok() {
+						deleted.set(deleted.get() + 1);
+					}
+					remaining.set(remaining.get() - 1);
+					if remaining.get() == 0 {
+						unsafe { complete_cb(this, cb_data, deleted.get()) };
+					}
+				});
+			}
+		});
+	}
+
+	fn global() -> Option<CookieJarImpl> {
+		let data_manager = WebContext::default()?.website_data_manager()?;
+		Some(Self(data_manager))
+	}
+
+	fn iterator(&self, url: &str, include_http_only: bool) -> CookieIteratorImpl {
+		CookieIteratorImpl {
+			data_manager: self.0.clone(),
+			url: url.to_owned(),
+			include_http_only,
+			cookies: Rc::new(RefCell::new(None)),
+		}
+	}
+
+	fn iterator_all(&self) -> CookieIteratorImpl { self.iterator("", true) }
+
+	fn store(
+		&mut self, url: &str, cookie: &CookieImpl, complete_cb: Option<CookieStorageCallbackFn>,
+		cb_data: *mut (),
+	) {
+		let host = Uri::parse(url, UriFlags::NONE)
+			.ok()
+			.and_then(|u| u.host())
+			.filter(|h| !h.is_empty());
+		let host = match host {
+			Some(h) => h,
+			None => {
+				if let Some(cb) = complete_cb {
+					unsafe { cb(self.clone(), cb_data, Err(CookieStorageError::Unknown)) };
+				}
+				return;
+			}
+		};
+
+		// Like with CEF, a cookie without a domain becomes a cookie of the url's host.
+		let mut inner = cookie.inner.borrow().clone();
+		if inner.domain().unwrap_or_default().is_empty() {
+			inner.set_domain(&host);
+		}
+		if inner.path().unwrap_or_default().is_empty() {
+			inner.set_path("/");
+		}
+
+		let this = self.clone();
+		self.cookie_manager()
+			.add_cookie(&mut inner, Option::<&Cancellable>::None, move |r| {
+				if let Some(cb) = complete_cb {
+					let result = r.map_err(|_| CookieStorageError::Unknown);
+					unsafe { cb(this, cb_data, result) };
+				}
+			});
+	}
+}
+
+impl CookieJarImpl {
+	fn cookie_manager(&self) -> CookieManager {
+		self.0
+			.cookie_manager()
+			.expect("website data manager without cookie manager")
+	}
+}
+
+impl CookieIteratorExt for CookieIteratorImpl {
+	fn next(&mut self, on_next: CookieIteratorNextCallbackFn, cb_data: *mut ()) -> bool {
+		let next = self.cookies.borrow_mut().as_mut().map(|c| c.pop_front());
+		match next {
+			Some(Some(cookie)) => {
+				unsafe { on_next(self.clone(), cb_data, Some(cookie.into())) };
+				true
+			}
+			Some(None) => false,
+			// The cookies are fetched when the first one is requested.
+			None => {
+				let this = self.clone();
+				fetch_cookies(&self.data_manager, &self.url, move |mut cookies| {
+					if !this.include_http_only {
+						cookies.retain_mut(|c| !c.is_http_only());
+					}
+					let mut cookies = VecDeque::from(cookies);
+					let first = cookies.pop_front();
+					*this.cookies.borrow_mut() = Some(cookies);
+					unsafe { on_next(this.clone(), cb_data, first.map(|c| c.into())) };
+				});
+				true
+			}
+		}
+	}
+}
+
+fn cookie_name(cookie: &mut soup::Cookie) -> String {
+	cookie.name().map(|n| n.to_string()).unwrap_or_default()
+}
+
+/// Fetches the cookies that apply to `url`, or all cookies if `url` is empty.
+fn fetch_cookies(
+	data_manager: &WebsiteDataManager, url: &str,
+	on_complete: impl FnOnce(Vec<soup::Cookie>) + 'static,
+) {
+	let manager = data_manager
+		.cookie_manager()
+		.expect("website data manager without cookie manager");
+	if url.is_empty() {
+		all_cookies(&manager, on_complete);
+	} else {
+		manager.cookies(url, Option::<&Cancellable>::None, move |r| {
+			on_complete(r.unwrap_or_default())
+		});
+	}
+}
+
+extern "C" {
+	// The bindings don't have `get_all_cookies` yet, which is available since
+	// WebKitGTK 2.42. This is why at least that version is required, see
+	// docs/GETTING-STARTED.md.
+	fn webkit_cookie_manager_get_all_cookies(
+		cookie_manager: *mut webkit2gtk::ffi::WebKitCookieManager,
+		cancellable: *mut gio::ffi::GCancellable, callback: gio::ffi::GAsyncReadyCallback,
+		user_data: glib::ffi::gpointer,
+	);
+	fn webkit_cookie_manager_get_all_cookies_finish(
+		cookie_manager: *mut webkit2gtk::ffi::WebKitCookieManager,
+		result: *mut gio::ffi::GAsyncResult, error: *mut *mut glib::ffi::GError,
+	) -> *mut glib::ffi::GList;
+}
+
+/// Fetches the cookies of all domains and paths.
+fn all_cookies<F>(manager: &CookieManager, on_complete: F)
+where
+	F: FnOnce(Vec<soup::Cookie>) + 'static,
+{
+	unsafe extern "C" fn trampoline<F>(
+		source: *mut glib::gobject_ffi::GObject, result: *mut gio::ffi::GAsyncResult,
+		user_data: glib::ffi::gpointer,
+	) where
+		F: FnOnce(Vec<soup::Cookie>) + 'static,
+	{
+		let on_complete = Box::from_raw(user_data as *mut F);
+		let mut error = ptr::null_mut();
+		let cookies =
+			webkit_cookie_manager_get_all_cookies_finish(source as *mut _, result, &mut error);
+		if !error.is_null() {
+			glib::ffi::g_error_free(error);
+		}
+		// The list is null on error, which makes it empty.
+		on_complete(FromGlibPtrContainer::from_glib_full(cookies));
+	}
+
+	unsafe {
+		webkit_cookie_manager_get_all_cookies(
+			manager.to_glib_none().0,
+			ptr::null_mut(),
+			Some(trampoline::<F>),
+			Box::into_raw(Box::new(on_complete)) as _,
+		)
+	}
+}
@@ -1,10 +1,22 @@
-use std::{ffi::CStr, fmt};
+use std::{
+	ffi::{CStr, CString},
+	fmt,
+};
 
 use browser_window_c::*;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Error(cbw_Err);
 
+impl Error {
+	/// An error with the given message, for errors that the C code doesn't know
+	/// about.
+	pub(crate) fn new(message: &str) -> Self {
+		let message = CString::new(message).unwrap();
+		Self(unsafe { cbw_Err_new_with_msg(1, message.as_ptr()) })
+	}
+}
+
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		unsafe {
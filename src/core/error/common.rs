@@ -1,12 +1,27 @@
 use std::fmt;
 
-#[derive(Debug)]
-pub enum Error {}
+#[derive(Clone, Debug)]
+pub enum Error {
+	/// An error reported by GLib, like a page that failed to load.
+	Glib(gtk::glib::Error),
+}
 
 impl fmt::Display for Error {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "") }
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Glib(e) => write!(f, "{}", e),
+		}
+	}
 }
 
 impl std::error::Error for Error {
-	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> { None }
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Glib(e) => Some(e),
+		}
+	}
+}
+
+impl From<gtk::glib::Error> for Error {
+	fn from(e: gtk::glib::Error) -> Self { Self::Glib(e) }
 }
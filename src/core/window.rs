@@ -3,16 +3,47 @@ mod c;
 #[cfg(feature = "gtk")]
 mod gtk;
 
-use std::{ffi::c_void, ptr};
+use std::{any::Any, ffi::c_void, path::PathBuf, ptr};
 
 #[cfg(not(feature = "gtk"))]
-pub use c::WindowImpl;
+pub use c::{WindowImpl, file_dialog};
 #[cfg(feature = "gtk")]
-pub use gtk::WindowImpl;
+pub use gtk::{WindowImpl, file_dialog};
 
-use crate::prelude::*;
+use crate::{
+	prelude::*,
+	rc::{Rc, Weak},
+	window::*,
+};
 
-pub trait WindowExt: Clone {
+/// Receives the paths that have been chosen in a file dialog.
+pub type FileDialogCallbackFn = Box<dyn FnOnce(Vec<PathBuf>)>;
+
+pub trait WindowEventExt {
+	fn on_close_requested(&self, _handle: Weak<WindowOwner>) -> CloseRequestedEvent {
+		unimplemented!();
+	}
+	fn on_focus_changed(&self, _handle: Weak<WindowOwner>) -> FocusChangedEvent {
+		unimplemented!();
+	}
+	fn on_maximized_changed(&self, _handle: Weak<WindowOwner>) -> MaximizedChangedEvent {
+		unimplemented!();
+	}
+	fn on_minimized_changed(&self, _handle: Weak<WindowOwner>) -> MinimizedChangedEvent {
+		unimplemented!();
+	}
+	fn on_moved(&self, _handle: Weak<WindowOwner>) -> MovedEvent {
+		unimplemented!();
+	}
+	fn on_resized(&self, _handle: Weak<WindowOwner>) -> ResizedEvent {
+		unimplemented!();
+	}
+	fn on_visibility_changed(&self, _handle: Weak<WindowOwner>) -> VisibilityChangedEvent {
+		unimplemented!();
+	}
+}
+
+pub trait WindowExt: WindowEventExt + Clone {
 	fn app(&self) -> ApplicationImpl;
 
 	fn close(&self);
@@ -26,16 +57,28 @@ pub trait WindowExt: Clone {
 	/// known from the winapi. (See crate `winapi`.) For webkitgtk, the pointer
 	/// is a null pointer. You can use `WindowImpl::gtk_handle()`.
 	unsafe fn inner_handle(&self) -> *mut c_void { ptr::null_mut() }
+	fn is_fullscreen(&self) -> bool;
+	fn is_visible(&self) -> bool;
 	fn opacity(&self) -> u8;
 	fn position(&self) -> Pos2D;
 	fn title(&self) -> String;
+	/// The data that has been set with `set_user_data`, or null if there is
+	/// none.
+	fn user_data(&self) -> *mut ();
 	fn window_dimensions(&self) -> Dims2D;
 
+	fn focus(&self);
 	fn hide(&self);
+	fn maximize(&self);
+	fn minimize(&self);
+	fn restore(&self);
 
 	fn set_content_dimensions(&self, dimensions: Dims2D);
+	fn set_fullscreen(&self, fullscreen: bool);
+	fn set_keep_above(&self, keep_above: bool);
 	fn set_opacity(&self, opacity: u8);
 	fn set_position(&self, position: Pos2D);
+	fn set_resizable(&self, resizable: bool);
 	fn set_title(&self, title: &str);
 	fn set_user_data(&self, user_data: *mut ());
 	fn set_window_dimensions(&self, dimensions: Dims2D);
@@ -44,3 +87,18 @@ pub trait WindowExt: Clone {
 }
 
 pub type WindowOptions = cbw_WindowOptions;
+
+/// The data that is attached to a window. It keeps the owner of the window
+/// alive for as long as the window exists.
+pub(crate) struct WindowUserData {
+	pub(crate) _owner: Rc<dyn Any>,
+}
+
+impl WindowImpl {
+	pub(crate) fn free_user_data(user_data: *mut ()) {
+		let ptr = user_data as *mut WindowUserData;
+		unsafe {
+			let _ = Box::from_raw(ptr);
+		}
+	}
+}
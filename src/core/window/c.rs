@@ -3,18 +3,70 @@
 //! are basically wrapping the FFI provided by crate `browser-window-c`.
 
 use std::{
-	ffi::{c_char, c_void},
-	ptr,
+	ffi::{c_char, c_int, c_void},
+	path::PathBuf,
+	ptr, slice,
 };
 
-use super::{WindowExt, WindowOptions};
-use crate::{core::application::ApplicationImpl, prelude::*};
+use super::{FileDialogCallbackFn, WindowEventExt, WindowExt, WindowOptions};
+use crate::{
+	core::application::ApplicationImpl,
+	def_event, def_window_event,
+	dialog::{FileDialogMode, FileDialogOptions},
+	event::{EventHandlers, Subscription},
+	prelude::*,
+	rc::{Rc, Weak},
+	window::*,
+};
 
 #[derive(Clone)]
 pub struct WindowImpl {
 	pub(crate) inner: *mut cbw_Window,
 }
 
+struct EventData<C, A> {
+	owner: Weak<WindowOwner>,
+	// The handlers are owned by the `WindowOwner`, so that they get dropped
+	// together with it.
+	handlers: Weak<EventHandlers<WindowHandle, Window, A>>,
+	converter: unsafe fn(&C) -> A,
+}
+
+macro_rules! def_window_event_c {
+	($name:ident<$carg_type:ty, $rarg_type:ty> => $converter:ident => $c_event_name:ident) => {
+		def_window_event_c!($name<$carg_type, $rarg_type> => $converter => $c_event_name, ffi_window_event_callback::<$carg_type, $rarg_type>);
+	};
+	($name:ident<$carg_type:ty, $rarg_type:ty> => $converter:ident => $c_event_name:ident, $callback:expr) => {
+		def_window_event!($name<$rarg_type>(&mut self, handler) {
+			let upgraded = match self.owner.upgrade() {
+				Some(u) => u,
+				None => return Subscription::none(),
+			};
+			let c_ptr = unsafe { &mut *{ upgraded.0.0.inner } };
+
+			// The event data with the list of handlers is created for the first handler
+			if c_ptr.events.$c_event_name.callback.is_none() {
+				let event_data = EventData::<$carg_type, $rarg_type> {
+					owner: self.owner.clone(),
+					handlers: Rc::downgrade(&upgraded.new_event_handlers()),
+					converter: $converter,
+				};
+				let event_data_ptr = Box::into_raw(Box::new(event_data));
+				c_ptr.events.$c_event_name = cbw_Event {
+					callback: Some($callback),
+					data: event_data_ptr as _
+				};
+			}
+
+			let event_data = unsafe { &*(c_ptr.events.$c_event_name.data as *const EventData<$carg_type, $rarg_type>) };
+			match event_data.handlers.upgrade() {
+				Some(handlers) => EventHandlers::add(&handlers, handler),
+				None => Subscription::none(),
+			}
+		});
+	};
+}
+
 impl WindowImpl {
 	pub fn new(
 		app: ApplicationImpl, parent: Self, title: &str, width: Option<u32>, height: Option<u32>,
@@ -46,6 +98,32 @@ impl Default for WindowImpl {
 	}
 }
 
+impl WindowEventExt for WindowImpl {
+	fn on_close_requested(&self, handle: Weak<WindowOwner>) -> CloseRequestedEvent {
+		CloseRequestedEvent::new(handle)
+	}
+
+	fn on_focus_changed(&self, handle: Weak<WindowOwner>) -> FocusChangedEvent {
+		FocusChangedEvent::new(handle)
+	}
+
+	fn on_maximized_changed(&self, handle: Weak<WindowOwner>) -> MaximizedChangedEvent {
+		MaximizedChangedEvent::new(handle)
+	}
+
+	fn on_minimized_changed(&self, handle: Weak<WindowOwner>) -> MinimizedChangedEvent {
+		MinimizedChangedEvent::new(handle)
+	}
+
+	fn on_moved(&self, handle: Weak<WindowOwner>) -> MovedEvent { MovedEvent::new(handle) }
+
+	fn on_resized(&self, handle: Weak<WindowOwner>) -> ResizedEvent { ResizedEvent::new(handle) }
+
+	fn on_visibility_changed(&self, handle: Weak<WindowOwner>) -> VisibilityChangedEvent {
+		VisibilityChangedEvent::new(handle)
+	}
+}
+
 impl WindowExt for WindowImpl {
 	fn app(&self) -> ApplicationImpl {
 		ApplicationImpl {
@@ -63,6 +141,10 @@ impl WindowExt for WindowImpl {
 
 	unsafe fn inner_handle(&self) -> *mut c_void { cbw_Window_innerHandle(self.inner) }
 
+	fn is_fullscreen(&self) -> bool { unsafe { cbw_Window_isFullscreen(self.inner) } }
+
+	fn is_visible(&self) -> bool { unsafe { cbw_Window_isVisible(self.inner) } }
+
 	fn opacity(&self) -> u8 { unsafe { cbw_Window_getOpacity(self.inner) } }
 
 	fn position(&self) -> Pos2D { unsafe { cbw_Window_getPosition(self.inner) } }
@@ -83,20 +165,42 @@ impl WindowExt for WindowImpl {
 		slice.into()
 	}
 
+	fn user_data(&self) -> *mut () { unsafe { (*self.inner).user_data as _ } }
+
 	fn window_dimensions(&self) -> Dims2D { unsafe { cbw_Window_getWindowDimensions(self.inner) } }
 
+	fn focus(&self) { unsafe { cbw_Window_focus(self.inner) } }
+
 	fn hide(&self) { unsafe { cbw_Window_hide(self.inner) } }
 
+	fn maximize(&self) { unsafe { cbw_Window_maximize(self.inner) } }
+
+	fn minimize(&self) { unsafe { cbw_Window_minimize(self.inner) } }
+
+	fn restore(&self) { unsafe { cbw_Window_restore(self.inner) } }
+
 	fn set_content_dimensions(&self, dimensions: Dims2D) {
 		unsafe { cbw_Window_setContentDimensions(self.inner, dimensions) }
 	}
 
+	fn set_fullscreen(&self, fullscreen: bool) {
+		unsafe { cbw_Window_setFullscreen(self.inner, fullscreen) }
+	}
+
+	fn set_keep_above(&self, keep_above: bool) {
+		unsafe { cbw_Window_setKeepAbove(self.inner, keep_above) }
+	}
+
 	fn set_opacity(&self, opacity: u8) { unsafe { cbw_Window_setOpacity(self.inner, opacity) } }
 
 	fn set_position(&self, position: Pos2D) {
 		unsafe { cbw_Window_setPosition(self.inner, position) }
 	}
 
+	fn set_resizable(&self, resizable: bool) {
+		unsafe { cbw_Window_setResizable(self.inner, resizable) }
+	}
+
 	fn set_title(&self, title: &str) {
 		let slice: cbw_CStrSlice = title.into();
 		unsafe { cbw_Window_setTitle(self.inner, slice) };
@@ -114,3 +218,122 @@ impl WindowExt for WindowImpl {
 
 	fn show(&self) { unsafe { cbw_Window_show(self.inner) } }
 }
+
+/// Shows a file dialog, and calls `callback` with the chosen paths once it
+/// has been closed.
+/// Returns false if the dialog can't be shown, in which case `callback` is
+/// never called.
+pub fn file_dialog(
+	parent: Option<&WindowImpl>, mode: FileDialogMode, options: &FileDialogOptions,
+	callback: FileDialogCallbackFn,
+) -> bool {
+	let default_path = options
+		.default_path
+		.as_ref()
+		.map(|p| p.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	// The pattern slices need to stay alive while the filters point to them
+	let patterns: Vec<Vec<cbw_CStrSlice>> = options
+		.filters
+		.iter()
+		.map(|f| f.patterns.iter().map(|p| p.as_str().into()).collect())
+		.collect();
+	let filters: Vec<cbw_FileFilter> = options
+		.filters
+		.iter()
+		.zip(patterns.iter())
+		.map(|(f, p)| cbw_FileFilter {
+			name: f.name.as_str().into(),
+			patterns: p.as_ptr(),
+			pattern_count: p.len(),
+		})
+		.collect();
+	let c_options = cbw_FileDialogOptions {
+		mode: match mode {
+			FileDialogMode::Open => cBW_FILE_DIALOG_OPEN,
+			FileDialogMode::OpenMultiple => cBW_FILE_DIALOG_OPEN_MULTIPLE,
+			FileDialogMode::OpenFolder => cBW_FILE_DIALOG_OPEN_FOLDER,
+			FileDialogMode::Save => cBW_FILE_DIALOG_SAVE,
+		},
+		title: options.title.as_str().into(),
+		default_path: default_path.as_str().into(),
+		filters: filters.as_ptr(),
+		filter_count: filters.len(),
+	};
+
+	let data = Box::into_raw(Box::new(callback));
+	let shown = unsafe {
+		cbw_Window_fileDialog(
+			parent.map(|p| p.inner).unwrap_or(ptr::null_mut()),
+			&c_options,
+			Some(ffi_file_dialog_callback),
+			data as _,
+		) != 0
+	};
+	if !shown {
+		let _ = unsafe { Box::from_raw(data) };
+	}
+	shown
+}
+
+def_window_event_c!(CloseRequestedEvent<(), CloseRequestedEventArgs> => close_requested_converter => on_close_requested, ffi_close_requested_callback);
+def_window_event_c!(FocusChangedEvent<c_int, bool> => bool_converter => on_focus_changed);
+def_window_event_c!(MaximizedChangedEvent<c_int, bool> => bool_converter => on_maximized_changed);
+def_window_event_c!(MinimizedChangedEvent<c_int, bool> => bool_converter => on_minimized_changed);
+def_window_event_c!(MovedEvent<cbw_Pos2D, Pos2D> => copy_converter => on_moved);
+def_window_event_c!(ResizedEvent<cbw_Dims2D, Dims2D> => copy_converter => on_resized);
+def_window_event_c!(VisibilityChangedEvent<c_int, bool> => bool_converter => on_visibility_changed);
+
+unsafe extern "C" fn ffi_window_event_callback<C, A>(
+	handler_data: *mut c_void, arg_ptr: *mut c_void,
+) -> i32
+where
+	A: Clone,
+{
+	let event_data = &*(handler_data as *const EventData<C, A>);
+	let rarg = (event_data.converter)(&*(arg_ptr as *const C));
+
+	if let Some(handlers) = event_data.handlers.upgrade() {
+		WindowOwner::fire_event(&event_data.owner, &handlers, rarg);
+	}
+	0
+}
+
+/// Returns 1 if one of the handlers has prevented the window from closing.
+unsafe extern "C" fn ffi_close_requested_callback(
+	handler_data: *mut c_void, _arg_ptr: *mut c_void,
+) -> i32 {
+	let event_data = &*(handler_data as *const EventData<(), CloseRequestedEventArgs>);
+	let rarg = (event_data.converter)(&());
+
+	if let Some(handlers) = event_data.handlers.upgrade() {
+		WindowOwner::fire_event(&event_data.owner, &handlers, rarg.clone());
+	}
+	rarg.is_prevented() as _
+}
+
+unsafe extern "C" fn ffi_file_dialog_callback(
+	data: *mut c_void, paths: *const cbw_CStrSlice, path_count: usize,
+) {
+	let callback = Box::from_raw(data as *mut FileDialogCallbackFn);
+	let paths = if path_count > 0 {
+		slice::from_raw_parts(paths, path_count)
+			.iter()
+			.map(|p| {
+				let path: String = (*p).into();
+				PathBuf::from(path)
+			})
+			.collect()
+	} else {
+		Vec::new()
+	};
+	callback(paths);
+}
+
+unsafe fn bool_converter(input: &c_int) -> bool { *input > 0 }
+
+unsafe fn close_requested_converter(_input: &()) -> CloseRequestedEventArgs {
+	CloseRequestedEventArgs::new()
+}
+
+unsafe fn copy_converter<T: Copy>(input: &T) -> T { *input }
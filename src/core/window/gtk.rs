@@ -1,14 +1,77 @@
-use std::sync::{Arc, atomic::AtomicI32};
+use std::cell::{Cell, RefCell};
 
-use glib::object::ObjectExt;
-use gtk::prelude::{GtkWindowExt, WidgetExt};
+use glib::{Propagation, object::ObjectExt};
+use gtk::{
+	FileChooserAction, FileChooserNative, ResponseType, WindowPosition,
+	gdk::{Geometry, Gravity, WindowHints, WindowState},
+	prelude::{ContainerExt, FileChooserExt, GtkWindowExt, NativeDialogExt, WidgetExt},
+};
 
-use super::{WindowExt, WindowOptions};
-use crate::{core::application::ApplicationImpl, prelude::*};
+use super::{FileDialogCallbackFn, WindowEventExt, WindowExt, WindowOptions};
+use crate::{
+	core::application::ApplicationImpl,
+	def_event, def_event_gtk, def_window_event,
+	dialog::{FileDialogMode, FileDialogOptions},
+	event::Subscription,
+	prelude::*,
+	rc::{Rc, Weak},
+	window::*,
+};
 
 #[derive(Clone)]
 pub struct WindowImpl(pub gtk::Window);
 
+/// Defines an event that connects to the signals of a GTK object once its
+/// first handler gets registered, for both windows and browser windows.
+/// The connector function is given the object and a function that fires the
+/// event. The object is taken from the owner of the event by `$object`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_event_gtk {
+	($def_event:ident, $owner_type:ident, $name:ident<$arg_type:ty> => $connector:ident, |$owner:ident| $object:expr) => {
+		$def_event!($name<$arg_type>(&mut self, handler) {
+			let $owner = match self.owner.upgrade() {
+				Some(o) => o,
+				None => return Subscription::none(),
+			};
+			let object = $object;
+			let key = concat!("bw-event:", stringify!($name));
+
+			// The signals are only connected for the first handler. The handlers are owned
+			// by the owner of the event, so that they get dropped together with it.
+			let existing = unsafe {
+				object.data::<$crate::rc::Weak<$crate::event::EventHandlers<_, _, $arg_type>>>(key)
+			}
+			.map(|h| unsafe { h.as_ref() }.clone());
+			let handlers = match existing {
+				Some(h) => h,
+				None => {
+					let handlers = $crate::rc::Rc::downgrade(&$owner.new_event_handlers());
+					let (o, h) = (self.owner.clone(), handlers.clone());
+					$connector(object, move |arg| {
+						if let Some(handlers) = h.upgrade() {
+							$owner_type::fire_event(&o, &handlers, arg);
+						}
+					});
+					unsafe { object.set_data(key, handlers.clone()) };
+					handlers
+				}
+			};
+
+			match handlers.upgrade() {
+				Some(handlers) => $crate::event::EventHandlers::add(&handlers, handler),
+				None => Subscription::none(),
+			}
+		});
+	};
+}
+
+macro_rules! def_window_event_gtk {
+	($name:ident<$arg_type:ty> => $connector:ident) => {
+		def_event_gtk!(def_window_event, WindowOwner, $name<$arg_type> => $connector, |owner| &owner.0.0.0);
+	};
+}
+
 impl WindowImpl {
 	pub fn new(
 		app: ApplicationImpl, parent: Self, title: &str, width: Option<u32>, height: Option<u32>,
@@ -26,48 +89,160 @@ impl WindowImpl {
 			.resizable(options.resizable);
 
 		if let Some(w) = width {
-			builder = builder.width_request(w as _);
+			builder = builder.default_width(w as _);
 		}
 		if let Some(h) = height {
-			builder = builder.height_request(h as _);
+			builder = builder.default_height(h as _);
+		}
+		if options.center {
+			// GTK only centers the window on the window that it is transient for.
+			builder = if parent.0.is_visible() {
+				builder
+					.transient_for(&parent.0)
+					.window_position(WindowPosition::CenterOnParent)
+			} else {
+				builder.window_position(WindowPosition::Center)
+			};
 		}
 
 		let inner = builder.build();
 		inner.set_keep_above(options.keep_above);
+		if !options.center && options.has_position {
+			inner.move_(options.x, options.y);
+		}
+		Self::set_size_constraints(&inner, options);
+
+		if options.maximized {
+			inner.maximize();
+		}
+		if options.fullscreen {
+			inner.fullscreen();
+		}
 
 		// Delete user data when closing the window
 		inner.connect_destroy(|this| {
-			let user_data = unsafe { *this.data::<*mut ()>("bw-data").unwrap().as_ref() };
-			BrowserWindowImpl::free_user_data(user_data);
+			if let Some(user_data) = unsafe { this.steal_data::<*mut ()>("bw-data") } {
+				WindowImpl::free_user_data(user_data);
+			}
 		});
 
-		Self(inner)
+		let this = Self(inner);
+		if options.visible {
+			this.show();
+		}
+		this
 	}
 
 	pub fn gtk_handle(&self) -> &gtk::Window { &self.0 }
+
+	/// Sets the minimum and maximum size of the window. A dimension of 0 means
+	/// that it isn't constrained.
+	fn set_size_constraints(window: &gtk::Window, options: &WindowOptions) {
+		let mut hints = WindowHints::empty();
+		let limit = |value: u16, default: i32| if value == 0 { default } else { value as i32 };
+		let (min, max) = (options.min_size, options.max_size);
+		if min.width > 0 || min.height > 0 {
+			hints |= WindowHints::MIN_SIZE;
+		}
+		if max.width > 0 || max.height > 0 {
+			hints |= WindowHints::MAX_SIZE;
+		}
+		if hints.is_empty() {
+			return;
+		}
+
+		let geometry = Geometry::new(
+			limit(min.width, 0),
+			limit(min.height, 0),
+			limit(max.width, i32::MAX),
+			limit(max.height, i32::MAX),
+			0,
+			0,
+			0,
+			0,
+			0.0,
+			0.0,
+			Gravity::NorthWest,
+		);
+		window.set_geometry_hints(None::<&gtk::Widget>, Some(&geometry), hints);
+	}
+
+	/// The size that the decorations of the window manager add to the window.
+	/// It is only known once the window has been realized.
+	fn frame_size(&self) -> (i32, i32) {
+		match self.0.window() {
+			Some(window) => {
+				let frame = window.frame_extents();
+				let (w, h) = self.0.size();
+				((frame.width() - w).max(0), (frame.height() - h).max(0))
+			}
+			None => (0, 0),
+		}
+	}
 }
 
 impl Default for WindowImpl {
 	fn default() -> Self { Self(gtk::Window::new(gtk::WindowType::Toplevel)) }
 }
 
-impl WindowExt for WindowImpl {
-	fn app(&self) -> ApplicationImpl {
-		ApplicationImpl {
-			inner: self.0.application().unwrap(),
-			exit_code: Arc::new(AtomicI32::new(0)),
-		}
+impl WindowEventExt for WindowImpl {
+	fn on_close_requested(&self, handle: Weak<WindowOwner>) -> CloseRequestedEvent {
+		CloseRequestedEvent::new(handle)
+	}
+
+	fn on_focus_changed(&self, handle: Weak<WindowOwner>) -> FocusChangedEvent {
+		FocusChangedEvent::new(handle)
+	}
+
+	fn on_maximized_changed(&self, handle: Weak<WindowOwner>) -> MaximizedChangedEvent {
+		MaximizedChangedEvent::new(handle)
+	}
+
+	fn on_minimized_changed(&self, handle: Weak<WindowOwner>) -> MinimizedChangedEvent {
+		MinimizedChangedEvent::new(handle)
 	}
 
-	fn close(&self) { self.0.close(); }
+	fn on_moved(&self, handle: Weak<WindowOwner>) -> MovedEvent { MovedEvent::new(handle) }
+
+	fn on_resized(&self, handle: Weak<WindowOwner>) -> ResizedEvent { ResizedEvent::new(handle) }
+
+	fn on_visibility_changed(&self, handle: Weak<WindowOwner>) -> VisibilityChangedEvent {
+		VisibilityChangedEvent::new(handle)
+	}
+}
+
+impl WindowExt for WindowImpl {
+	fn app(&self) -> ApplicationImpl { ApplicationImpl::from_gtk(self.0.application().unwrap()) }
+
+	fn close(&self) {
+		// Closing the window still goes through the delete event, where
+		// `on_close_requested` shouldn't be able to stop it.
+		unsafe { self.0.set_data(CLOSING_KEY, true) };
+		self.0.close();
+	}
 
 	fn free(&self) {}
 
 	fn content_dimensions(&self) -> Dims2D {
-		unimplemented!();
+		let (w, h) = self.0.size();
+		let border = 2 * self.0.border_width() as i32;
+		Dims2D {
+			width: (w - border).max(0) as _,
+			height: (h - border).max(0) as _,
+		}
 	}
 
-	fn opacity(&self) -> u8 { 0 }
+	fn is_fullscreen(&self) -> bool {
+		// GTK 3 only keeps track of this in the window state of the GDK window.
+		self.0
+			.window()
+			.map(|w| w.state().contains(WindowState::FULLSCREEN))
+			.unwrap_or(false)
+	}
+
+	fn is_visible(&self) -> bool { self.0.is_visible() }
+
+	fn opacity(&self) -> u8 { (WidgetExt::opacity(&self.0) * 255.0).round() as _ }
 
 	fn position(&self) -> Pos2D {
 		let (x, y) = self.0.position();
@@ -84,24 +259,60 @@ impl WindowExt for WindowImpl {
 			.unwrap_or(String::new())
 	}
 
+	fn user_data(&self) -> *mut () {
+		unsafe { self.0.data::<*mut ()>("bw-data") }
+			.map(|p| unsafe { *p.as_ref() })
+			.unwrap_or(std::ptr::null_mut())
+	}
+
 	fn window_dimensions(&self) -> Dims2D {
 		let (w, h) = self.0.size();
+		let (frame_w, frame_h) = self.frame_size();
+		let (w, h) = (w + frame_w, h + frame_h);
 		Dims2D {
 			width: w as _,
 			height: h as _,
 		}
 	}
 
+	fn focus(&self) { self.0.present(); }
+
 	fn hide(&self) { self.0.hide(); }
 
-	fn set_content_dimensions(&self, _dimensions: Dims2D) {}
+	fn maximize(&self) { self.0.maximize(); }
+
+	fn minimize(&self) { self.0.iconify(); }
 
-	fn set_opacity(&self, _opacity: u8) {}
+	fn restore(&self) {
+		self.0.deiconify();
+		self.0.unmaximize();
+	}
+
+	fn set_content_dimensions(&self, dimensions: Dims2D) {
+		let border = 2 * self.0.border_width() as i32;
+		self.0.resize(
+			dimensions.width as i32 + border,
+			dimensions.height as i32 + border,
+		);
+	}
 
-	fn set_position(&self, _position: Pos2D) {
-		unimplemented!();
+	fn set_fullscreen(&self, fullscreen: bool) {
+		if fullscreen {
+			self.0.fullscreen();
+		} else {
+			self.0.unfullscreen();
+		}
 	}
 
+	fn set_keep_above(&self, keep_above: bool) { self.0.set_keep_above(keep_above); }
+
+	/// Only has an effect when a compositing window manager is used.
+	fn set_opacity(&self, opacity: u8) { WidgetExt::set_opacity(&self.0, opacity as f64 / 255.0); }
+
+	fn set_position(&self, position: Pos2D) { self.0.move_(position.x as _, position.y as _); }
+
+	fn set_resizable(&self, resizable: bool) { self.0.set_resizable(resizable); }
+
 	fn set_title(&self, title: &str) { self.0.set_title(title); }
 
 	fn set_user_data(&self, user_data: *mut ()) {
@@ -111,9 +322,175 @@ impl WindowExt for WindowImpl {
 	}
 
 	fn set_window_dimensions(&self, dimensions: Dims2D) {
-		self.0
-			.set_size_request(dimensions.width as _, dimensions.height as _);
+		let (frame_w, frame_h) = self.frame_size();
+		self.0.resize(
+			(dimensions.width as i32 - frame_w).max(1),
+			(dimensions.height as i32 - frame_h).max(1),
+		);
 	}
 
 	fn show(&self) { self.0.show_all(); }
 }
+
+def_window_event_gtk!(CloseRequestedEvent<CloseRequestedEventArgs> => connect_close_requested);
+def_window_event_gtk!(FocusChangedEvent<bool> => connect_focus_changed);
+def_window_event_gtk!(MaximizedChangedEvent<bool> => connect_maximized_changed);
+def_window_event_gtk!(MinimizedChangedEvent<bool> => connect_minimized_changed);
+def_window_event_gtk!(MovedEvent<Pos2D> => connect_moved);
+def_window_event_gtk!(ResizedEvent<Dims2D> => connect_resized);
+def_window_event_gtk!(VisibilityChangedEvent<bool> => connect_visibility_changed);
+
+/// Set on windows that are being closed by `close`, rather than by the user.
+const CLOSING_KEY: &str = "bw-closing";
+
+fn connect_close_requested(window: &gtk::Window, fire: impl Fn(CloseRequestedEventArgs) + 'static) {
+	window.connect_delete_event(move |window, _| {
+		// Only the user gets to be asked whether the window can close.
+		if unsafe { window.data::<bool>(CLOSING_KEY) }.is_some() {
+			return Propagation::Proceed;
+		}
+		let args = CloseRequestedEventArgs::new();
+		fire(args.clone());
+		if args.is_prevented() {
+			Propagation::Stop
+		} else {
+			Propagation::Proceed
+		}
+	});
+}
+
+fn connect_focus_changed(window: &gtk::Window, fire: impl Fn(bool) + 'static) {
+	let fire = Rc::new(fire);
+	let f = fire.clone();
+	window.connect_focus_in_event(move |_, _| {
+		f(true);
+		Propagation::Proceed
+	});
+	window.connect_focus_out_event(move |_, _| {
+		fire(false);
+		Propagation::Proceed
+	});
+}
+
+fn connect_maximized_changed(window: &gtk::Window, fire: impl Fn(bool) + 'static) {
+	connect_window_state_changed(window, WindowState::MAXIMIZED, fire);
+}
+
+fn connect_minimized_changed(window: &gtk::Window, fire: impl Fn(bool) + 'static) {
+	connect_window_state_changed(window, WindowState::ICONIFIED, fire);
+}
+
+/// Fires whenever the given state gets set or unset.
+fn connect_window_state_changed(
+	window: &gtk::Window, state: WindowState, fire: impl Fn(bool) + 'static,
+) {
+	window.connect_window_state_event(move |_, e| {
+		if e.changed_mask().contains(state) {
+			fire(e.new_window_state().contains(state));
+		}
+		Propagation::Proceed
+	});
+}
+
+fn connect_moved(window: &gtk::Window, fire: impl Fn(Pos2D) + 'static) {
+	// The configure event is also emitted for changes other than the position.
+	let last = Cell::new(window.position());
+	window.connect_configure_event(move |w, _| {
+		let (x, y) = w.position();
+		if last.replace((x, y)) != (x, y) {
+			fire(Pos2D {
+				x: x as _,
+				y: y as _,
+			});
+		}
+		false
+	});
+}
+
+fn connect_resized(window: &gtk::Window, fire: impl Fn(Dims2D) + 'static) {
+	// The configure event is also emitted for changes other than the size.
+	let last = Cell::new(window.size());
+	window.connect_configure_event(move |w, _| {
+		let (width, height) = w.size();
+		if last.replace((width, height)) != (width, height) {
+			fire(Dims2D {
+				width: width as _,
+				height: height as _,
+			});
+		}
+		false
+	});
+}
+
+fn connect_visibility_changed(window: &gtk::Window, fire: impl Fn(bool) + 'static) {
+	let fire = Rc::new(fire);
+	let f = fire.clone();
+	window.connect_show(move |_| f(true));
+	window.connect_hide(move |_| fire(false));
+}
+
+/// Shows a `FileChooserNative`, on top of `parent` if given.
+/// Always returns true, because GTK can always show one.
+pub fn file_dialog(
+	parent: Option<&WindowImpl>, mode: FileDialogMode, options: &FileDialogOptions,
+	callback: FileDialogCallbackFn,
+) -> bool {
+	let action = match mode {
+		FileDialogMode::Open | FileDialogMode::OpenMultiple => FileChooserAction::Open,
+		FileDialogMode::OpenFolder => FileChooserAction::SelectFolder,
+		FileDialogMode::Save => FileChooserAction::Save,
+	};
+	let title = if options.title.is_empty() {
+		None
+	} else {
+		Some(options.title.as_str())
+	};
+	let dialog = FileChooserNative::new(title, parent.map(|p| &p.0), action, None, None);
+	dialog.set_select_multiple(mode == FileDialogMode::OpenMultiple);
+	dialog.set_do_overwrite_confirmation(true);
+
+	if let Some(path) = &options.default_path {
+		if path.is_dir() {
+			dialog.set_current_folder(path);
+		} else if mode == FileDialogMode::Save {
+			if let Some(folder) = path.parent().filter(|f| !f.as_os_str().is_empty()) {
+				dialog.set_current_folder(folder);
+			}
+			if let Some(name) = path.file_name() {
+				dialog.set_current_name(&name.to_string_lossy());
+			}
+		} else {
+			dialog.set_filename(path);
+		}
+	}
+
+	for filter in &options.filters {
+		let f = gtk::FileFilter::new();
+		f.set_name(Some(&filter.name));
+		for pattern in &filter.patterns {
+			if pattern.contains('/') {
+				f.add_mime_type(pattern);
+			} else {
+				f.add_pattern(pattern);
+			}
+		}
+		dialog.add_filter(f);
+	}
+
+	// The dialog needs to be kept alive until it has been answered.
+	let callback = Cell::new(Some(callback));
+	let kept_alive = RefCell::new(Some(dialog.clone()));
+	dialog.connect_response(move |d, response| {
+		let paths = if response == ResponseType::Accept {
+			d.filenames()
+		} else {
+			Vec::new()
+		};
+		if let Some(c) = callback.take() {
+			c(paths);
+		}
+		kept_alive.borrow_mut().take();
+	});
+	dialog.show();
+	true
+}
@@ -0,0 +1,110 @@
+//! This module contains the types that describe native file dialogs.
+//!
+//! File dialogs can be shown from Rust with the `open_file`, `save_file` and
+//! `pick_folder` methods of
+//! [`WindowHandle`](crate::window::WindowHandle) and
+//! [`ApplicationHandle`](crate::application::ApplicationHandle).
+//! The dialogs of `<input type="file">` elements can be answered from Rust as
+//! well, by
+//! [`on_file_dialog`](crate::browser::BrowserWindow::on_file_dialog).
+//!
+//! ```no_run
+//! use browser_window::{dialog::*, window::WindowHandle};
+//!
+//! async fn open_images(
+//! 	window: &WindowHandle,
+//! ) -> Result<Vec<std::path::PathBuf>, FileDialogError> {
+//! 	let mut options = FileDialogOptions::default();
+//! 	options.title = "Open Images".to_owned();
+//! 	options
+//! 		.filters
+//! 		.push(FileFilter::new("Images", &["*.png", "*.jpg"]));
+//! 	options.multiple = true;
+//! 	window.open_file(&options).await
+//! }
+//! ```
+
+use std::{error::Error as StdError, fmt, path::PathBuf};
+
+use futures_channel::oneshot;
+
+use crate::core::window::{WindowImpl, file_dialog};
+
+/// The kind of file dialog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileDialogMode {
+	Open,
+	OpenMultiple,
+	OpenFolder,
+	Save,
+}
+
+/// The options of a file dialog.
+#[derive(Clone, Debug, Default)]
+pub struct FileDialogOptions {
+	pub title: String,
+	/// The file or folder that the dialog starts out with. For save dialogs,
+	/// its file name is suggested.
+	pub default_path: Option<PathBuf>,
+	/// The types of files that can be chosen. If empty, all files can be
+	/// chosen.
+	pub filters: Vec<FileFilter>,
+	/// Whether or not multiple files can be opened at once.
+	pub multiple: bool,
+}
+
+/// The reason why a file dialog couldn't be shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileDialogError {
+	/// The browser framework can't show the dialog. CEF can only show dialogs
+	/// while there is a browser window, and the dialogs aren't implemented for
+	/// Edge WebView2 yet.
+	Unsupported,
+}
+
+/// A type of files that can be chosen in a file dialog.
+#[derive(Clone, Debug)]
+pub struct FileFilter {
+	pub name: String,
+	/// Glob patterns like `*.png`, or MIME types like `image/png`.
+	pub patterns: Vec<String>,
+}
+
+impl FileFilter {
+	pub fn new(name: &str, patterns: &[&str]) -> Self {
+		Self {
+			name: name.to_owned(),
+			patterns: patterns.iter().map(|p| (*p).to_owned()).collect(),
+		}
+	}
+}
+
+/// Shows a file dialog, on top of `parent` if given, and resolves to the chosen
+/// paths. Cancelling the dialog chooses none.
+pub(crate) async fn show(
+	parent: Option<&WindowImpl>, mode: FileDialogMode, options: &FileDialogOptions,
+) -> Result<Vec<PathBuf>, FileDialogError> {
+	let (tx, rx) = oneshot::channel();
+	let shown = file_dialog(
+		parent,
+		mode,
+		options,
+		Box::new(move |paths| {
+			let _ = tx.send(paths);
+		}),
+	);
+	if !shown {
+		return Err(FileDialogError::Unsupported);
+	}
+	Ok(rx.await.unwrap_or_default())
+}
+
+impl fmt::Display for FileDialogError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unsupported => write!(f, "file dialog not supported"),
+		}
+	}
+}
+
+impl StdError for FileDialogError {}
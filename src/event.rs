@@ -44,8 +44,63 @@
 //! The other browser frameworks only support a subset of what is supported for
 //! CEF. The reason for this is that CEF is simply the most cross-platform
 //! framework out there, so it gets the most care.
+//!
+//! A [`Window`](../window/struct.Window.html) has events of its own, like
+//! `on_close_requested`. They work the same way, and are available on a
+//! `BrowserWindow` as well.
+//!
+//! Any number of handlers can be registered for the same event. They are
+//! invoked in the order in which they have been registered. Registering
+//! returns a [`Subscription`](struct.Subscription.html), which can be used to
+//! remove the handler again:
+//!
+//! ```
+//! use browser_window::{browser::*, prelude::*};
+//!
+//! fn example(bw: BrowserWindow) {
+//! 	let subscription = bw.on_page_title_changed().register(|_, title| {
+//! 		println!("title: {}", title);
+//! 	});
+//!
+//! 	// ... later on ...
+//! 	subscription.unregister();
+//! }
+//! ```
+//!
+//! In async code, it may be more convenient to receive the events from a
+//! [`stream`](trait.EventExt.html#tymethod.stream) instead:
+//!
+//! ```ignore
+//! use browser_window::{browser::*, prelude::*};
+//! use futures_util::StreamExt;
+//!
+//! async fn stream_example(bw: BrowserWindow) {
+//! 	let mut messages = bw.on_message().stream();
+//! 	while let Some(msg) = messages.next().await {
+//! 		// .. code here ...
+//! 	}
+//! }
+//! ```
+
+mod stream;
 
-use std::{boxed::Box, future::Future, pin::Pin};
+use std::{
+	boxed::Box,
+	cell::RefCell,
+	collections::VecDeque,
+	future::Future,
+	pin::Pin,
+	sync::{Mutex, MutexGuard},
+};
+
+pub use self::stream::EventStream;
+use crate::{
+	application::ApplicationHandle,
+	rc::{Rc, Weak},
+};
+
+/// The number of arguments that `EventExt::stream` buffers.
+const DEFAULT_STREAM_CAPACITY: usize = 32;
 
 #[cfg(not(feature = "threadsafe"))]
 pub type EventHandlerAsyncCallback<O, A> =
@@ -63,25 +118,81 @@ pub enum EventHandler<H, O, A> {
 	Async(Box<EventHandlerAsyncCallback<O, A>>),
 }
 
+/// The handlers that have been registered for an event, in the order in which
+/// they have been registered.
+/// The list is locked, because a `Subscription` may remove its handler from
+/// another thread.
+pub(crate) struct EventHandlers<H, O, A>(Mutex<HandlerEntries<H, O, A>>);
+
+struct HandlerEntries<H, O, A> {
+	entries: Vec<(u64, SharedEventHandler<H, O, A>)>,
+	next_id: u64,
+}
+
+/// A handler, together with the arguments that it still has to be invoked
+/// with.
+pub(crate) struct HandlerCell<H, O, A> {
+	handler: RefCell<EventHandler<H, O, A>>,
+	/// The arguments of the events that the handler has caused to fire again
+	/// while it was running.
+	queue: RefCell<VecDeque<A>>,
+}
+
+pub(crate) type SharedEventHandler<H, O, A> = Rc<HandlerCell<H, O, A>>;
+pub(crate) type SharedEventHandlers<H, O, A> = Rc<EventHandlers<H, O, A>>;
+
+/// Identifies a handler that has been registered for an event.
+///
+/// Dropping a subscription leaves its handler registered. Call `unregister` to
+/// remove the handler, or use `guard` to have it removed once the guard is
+/// dropped.
+pub struct Subscription {
+	handlers: Option<Weak<DynHandlerList>>,
+	id: u64,
+}
+
+/// Unregisters the handler of its subscription when it is dropped.
+pub struct SubscriptionGuard(Subscription);
+
+/// Allows a subscription to remove its handler without knowing the handler's
+/// type.
+trait HandlerList {
+	fn remove(&self, id: u64) -> bool;
+}
+
+#[cfg(not(feature = "threadsafe"))]
+type DynHandlerList = dyn HandlerList;
+#[cfg(feature = "threadsafe")]
+type DynHandlerList = dyn HandlerList + Send + Sync;
+
+// Other threads only ever lock the list to remove a handler from it, and the
+// handlers themselves are `Send`. They are only invoked on the GUI thread.
+#[cfg(feature = "threadsafe")]
+unsafe impl<H, O, A> Send for EventHandlers<H, O, A> {}
+#[cfg(feature = "threadsafe")]
+unsafe impl<H, O, A> Sync for EventHandlers<H, O, A> {}
+
 /// An `Event` can be registered to with a regular closure or an 'async
 /// enclosure'. All events are implemented for CEF.
 /// If an event is not implemented for another browser framework, it will simply
 /// never be invoked. If an event _is_ supported by another browser framework,
 /// it should say so in its documentation.
 pub(crate) trait Event<H, O, A> {
-	fn register_handler(&mut self, handler: EventHandler<H, O, A>);
+	fn register_handler(&mut self, handler: EventHandler<H, O, A>) -> Subscription;
 }
 
 pub trait EventExt<H, O, A> {
 	/// Register a closure to be invoked for this event.
+	/// It is invoked after the handlers that have been registered before it.
 	#[cfg(not(feature = "threadsafe"))]
-	fn register<X>(&mut self, handler: X)
+	fn register<X>(&mut self, handler: X) -> Subscription
 	where
 		X: FnMut(&H, A) + 'static;
 
 	/// Register a closure to be invoked for this event.
+	/// It is invoked after the handlers that have been registered before it.
 	#[cfg(feature = "threadsafe")]
-	fn register<X>(&mut self, handler: X)
+	fn register<X>(&mut self, handler: X) -> Subscription
 	where
 		X: FnMut(&H, A) + Send + 'static;
 
@@ -94,7 +205,7 @@ pub trait EventExt<H, O, A> {
 	/// });
 	/// ```
 	#[cfg(not(feature = "threadsafe"))]
-	fn register_async<X, F>(&mut self, handler: X)
+	fn register_async<X, F>(&mut self, handler: X) -> Subscription
 	where
 		X: FnMut(O, A) -> F + 'static,
 		F: Future<Output = ()> + 'static;
@@ -108,10 +219,40 @@ pub trait EventExt<H, O, A> {
 	/// });
 	/// ```
 	#[cfg(feature = "threadsafe")]
-	fn register_async<X, F>(&mut self, handler: X)
+	fn register_async<X, F>(&mut self, handler: X) -> Subscription
 	where
 		X: FnMut(O, A) -> F + Send + 'static,
 		F: Future<Output = ()> + 'static;
+
+	/// Returns a stream that yields the arguments of this event.
+	/// Up to 32 arguments are buffered, see
+	/// [`EventStream`](struct.EventStream.html) for what happens when the
+	/// buffer is full.
+	#[cfg(not(feature = "threadsafe"))]
+	fn stream(&mut self) -> EventStream<A>
+	where
+		A: 'static;
+
+	/// Returns a stream that yields the arguments of this event.
+	/// Up to 32 arguments are buffered, see
+	/// [`EventStream`](struct.EventStream.html) for what happens when the
+	/// buffer is full.
+	#[cfg(feature = "threadsafe")]
+	fn stream(&mut self) -> EventStream<A>
+	where
+		A: Send + 'static;
+
+	/// Like `stream`, but buffers up to `capacity` arguments.
+	#[cfg(not(feature = "threadsafe"))]
+	fn stream_with_capacity(&mut self, capacity: usize) -> EventStream<A>
+	where
+		A: 'static;
+
+	/// Like `stream`, but buffers up to `capacity` arguments.
+	#[cfg(feature = "threadsafe")]
+	fn stream_with_capacity(&mut self, capacity: usize) -> EventStream<A>
+	where
+		A: Send + 'static;
 }
 
 impl<H, O, A, T> EventExt<H, O, A> for T
@@ -119,48 +260,251 @@ where
 	T: Event<H, O, A>,
 {
 	#[cfg(not(feature = "threadsafe"))]
-	fn register<X>(&mut self, mut handler: X)
+	fn register<X>(&mut self, mut handler: X) -> Subscription
 	where
 		X: FnMut(&H, A) + 'static,
 	{
 		self.register_handler(EventHandler::Sync(Box::new(move |h, args| {
 			handler(h, args);
-		})));
+		})))
 	}
 
 	#[cfg(feature = "threadsafe")]
-	fn register<X>(&mut self, mut handler: X)
+	fn register<X>(&mut self, mut handler: X) -> Subscription
 	where
 		X: FnMut(&H, A) + Send + 'static,
 	{
 		self.register_handler(EventHandler::Sync(Box::new(move |h, args| {
 			handler(h, args);
-		})));
+		})))
 	}
 
 	#[cfg(not(feature = "threadsafe"))]
-	fn register_async<X, F>(&mut self, mut handler: X)
+	fn register_async<X, F>(&mut self, mut handler: X) -> Subscription
 	where
 		X: FnMut(O, A) -> F + 'static,
 		F: Future<Output = ()> + 'static,
 	{
 		self.register_handler(EventHandler::Async(Box::new(move |h, args| {
 			Box::pin(handler(h, args))
-		})));
+		})))
 	}
 
 	#[cfg(feature = "threadsafe")]
-	fn register_async<X, F>(&mut self, mut handler: X)
+	fn register_async<X, F>(&mut self, mut handler: X) -> Subscription
 	where
 		X: FnMut(O, A) -> F + Send + 'static,
 		F: Future<Output = ()> + 'static,
 	{
 		self.register_handler(EventHandler::Async(Box::new(move |h, args| {
 			Box::pin(handler(h, args))
+		})))
+	}
+
+	#[cfg(not(feature = "threadsafe"))]
+	fn stream(&mut self) -> EventStream<A>
+	where
+		A: 'static,
+	{
+		self.stream_with_capacity(DEFAULT_STREAM_CAPACITY)
+	}
+
+	#[cfg(feature = "threadsafe")]
+	fn stream(&mut self) -> EventStream<A>
+	where
+		A: Send + 'static,
+	{
+		self.stream_with_capacity(DEFAULT_STREAM_CAPACITY)
+	}
+
+	#[cfg(not(feature = "threadsafe"))]
+	fn stream_with_capacity(&mut self, capacity: usize) -> EventStream<A>
+	where
+		A: 'static,
+	{
+		let (sender, mut stream) = stream::channel(capacity);
+		let subscription = self.register_handler(EventHandler::Sync(Box::new(move |_, args| {
+			sender.send(args);
+		})));
+		stream.set_subscription(subscription);
+		stream
+	}
+
+	#[cfg(feature = "threadsafe")]
+	fn stream_with_capacity(&mut self, capacity: usize) -> EventStream<A>
+	where
+		A: Send + 'static,
+	{
+		let (sender, mut stream) = stream::channel(capacity);
+		let subscription = self.register_handler(EventHandler::Sync(Box::new(move |_, args| {
+			sender.send(args);
 		})));
+		stream.set_subscription(subscription);
+		stream
+	}
+}
+
+impl<H, O, A> EventHandlers<H, O, A> {
+	pub(crate) fn new() -> Self {
+		Self(Mutex::new(HandlerEntries {
+			entries: Vec::new(),
+			next_id: 0,
+		}))
+	}
+
+	pub(crate) fn is_empty(&self) -> bool { self.lock().entries.is_empty() }
+
+	/// Whether the handler with the given ID is still registered.
+	pub(crate) fn contains(&self, id: u64) -> bool {
+		self.lock().entries.iter().any(|(i, _)| *i == id)
+	}
+
+	/// The handlers that are currently registered, in the order in which they
+	/// should be invoked.
+	/// Because the list itself doesn't stay locked, handlers are free to
+	/// (un)register handlers while they are being invoked.
+	pub(crate) fn snapshot(&self) -> Vec<(u64, SharedEventHandler<H, O, A>)> {
+		self.lock().entries.clone()
+	}
+
+	fn lock(&self) -> MutexGuard<'_, HandlerEntries<H, O, A>> {
+		// A handler never runs while the list is locked, so it can't be poisoned.
+		self.0.lock().unwrap()
+	}
+}
+
+impl<H, O, A> EventHandlers<H, O, A>
+where
+	A: Clone,
+{
+	/// Passes the argument on to every handler, in the order in which they have
+	/// been registered.
+	/// The first handler receives the argument itself, the others receive a
+	/// clone of it.
+	pub(crate) fn fire(&self, handle: &H, owner: &dyn Fn() -> O, app: &ApplicationHandle, arg: A) {
+		let entries = self.snapshot();
+		let spare = if entries.len() > 1 {
+			Some(arg.clone())
+		} else {
+			None
+		};
+
+		let mut arg = Some(arg);
+		for (id, handler) in entries {
+			// Skip the handlers that have been unregistered by the handlers before them.
+			if !self.contains(id) {
+				continue;
+			}
+			let a = match arg.take() {
+				Some(a) => a,
+				None => spare.clone().unwrap(),
+			};
+			handler.invoke(handle, owner, app, a);
+		}
+	}
+}
+
+impl<H, O, A> EventHandlers<H, O, A>
+where
+	H: 'static,
+	O: 'static,
+	A: 'static,
+{
+	/// Adds the handler to the end of the list.
+	pub(crate) fn add(
+		this: &SharedEventHandlers<H, O, A>, handler: EventHandler<H, O, A>,
+	) -> Subscription {
+		let id = {
+			let mut handlers = this.lock();
+			let id = handlers.next_id;
+			handlers.next_id += 1;
+			handlers.entries.push((id, HandlerCell::new(handler)));
+			id
+		};
+
+		let list: Rc<DynHandlerList> = this.clone();
+		Subscription {
+			handlers: Some(Rc::downgrade(&list)),
+			id,
+		}
+	}
+}
+
+impl<H, O, A> HandlerList for EventHandlers<H, O, A> {
+	fn remove(&self, id: u64) -> bool {
+		// The handler is dropped after the list has been unlocked.
+		let removed = {
+			let mut handlers = self.lock();
+			let index = handlers.entries.iter().position(|(i, _)| *i == id);
+			index.map(|i| handlers.entries.remove(i))
+		};
+		removed.is_some()
+	}
+}
+
+impl<H, O, A> HandlerCell<H, O, A> {
+	pub(crate) fn new(handler: EventHandler<H, O, A>) -> Rc<Self> {
+		Rc::new(Self {
+			handler: RefCell::new(handler),
+			queue: RefCell::new(VecDeque::new()),
+		})
+	}
+
+	/// Invokes the handler with the argument.
+	/// A handler that causes its own event to fire again, can't be invoked
+	/// again while it is still running. In that case, the argument is queued,
+	/// and the handler is invoked with it once it has returned.
+	pub(crate) fn invoke(
+		&self, handle: &H, owner: &dyn Fn() -> O, app: &ApplicationHandle, arg: A,
+	) {
+		self.queue.borrow_mut().push_back(arg);
+		let mut handler = match self.handler.try_borrow_mut() {
+			Ok(h) => h,
+			Err(_) => return,
+		};
+
+		loop {
+			let arg = match self.queue.borrow_mut().pop_front() {
+				Some(a) => a,
+				None => break,
+			};
+			match &mut *handler {
+				EventHandler::Sync(callback) => callback(handle, arg),
+				EventHandler::Async(callback) => app.spawn(callback(owner(), arg)),
+			}
+		}
+	}
+}
+
+impl Subscription {
+	/// The subscription of a handler that could not be registered, because the
+	/// browser window doesn't exist anymore.
+	pub(crate) fn none() -> Self {
+		Self {
+			handlers: None,
+			id: 0,
+		}
+	}
+
+	/// Returns a guard that unregisters the handler once it is dropped.
+	pub fn guard(self) -> SubscriptionGuard { SubscriptionGuard(self) }
+
+	/// Removes the handler from its event.
+	/// Returns `false` if it wasn't registered anymore.
+	pub fn unregister(self) -> bool { self.remove() }
+
+	fn remove(&self) -> bool {
+		match self.handlers.as_ref().and_then(|h| h.upgrade()) {
+			Some(handlers) => handlers.remove(self.id),
+			None => false,
+		}
 	}
 }
 
+impl Drop for SubscriptionGuard {
+	fn drop(&mut self) { self.0.remove(); }
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! decl_event {
@@ -185,12 +529,20 @@ macro_rules! decl_browser_event {
 	};
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! decl_window_event {
+	($name:ident) => {
+		decl_event!($name<WindowOwner>);
+	};
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! def_event {
 	( $name:ident<$handle_type:ty, $owner_type:ty, $arg_type:ty> (&mut $this:ident, $arg_name:ident) $body:block ) => {
 		impl crate::event::Event<$handle_type, $owner_type, $arg_type> for $name {
-			fn register_handler(&mut $this, $arg_name: crate::event::EventHandler<$handle_type, $owner_type, $arg_type>) $body
+			fn register_handler(&mut $this, $arg_name: crate::event::EventHandler<$handle_type, $owner_type, $arg_type>) -> crate::event::Subscription $body
 		}
 	}
 }
@@ -202,3 +554,11 @@ macro_rules! def_browser_event {
 		def_event!($name<BrowserWindowHandle, BrowserWindow, $arg_type> (&mut $this, $arg_name) $body);
 	}
 }
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_window_event {
+	( $name:ident<$arg_type:ty> (&mut $this:ident, $arg_name:ident) $body:block ) => {
+		def_event!($name<WindowHandle, Window, $arg_type> (&mut $this, $arg_name) $body);
+	}
+}
@@ -0,0 +1,144 @@
+use std::{
+	collections::VecDeque,
+	pin::Pin,
+	sync::{Arc, Mutex},
+	task::{Context, Poll, Waker},
+};
+
+use futures_core::Stream;
+
+use super::{Subscription, SubscriptionGuard};
+
+/// A stream of the arguments of an event, as returned by
+/// [`EventExt::stream`](trait.EventExt.html#tymethod.stream).
+///
+/// The arguments are buffered until the stream is polled for them.
+/// The buffer is bounded: once it is full, the oldest argument in it is
+/// dropped to make room for the new one. So if the stream isn't polled often
+/// enough, it only misses out on the oldest events, never on the latest ones.
+/// For a `MessageEvent`, dropping its arguments resolves the promise of
+/// `invoke_extern` with `undefined`.
+///
+/// The stream ends once the owner of the event (the `BrowserWindowOwner` or
+/// `WindowOwner`) is dropped, which happens after the window has been closed
+/// and no more handles to it exist.
+/// Dropping the stream unregisters its handler.
+/// With the `threadsafe` feature, the stream is `Send` whenever its arguments
+/// are, so it can be polled and dropped on any thread.
+pub struct EventStream<A> {
+	shared: Arc<Mutex<Shared<A>>>,
+	/// Unregisters the handler that feeds the stream once the stream is
+	/// dropped.
+	subscription: Option<SubscriptionGuard>,
+}
+
+/// Puts the arguments of the event into the buffer of its `EventStream`.
+/// It lives inside the event handler, so it gets dropped together with the
+/// owner of the event, which ends the stream.
+pub(super) struct EventSender<A> {
+	shared: Arc<Mutex<Shared<A>>>,
+}
+
+struct Shared<A> {
+	buffer: VecDeque<A>,
+	capacity: usize,
+	/// Whether the sender has been dropped.
+	ended: bool,
+	/// Whether the stream has been dropped.
+	abandoned: bool,
+	waker: Option<Waker>,
+}
+
+/// Creates a stream that can buffer `capacity` arguments, and the sender for
+/// it.
+pub(super) fn channel<A>(capacity: usize) -> (EventSender<A>, EventStream<A>) {
+	let shared = Arc::new(Mutex::new(Shared {
+		buffer: VecDeque::new(),
+		capacity: capacity.max(1),
+		ended: false,
+		abandoned: false,
+		waker: None,
+	}));
+	(
+		EventSender {
+			shared: shared.clone(),
+		},
+		EventStream {
+			shared,
+			subscription: None,
+		},
+	)
+}
+
+impl<A> EventStream<A> {
+	/// Ties the subscription of the stream's handler to the stream.
+	pub(super) fn set_subscription(&mut self, subscription: Subscription) {
+		self.subscription = Some(subscription.guard());
+	}
+}
+
+impl<A> EventSender<A> {
+	pub fn send(&self, arg: A) {
+		let (dropped, waker) = {
+			let mut shared = self.shared.lock().unwrap();
+			if shared.abandoned {
+				return;
+			}
+
+			let dropped = if shared.buffer.len() >= shared.capacity {
+				shared.buffer.pop_front()
+			} else {
+				None
+			};
+			shared.buffer.push_back(arg);
+			(dropped, shared.waker.take())
+		};
+
+		// The dropped argument and the waker may run code of their own, so the lock
+		// is released first.
+		drop(dropped);
+		if let Some(waker) = waker {
+			waker.wake();
+		}
+	}
+}
+
+impl<A> Drop for EventSender<A> {
+	fn drop(&mut self) {
+		let waker = {
+			let mut shared = self.shared.lock().unwrap();
+			shared.ended = true;
+			shared.waker.take()
+		};
+		if let Some(waker) = waker {
+			waker.wake();
+		}
+	}
+}
+
+impl<A> Stream for EventStream<A> {
+	type Item = A;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<A>> {
+		let mut shared = self.shared.lock().unwrap();
+		if let Some(arg) = shared.buffer.pop_front() {
+			Poll::Ready(Some(arg))
+		} else if shared.ended {
+			Poll::Ready(None)
+		} else {
+			shared.waker = Some(cx.waker().clone());
+			Poll::Pending
+		}
+	}
+}
+
+impl<A> Drop for EventStream<A> {
+	fn drop(&mut self) {
+		let buffer = {
+			let mut shared = self.shared.lock().unwrap();
+			shared.abandoned = true;
+			std::mem::take(&mut shared.buffer)
+		};
+		drop(buffer);
+	}
+}
@@ -1,10 +1,23 @@
-use std::{borrow::Cow, collections::HashMap, fmt, str::FromStr};
+mod convert;
+#[cfg(feature = "serde")]
+mod de;
+mod parser;
+#[cfg(feature = "serde")]
+mod ser;
+
+use std::{borrow::Cow, collections::HashMap, error::Error, fmt, str::FromStr};
 
 use json::JsonValue;
 pub use num_bigfloat::BigFloat;
+use num_bigfloat::NAN;
+
+pub use self::parser::JsParseError;
+use self::parser::Parser;
+#[cfg(feature = "serde")]
+pub use self::{de::from_value, ser::to_value};
 
 /// A JavaScript value.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum JsValue {
 	Array(Vec<JsValue>),
 	Boolean(bool),
@@ -30,73 +43,36 @@ impl JsValue {
 		}
 	}
 
+	/// Parses a JavaScript literal, like the ones produced by `JsValue`'s
+	/// `Display` implementation. If parsing failed, the string is returned as
+	/// a `JsValue::Other`.
+	/// Use `str::parse` if you want to know why parsing failed.
 	pub fn from_string(string: &str) -> Self {
-		if string.len() == 0 {
-			return Self::Other(String::new());
-		}
-
-		// If the symbol starts with a digit, interpret it as a (positive) number
-		if "0123456789".contains(|c| c == string.chars().nth(0).unwrap()) {
-			return match BigFloat::from_str(string) {
-				Err(_) => Self::Other(format!("unable to parse number: {}", string)),
-				Ok(f) => Self::Number(f),
-			};
-		}
-		if string == "null" {
-			return Self::Null;
-		}
-		if string == "undefined" {
-			return Self::Undefined;
+		match string.parse() {
+			Err(_) => Self::Other(string.to_string()),
+			Ok(value) => value,
 		}
-		if string == "true" {
-			return Self::Boolean(true);
-		}
-		if string == "false" {
-			return Self::Boolean(false);
-		}
-		if string.chars().nth(0) == Some('\"') && string.chars().last() == Some('\"') {
-			return Self::String(string[1..(string.len() - 1)].to_string());
-		}
-		if string.chars().nth(0) == Some('[') && string.chars().last() == Some(']') {
-			let mut array = Vec::new();
-			for part in string[1..(string.len() - 1)].split(',') {
-				array.push(Self::from_string(part));
-			}
-			return Self::Array(array);
-		}
-		if string.chars().nth(0) == Some('{') && string.chars().last() == Some('}') {
-			let mut map = HashMap::new();
-			for part in string[1..(string.len() - 1)].split(',') {
-				if let Some((key, value)) = part.split_once(':') {
-					map.insert(key.to_string(), Self::from_string(value));
-				}
-			}
-			return Self::Object(map);
-		}
-
-		Self::Other(string.to_string())
 	}
 
 	fn _from_json(value: JsonValue) -> Self {
 		match value {
 			JsonValue::Null => Self::Null,
 			JsonValue::Short(s) => Self::String(s.to_string()),
-			JsonValue::String(s) => {
-				println!("S '{}'", s);
-				Self::String(s)
-			}
-			JsonValue::Number(n) => {
-				let (sign, mantissa, exponent) = n.as_parts();
-
-				let big: BigFloat = mantissa.into();
-				for _i in 0..exponent {
-					big.mul(&10.into());
-				}
-				if !sign {
-					big.inv_sign();
-				}
-				Self::Number(big)
-			}
+			JsonValue::String(s) => Self::String(s),
+			JsonValue::Number(n) =>
+				if n.is_nan() {
+					Self::Number(NAN)
+				} else {
+					// Build the number from its decimal parts, so that it stays exact
+					let (positive, mantissa, exponent) = n.as_parts();
+					let string = format!(
+						"{}{}e{}",
+						if positive { "" } else { "-" },
+						mantissa,
+						exponent
+					);
+					Self::Number(BigFloat::parse(&string).unwrap_or(NAN))
+				},
 			JsonValue::Boolean(b) => Self::Boolean(b),
 			JsonValue::Object(o) => {
 				let mut map = HashMap::with_capacity(o.len());
@@ -120,6 +96,75 @@ impl JsValue {
 	}
 }
 
+impl FromStr for JsValue {
+	type Err = JsParseError;
+
+	fn from_str(string: &str) -> Result<Self, JsParseError> { Parser::new(string).parse() }
+}
+
+/// The error that is returned when a `JsValue` couldn't be converted from or
+/// into another type.
+#[derive(Clone, Debug)]
+pub struct JsConversionError {
+	message: String,
+	path: String,
+}
+
+impl JsConversionError {
+	/// Creates an error with the given message, and an empty path.
+	pub fn new(message: impl Into<String>) -> Self {
+		Self {
+			message: message.into(),
+			path: String::new(),
+		}
+	}
+
+	/// Prefixes the path with the index of the array element that failed to
+	/// convert.
+	pub fn at_index(mut self, index: usize) -> Self {
+		self.path.insert_str(0, &format!("[{}]", index));
+		self
+	}
+
+	/// Prefixes the path with the key of the object property that failed to
+	/// convert.
+	pub fn at_key(mut self, key: &str) -> Self {
+		let is_identifier = key.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+			&& key
+				.chars()
+				.all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+		if is_identifier {
+			self.path.insert_str(0, &format!(".{}", key));
+		} else {
+			self.path
+				.insert_str(0, &format!("[\"{}\"]", escape_string(key)));
+		}
+		self
+	}
+
+	/// Prefixes the path with the name of the variable that failed to convert.
+	pub fn at_variable(mut self, name: &str) -> Self {
+		self.path.insert_str(0, name);
+		self
+	}
+
+	/// The location of the value that failed to convert, like
+	/// `args[2].user.id`. It is empty if the value itself failed to convert.
+	pub fn path(&self) -> &str { self.path.strip_prefix('.').unwrap_or(&self.path) }
+}
+
+impl fmt::Display for JsConversionError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.path.is_empty() {
+			write!(f, "{}", self.message)
+		} else {
+			write!(f, "{}: {}", self.path(), self.message)
+		}
+	}
+}
+
+impl Error for JsConversionError {}
+
 impl fmt::Display for JsValue {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
@@ -134,11 +179,14 @@ impl fmt::Display for JsValue {
 				write!(f, "]")
 			}
 			Self::Boolean(b) => write!(f, "{}", b),
-			Self::Number(n) => write!(f, "{}", n),
+			Self::Number(n) => write_number(f, n),
 			Self::Object(o) => {
 				write!(f, "{{")?;
-				for (k, v) in o.iter() {
-					write!(f, "\"{}\":{}", k, v)?;
+				for (i, (k, v)) in o.iter().enumerate() {
+					if i > 0 {
+						write!(f, ",")?;
+					}
+					write!(f, "\"{}\":{}", escape_string(k), v)?;
 				}
 				write!(f, "}}")
 			}
@@ -150,6 +198,46 @@ impl fmt::Display for JsValue {
 	}
 }
 
+/// Writes the number in the shortest form that still has the exact same value.
+fn write_number(f: &mut fmt::Formatter<'_>, n: &BigFloat) -> fmt::Result {
+	if n.is_nan() {
+		return write!(f, "NaN");
+	} else if n.is_inf_pos() {
+		return write!(f, "Infinity");
+	} else if n.is_inf_neg() {
+		return write!(f, "-Infinity");
+	} else if n.frac().is_zero() {
+		if let Some(i) = n.to_i128() {
+			return write!(f, "{}", i);
+		}
+	}
+
+	// `BigFloat` always writes all of the digits of its mantissa, so strip the
+	// trailing zeros.
+	let string = n.to_string();
+	let (mantissa, exponent) = match string.find('e') {
+		None => (string.as_str(), ""),
+		Some(i) => string.split_at(i),
+	};
+	let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+	write!(f, "{}{}", mantissa, exponent)
+}
+
+/// Returns the number as an `i64`, but only if it is an integer that fits.
+#[cfg(feature = "serde")]
+fn integer_i64(n: &BigFloat) -> Option<i64> { if n.frac().is_zero() { n.to_i64() } else { None } }
+
+#[cfg(feature = "serde")]
+fn integer_u64(n: &BigFloat) -> Option<u64> {
+	// `to_u64` converts the absolute value, so negative numbers are ruled out
+	// first.
+	if n.frac().is_zero() && !n.is_negative() {
+		n.to_u64()
+	} else {
+		None
+	}
+}
+
 const UNESCAPED_CHARACTERS: &str =
 	" -_0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ!#$%&()*+,./:;<=>?@[]^`{|}~";
 
@@ -227,4 +315,253 @@ mod tests {
 		let output = " test\\r\\n\\t\\b\\v\\f\\0\\x7f\\u{1234}\\u{2764}\\xa9";
 		assert_eq!(output, escape_string(input))
 	}
+
+	fn num(n: i64) -> JsValue { JsValue::Number(BigFloat::from_i64(n)) }
+
+	fn string(s: &str) -> JsValue { JsValue::String(s.into()) }
+
+	#[test]
+	fn test_parse_literals() {
+		assert_eq!("undefined".parse(), Ok(JsValue::Undefined));
+		assert_eq!(" null ".parse(), Ok(JsValue::Null));
+		assert_eq!("true".parse(), Ok(JsValue::Boolean(true)));
+		assert_eq!("false".parse(), Ok(JsValue::Boolean(false)));
+		assert_eq!("function".parse(), Ok(JsValue::Other("function".into())));
+		assert_eq!("-12".parse(), Ok(num(-12)));
+		assert_eq!("1.5e+2".parse(), Ok(num(150)));
+		assert_eq!(
+			"-0.25".parse(),
+			Ok(JsValue::Number(BigFloat::from_f64(-0.25)))
+		);
+		assert_eq!(".5".parse(), Ok(JsValue::Number(BigFloat::from_f64(0.5))));
+		assert_eq!(
+			"-Infinity".parse(),
+			Ok(JsValue::Number(num_bigfloat::INF_NEG))
+		);
+		assert!(matches!("NaN".parse(), Ok(JsValue::Number(n)) if n.is_nan()));
+	}
+
+	#[test]
+	fn test_parse_strings() {
+		assert_eq!(r#""a, b""#.parse(), Ok(string("a, b")));
+		assert_eq!(r#"'it\'s'"#.parse(), Ok(string("it's")));
+		assert_eq!(
+			r#""\"\\\n\r\t\b\f\v\0\x7f\xa9""#.parse(),
+			Ok(string("\"\\\n\r\t\x08\x0c\x0b\0\x7f©"))
+		);
+		assert_eq!(
+			r#""\u2764\u{1F600}\ud83d\ude00""#.parse(),
+			Ok(string("❤😀😀"))
+		);
+		assert_eq!("\"𝟛 ❤\"".parse(), Ok(string("𝟛 ❤")));
+	}
+
+	#[test]
+	fn test_parse_nested() {
+		let value: JsValue = r#"[1, [2, [-3, "x,y"]], {"a,b": [], c: {'d': null}}, ]"#
+			.parse()
+			.unwrap();
+
+		let mut inner = HashMap::new();
+		inner.insert("d".to_owned(), JsValue::Null);
+		let mut object = HashMap::new();
+		object.insert("a,b".to_owned(), JsValue::Array(Vec::new()));
+		object.insert("c".to_owned(), JsValue::Object(inner));
+		assert_eq!(
+			value,
+			JsValue::Array(vec![
+				num(1),
+				JsValue::Array(vec![num(2), JsValue::Array(vec![num(-3), string("x,y")])]),
+				JsValue::Object(object),
+			])
+		);
+	}
+
+	#[test]
+	fn test_parse_errors() {
+		let error = "[1, 2".parse::<JsValue>().unwrap_err();
+		assert_eq!(error.to_string(), "unterminated array at position 5");
+		let error = "{a 1}".parse::<JsValue>().unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"expected ':' but found '1' at position 3"
+		);
+		let error = "[1 2]".parse::<JsValue>().unwrap_err();
+		assert_eq!(error.position(), 3);
+		let error = r#"["abc]"#.parse::<JsValue>().unwrap_err();
+		assert_eq!(error.to_string(), "unterminated string at position 1");
+		let error = r#""\u12""#.parse::<JsValue>().unwrap_err();
+		assert_eq!(error.to_string(), "invalid escape sequence at position 1");
+		assert!("1e".parse::<JsValue>().is_err());
+		assert!("true false".parse::<JsValue>().is_err());
+		assert!("".parse::<JsValue>().is_err());
+
+		assert_eq!(JsValue::from_string("[1,"), JsValue::Other("[1,".into()));
+	}
+
+	#[test]
+	fn test_display_roundtrip() {
+		let mut object = HashMap::new();
+		object.insert("key with \"quotes\"".to_owned(), num(-7));
+		object.insert("❤".to_owned(), JsValue::Undefined);
+		let value = JsValue::Array(vec![
+			JsValue::Object(object),
+			string(" test\r\n\t\x08\x0b\x0c\0\x7f\u{1234}❤©, '\\"),
+			JsValue::Number(BigFloat::from_f64(-1234.5678e-20)),
+			JsValue::Number(num_bigfloat::INF_POS),
+			JsValue::Boolean(false),
+			JsValue::Null,
+			JsValue::Array(Vec::new()),
+		]);
+		assert_eq!(value.to_string().parse(), Ok(value));
+	}
+
+	#[test]
+	fn test_from_json() {
+		assert_eq!(JsValue::from_json("-1.5e3"), num(-1500));
+		assert_eq!(
+			JsValue::from_json("0.25"),
+			JsValue::Number(BigFloat::from_f64(0.25))
+		);
+		assert_eq!(
+			JsValue::from_json("9007199254740993"),
+			num(9007199254740993)
+		);
+		assert_eq!(
+			JsValue::from_json(r#"["a", "é"]"#),
+			JsValue::Array(vec![string("a"), string("é")])
+		);
+	}
+
+	#[test]
+	fn test_conversions() {
+		use std::convert::TryFrom;
+
+		assert_eq!(u64::try_from(JsValue::from(u64::MAX)).unwrap(), u64::MAX);
+		assert_eq!(i128::try_from(JsValue::from(i128::MIN)).unwrap(), i128::MIN);
+		assert_eq!(i8::try_from(num(-128)).unwrap(), -128);
+		assert_eq!(u32::try_from(JsValue::from_string("4.2e+1")).unwrap(), 42);
+		assert_eq!(f64::try_from(JsValue::from(0.1)).unwrap(), 0.1);
+		assert_eq!(f32::try_from(JsValue::from(0.1f32)).unwrap(), 0.1f32);
+		assert!(bool::try_from(JsValue::from(true)).unwrap());
+		assert_eq!(String::try_from(JsValue::from("abc")).unwrap(), "abc");
+		assert_eq!(Option::<u8>::try_from(JsValue::Undefined).unwrap(), None);
+		assert_eq!(
+			Option::<u8>::try_from(JsValue::from(Some(3u8))).unwrap(),
+			Some(3)
+		);
+		assert_eq!(JsValue::from(None::<u8>), JsValue::Null);
+		assert_eq!(
+			Vec::<i32>::try_from(JsValue::from(vec![1, -2])).unwrap(),
+			vec![1, -2]
+		);
+
+		let mut map = HashMap::new();
+		map.insert("a".to_owned(), vec![Some(1u16), None]);
+		assert_eq!(HashMap::try_from(JsValue::from(map.clone())).unwrap(), map);
+
+		assert_eq!(
+			JsValue::from(-1234567890123i64).to_string(),
+			"-1234567890123"
+		);
+		assert_eq!(JsValue::from(-0.0625).to_string(), "-6.25e-2");
+
+		let error = u8::try_from(num(256)).unwrap_err();
+		assert_eq!(error.to_string(), "256 is out of range for u8");
+		let error = u8::try_from(num(-1)).unwrap_err();
+		assert_eq!(error.to_string(), "-1 is out of range for u8");
+		let error = i64::try_from(JsValue::from(1.5)).unwrap_err();
+		assert_eq!(error.to_string(), "expected an integer, found 1.5");
+		let error = String::try_from(JsValue::Null).unwrap_err();
+		assert_eq!(error.to_string(), "expected a string, found null");
+	}
+
+	#[test]
+	fn test_conversion_error_path() {
+		use std::convert::TryFrom;
+
+		let value = JsValue::from_string(r#"[{"user": {"id": 1}}, {"user": {"id": 1.5}}]"#);
+		let error = Vec::<HashMap<String, HashMap<String, u32>>>::try_from(value)
+			.unwrap_err()
+			.at_variable("args");
+		assert_eq!(error.path(), "args[1].user.id");
+		assert_eq!(
+			error.to_string(),
+			"args[1].user.id: expected an integer, found 1.5"
+		);
+
+		let value = JsValue::from_string(r#"{"odd key": [true, 1]}"#);
+		let error = HashMap::<String, Vec<bool>>::try_from(value).unwrap_err();
+		assert_eq!(error.path(), r#"["odd key"][1]"#);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_serde() {
+		use serde::{Deserialize, Serialize};
+
+		#[derive(Debug, Deserialize, PartialEq, Serialize)]
+		enum Role {
+			Admin,
+			Guest { until: u64 },
+		}
+
+		#[derive(Debug, Deserialize, PartialEq, Serialize)]
+		struct User {
+			id: u32,
+			name: String,
+			score: f64,
+			balance: i64,
+			roles: Vec<Role>,
+			email: Option<String>,
+		}
+
+		let user = User {
+			id: 7,
+			name: "a, \"b\"".into(),
+			score: 0.5,
+			balance: -12,
+			roles: vec![Role::Admin, Role::Guest { until: 1700000000 }],
+			email: None,
+		};
+		let value = to_value(&user).unwrap();
+		match &value {
+			JsValue::Object(o) => {
+				assert!(matches!(o["name"], JsValue::String(ref s) if s == "a, \"b\""));
+				assert!(matches!(o["email"], JsValue::Null));
+			}
+			_ => panic!("expected an object"),
+		}
+		assert_eq!(from_value::<User>(value).unwrap(), user);
+
+		// Undefined is also accepted for options, and unknown fields are ignored
+		let mut map = HashMap::new();
+		map.insert("id".to_owned(), JsValue::Number(BigFloat::from_u32(1)));
+		map.insert("name".to_owned(), JsValue::String("x".into()));
+		map.insert("score".to_owned(), JsValue::Number(BigFloat::from_i32(3)));
+		map.insert("balance".to_owned(), JsValue::Number(BigFloat::from_i32(0)));
+		map.insert("roles".to_owned(), JsValue::Array(vec![]));
+		map.insert("email".to_owned(), JsValue::Undefined);
+		map.insert("other".to_owned(), JsValue::Boolean(true));
+		let user: User = from_value(JsValue::Object(map)).unwrap();
+		assert_eq!(user.score, 3.0);
+		assert_eq!(user.email, None);
+
+		assert!(from_value::<u8>(JsValue::Number(BigFloat::from_i32(-1))).is_err());
+		assert!(from_value::<u32>(JsValue::Number(BigFloat::from_f64(1.5))).is_err());
+		assert!(from_value::<User>(JsValue::String("user".into())).is_err());
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn test_serde_json_roundtrip() {
+		let json = r#"{"a":[1,-2.5,"x",true,null],"b":{"c":18446744073709551615}}"#;
+		let value: JsValue = serde_json::from_str(json).unwrap();
+		let back: serde_json::Value =
+			serde_json::from_str(&serde_json::to_string(&value).unwrap()).unwrap();
+		assert_eq!(
+			back,
+			serde_json::from_str::<serde_json::Value>(json).unwrap()
+		);
+	}
 }
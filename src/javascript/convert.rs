@@ -0,0 +1,243 @@
+//! Conversions between `JsValue` and common Rust types.
+//!
+//! Numbers are only converted into integer types when they are whole and fit
+//! into the integer type, so no precision gets lost. Containers add the index
+//! or key of their failing element to the error's path.
+
+use std::{collections::HashMap, convert::TryFrom};
+
+use num_bigfloat::BigFloat;
+
+use super::{JsConversionError, JsValue};
+
+impl JsValue {
+	/// The name of the type of this value, as used in error messages.
+	fn type_name(&self) -> &'static str {
+		match self {
+			Self::Array(_) => "an array",
+			Self::Boolean(_) => "a boolean",
+			Self::Null => "null",
+			Self::Number(_) => "a number",
+			Self::Object(_) => "an object",
+			Self::String(_) => "a string",
+			Self::Undefined => "undefined",
+			Self::Other(_) => "an unsupported value",
+		}
+	}
+}
+
+fn unexpected(expected: &str, value: &JsValue) -> JsConversionError {
+	JsConversionError::new(format!(
+		"expected {}, found {}",
+		expected,
+		value.type_name()
+	))
+}
+
+/// Returns the whole number, or an error explaining why it isn't one.
+fn expect_integer(value: &JsValue) -> Result<&BigFloat, JsConversionError> {
+	match value {
+		JsValue::Number(n) =>
+			if n.is_nan() || n.is_inf() || !n.frac().is_zero() {
+				Err(JsConversionError::new(format!(
+					"expected an integer, found {}",
+					value
+				)))
+			} else {
+				Ok(n)
+			},
+		other => Err(unexpected("an integer", other)),
+	}
+}
+
+fn out_of_range(value: &JsValue, type_name: &str) -> JsConversionError {
+	JsConversionError::new(format!("{} is out of range for {}", value, type_name))
+}
+
+macro_rules! impl_signed {
+	($($type:ty),*) => {$(
+		impl From<$type> for JsValue {
+			fn from(value: $type) -> Self { Self::Number(BigFloat::from_i128(value as i128)) }
+		}
+
+		impl TryFrom<JsValue> for $type {
+			type Error = JsConversionError;
+
+			fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+				expect_integer(&value)?
+					.to_i128()
+					.and_then(|i| <$type>::try_from(i).ok())
+					.ok_or_else(|| out_of_range(&value, stringify!($type)))
+			}
+		}
+	)*};
+}
+
+macro_rules! impl_unsigned {
+	($($type:ty),*) => {$(
+		impl From<$type> for JsValue {
+			fn from(value: $type) -> Self { Self::Number(BigFloat::from_u128(value as u128)) }
+		}
+
+		impl TryFrom<JsValue> for $type {
+			type Error = JsConversionError;
+
+			fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+				let n = expect_integer(&value)?;
+				// `to_u128` converts the absolute value, so negative numbers are ruled out
+				// first.
+				if n.is_negative() && !n.is_zero() {
+					return Err(out_of_range(&value, stringify!($type)));
+				}
+				n.to_u128()
+					.and_then(|u| <$type>::try_from(u).ok())
+					.ok_or_else(|| out_of_range(&value, stringify!($type)))
+			}
+		}
+	)*};
+}
+
+impl_signed!(i8, i16, i32, i64, i128, isize);
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+
+impl From<f32> for JsValue {
+	fn from(value: f32) -> Self { Self::Number(BigFloat::from_f32(value)) }
+}
+
+impl From<f64> for JsValue {
+	fn from(value: f64) -> Self { Self::Number(BigFloat::from_f64(value)) }
+}
+
+impl TryFrom<JsValue> for f32 {
+	type Error = JsConversionError;
+
+	fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+		f64::try_from(value).map(|f| f as f32)
+	}
+}
+
+impl TryFrom<JsValue> for f64 {
+	type Error = JsConversionError;
+
+	fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+		match value {
+			JsValue::Number(n) => Ok(n.to_f64()),
+			other => Err(unexpected("a number", &other)),
+		}
+	}
+}
+
+impl From<bool> for JsValue {
+	fn from(value: bool) -> Self { Self::Boolean(value) }
+}
+
+impl TryFrom<JsValue> for bool {
+	type Error = JsConversionError;
+
+	fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+		match value {
+			JsValue::Boolean(b) => Ok(b),
+			other => Err(unexpected("a boolean", &other)),
+		}
+	}
+}
+
+impl From<String> for JsValue {
+	fn from(value: String) -> Self { Self::String(value) }
+}
+
+impl From<&str> for JsValue {
+	fn from(value: &str) -> Self { Self::String(value.to_owned()) }
+}
+
+impl TryFrom<JsValue> for String {
+	type Error = JsConversionError;
+
+	fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+		match value {
+			JsValue::String(s) => Ok(s),
+			other => Err(unexpected("a string", &other)),
+		}
+	}
+}
+
+impl<T> From<Vec<T>> for JsValue
+where
+	T: Into<JsValue>,
+{
+	fn from(value: Vec<T>) -> Self { Self::Array(value.into_iter().map(Into::into).collect()) }
+}
+
+impl<T> TryFrom<JsValue> for Vec<T>
+where
+	T: TryFrom<JsValue, Error = JsConversionError>,
+{
+	type Error = JsConversionError;
+
+	fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+		match value {
+			JsValue::Array(a) => a
+				.into_iter()
+				.enumerate()
+				.map(|(i, v)| T::try_from(v).map_err(|e| e.at_index(i)))
+				.collect(),
+			other => Err(unexpected("an array", &other)),
+		}
+	}
+}
+
+impl<T> From<HashMap<String, T>> for JsValue
+where
+	T: Into<JsValue>,
+{
+	fn from(value: HashMap<String, T>) -> Self {
+		Self::Object(value.into_iter().map(|(k, v)| (k, v.into())).collect())
+	}
+}
+
+impl<T> TryFrom<JsValue> for HashMap<String, T>
+where
+	T: TryFrom<JsValue, Error = JsConversionError>,
+{
+	type Error = JsConversionError;
+
+	fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+		match value {
+			JsValue::Object(o) => o
+				.into_iter()
+				.map(|(k, v)| match T::try_from(v) {
+					Ok(v) => Ok((k, v)),
+					Err(e) => Err(e.at_key(&k)),
+				})
+				.collect(),
+			other => Err(unexpected("an object", &other)),
+		}
+	}
+}
+
+impl<T> From<Option<T>> for JsValue
+where
+	T: Into<JsValue>,
+{
+	fn from(value: Option<T>) -> Self {
+		match value {
+			None => Self::Null,
+			Some(v) => v.into(),
+		}
+	}
+}
+
+/// Both `null` and `undefined` are converted into `None`.
+impl<T> TryFrom<JsValue> for Option<T>
+where
+	T: TryFrom<JsValue, Error = JsConversionError>,
+{
+	type Error = JsConversionError;
+
+	fn try_from(value: JsValue) -> Result<Self, JsConversionError> {
+		match value {
+			JsValue::Null | JsValue::Undefined => Ok(None),
+			other => T::try_from(other).map(Some),
+		}
+	}
+}
@@ -0,0 +1,271 @@
+use std::{collections::HashMap, fmt};
+
+use num_bigfloat::BigFloat;
+use serde::{
+	Deserialize,
+	de::{
+		self, DeserializeOwned, IntoDeserializer, MapAccess, SeqAccess, Unexpected, Visitor,
+		value::{MapDeserializer, SeqDeserializer},
+	},
+	forward_to_deserialize_any,
+};
+
+use super::{JsConversionError, JsValue, integer_i64, integer_u64};
+
+/// Converts a `JsValue` into any deserializable type.
+///
+/// This is useful for turning the arguments of a message, or the result of
+/// `eval_js`, into your own types:
+/// ```ignore
+/// #[derive(Deserialize)]
+/// struct User {
+/// 	id: u32,
+/// 	name: String,
+/// }
+///
+/// let user: User = from_value(bw.eval_js("getUser()").await?)?;
+/// ```
+///
+/// Both `null` and `undefined` are accepted for `Option`s and `()`.
+pub fn from_value<T>(value: JsValue) -> Result<T, JsConversionError>
+where
+	T: DeserializeOwned,
+{
+	T::deserialize(value)
+}
+
+impl<'de> Deserialize<'de> for JsValue {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: de::Deserializer<'de>,
+	{
+		deserializer.deserialize_any(JsValueVisitor)
+	}
+}
+
+impl de::Error for JsConversionError {
+	fn custom<T: fmt::Display>(msg: T) -> Self { Self::new(msg.to_string()) }
+}
+
+struct JsValueVisitor;
+
+impl<'de> Visitor<'de> for JsValueVisitor {
+	type Value = JsValue;
+
+	fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "any javascript value")
+	}
+
+	fn visit_bool<E>(self, v: bool) -> Result<JsValue, E> { Ok(JsValue::Boolean(v)) }
+
+	fn visit_i64<E>(self, v: i64) -> Result<JsValue, E> {
+		Ok(JsValue::Number(BigFloat::from_i64(v)))
+	}
+
+	fn visit_i128<E>(self, v: i128) -> Result<JsValue, E> {
+		Ok(JsValue::Number(BigFloat::from_i128(v)))
+	}
+
+	fn visit_u64<E>(self, v: u64) -> Result<JsValue, E> {
+		Ok(JsValue::Number(BigFloat::from_u64(v)))
+	}
+
+	fn visit_u128<E>(self, v: u128) -> Result<JsValue, E> {
+		Ok(JsValue::Number(BigFloat::from_u128(v)))
+	}
+
+	fn visit_f64<E>(self, v: f64) -> Result<JsValue, E> {
+		Ok(JsValue::Number(BigFloat::from_f64(v)))
+	}
+
+	fn visit_str<E>(self, v: &str) -> Result<JsValue, E> { Ok(JsValue::String(v.to_owned())) }
+
+	fn visit_string<E>(self, v: String) -> Result<JsValue, E> { Ok(JsValue::String(v)) }
+
+	fn visit_none<E>(self) -> Result<JsValue, E> { Ok(JsValue::Null) }
+
+	fn visit_some<D>(self, deserializer: D) -> Result<JsValue, D::Error>
+	where
+		D: de::Deserializer<'de>,
+	{
+		JsValue::deserialize(deserializer)
+	}
+
+	fn visit_unit<E>(self) -> Result<JsValue, E> { Ok(JsValue::Null) }
+
+	fn visit_seq<A>(self, mut seq: A) -> Result<JsValue, A::Error>
+	where
+		A: SeqAccess<'de>,
+	{
+		let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+		while let Some(value) = seq.next_element()? {
+			array.push(value);
+		}
+		Ok(JsValue::Array(array))
+	}
+
+	fn visit_map<A>(self, mut map: A) -> Result<JsValue, A::Error>
+	where
+		A: MapAccess<'de>,
+	{
+		let mut object = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+		while let Some((key, value)) = map.next_entry::<String, JsValue>()? {
+			object.insert(key, value);
+		}
+		Ok(JsValue::Object(object))
+	}
+}
+
+impl<'de> IntoDeserializer<'de, JsConversionError> for JsValue {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self { self }
+}
+
+impl JsValue {
+	fn unexpected(&self) -> Unexpected<'_> {
+		match self {
+			Self::Array(_) => Unexpected::Seq,
+			Self::Boolean(b) => Unexpected::Bool(*b),
+			Self::Null | Self::Undefined => Unexpected::Unit,
+			Self::Number(n) => Unexpected::Float(n.to_f64()),
+			Self::Object(_) => Unexpected::Map,
+			Self::String(s) => Unexpected::Str(s),
+			Self::Other(_) => Unexpected::Other("javascript value"),
+		}
+	}
+}
+
+impl<'de> de::Deserializer<'de> for JsValue {
+	type Error = JsConversionError;
+
+	forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq tuple
+		tuple_struct map struct identifier ignored_any
+	}
+
+	fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self {
+			Self::Array(a) => {
+				let mut seq = SeqDeserializer::new(a.into_iter());
+				let value = visitor.visit_seq(&mut seq)?;
+				seq.end()?;
+				Ok(value)
+			}
+			Self::Boolean(b) => visitor.visit_bool(b),
+			Self::Null | Self::Undefined => visitor.visit_unit(),
+			Self::Number(n) =>
+				if let Some(i) = integer_i64(&n) {
+					visitor.visit_i64(i)
+				} else if let Some(u) = integer_u64(&n) {
+					visitor.visit_u64(u)
+				} else {
+					visitor.visit_f64(n.to_f64())
+				},
+			Self::Object(o) => {
+				let mut map = MapDeserializer::new(o.into_iter());
+				let value = visitor.visit_map(&mut map)?;
+				map.end()?;
+				Ok(value)
+			}
+			Self::String(s) => visitor.visit_string(s),
+			other @ Self::Other(_) => Err(de::Error::invalid_type(other.unexpected(), &visitor)),
+		}
+	}
+
+	fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self {
+			Self::Null | Self::Undefined => visitor.visit_none(),
+			other => visitor.visit_some(other),
+		}
+	}
+
+	fn deserialize_newtype_struct<V>(
+		self, _name: &'static str, visitor: V,
+	) -> Result<V::Value, Self::Error>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V>(
+		self, _name: &'static str, _variants: &'static [&'static str], visitor: V,
+	) -> Result<V::Value, Self::Error>
+	where
+		V: Visitor<'de>,
+	{
+		match self {
+			Self::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+			Self::Object(o) if o.len() == 1 => {
+				let (variant, value) = o.into_iter().next().unwrap();
+				visitor.visit_enum(EnumDeserializer { variant, value })
+			}
+			other => Err(de::Error::invalid_type(
+				other.unexpected(),
+				&"a string or an object with a single key",
+			)),
+		}
+	}
+}
+
+/// Deserializes enum variants that carry data, which are represented as an
+/// object with a single key.
+struct EnumDeserializer {
+	variant: String,
+	value: JsValue,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+	type Error = JsConversionError;
+	type Variant = JsValue;
+
+	fn variant_seed<V>(self, seed: V) -> Result<(V::Value, JsValue), Self::Error>
+	where
+		V: de::DeserializeSeed<'de>,
+	{
+		let variant = seed.deserialize(self.variant.into_deserializer())?;
+		Ok((variant, self.value))
+	}
+}
+
+impl<'de> de::VariantAccess<'de> for JsValue {
+	type Error = JsConversionError;
+
+	fn unit_variant(self) -> Result<(), Self::Error> {
+		match self {
+			Self::Null | Self::Undefined => Ok(()),
+			other => Err(de::Error::invalid_type(other.unexpected(), &"unit variant")),
+		}
+	}
+
+	fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+	where
+		T: de::DeserializeSeed<'de>,
+	{
+		seed.deserialize(self)
+	}
+
+	fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+	where
+		V: Visitor<'de>,
+	{
+		de::Deserializer::deserialize_seq(self, visitor)
+	}
+
+	fn struct_variant<V>(
+		self, _fields: &'static [&'static str], visitor: V,
+	) -> Result<V::Value, Self::Error>
+	where
+		V: Visitor<'de>,
+	{
+		de::Deserializer::deserialize_map(self, visitor)
+	}
+}
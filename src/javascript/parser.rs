@@ -0,0 +1,359 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use num_bigfloat::{BigFloat, INF_NEG, INF_POS, NAN};
+
+use super::JsValue;
+
+/// The error that is returned when a string couldn't be parsed into a
+/// `JsValue`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsParseError {
+	message: String,
+	position: usize,
+}
+
+/// A parser for the JavaScript literals that are produced by `JsValue`'s
+/// `Display` implementation and by the CEF backend.
+///
+/// Besides JSON, it understands single-quoted strings, unquoted object keys,
+/// trailing commas, `undefined`, `NaN`, `Infinity`, and all escape sequences
+/// that JavaScript strings can contain.
+/// Any other identifiers, like `function`, are returned as `JsValue::Other`.
+pub(super) struct Parser<'a> {
+	input: &'a str,
+	position: usize,
+}
+
+impl JsParseError {
+	/// The byte offset into the input at which the error was encountered.
+	pub fn position(&self) -> usize { self.position }
+}
+
+impl fmt::Display for JsParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} at position {}", self.message, self.position)
+	}
+}
+
+impl Error for JsParseError {}
+
+impl<'a> Parser<'a> {
+	pub fn new(input: &'a str) -> Self { Self { input, position: 0 } }
+
+	/// Parses the whole input as a single value.
+	pub fn parse(mut self) -> Result<JsValue, JsParseError> {
+		self.skip_whitespace();
+		let value = self.parse_value()?;
+		self.skip_whitespace();
+		match self.peek() {
+			None => Ok(value),
+			Some(c) => Err(self.error(format!("unexpected character '{}' after value", c))),
+		}
+	}
+
+	fn error(&self, message: impl Into<String>) -> JsParseError {
+		JsParseError {
+			message: message.into(),
+			position: self.position,
+		}
+	}
+
+	fn expect(&mut self, expected: char) -> Result<(), JsParseError> {
+		match self.peek() {
+			Some(c) if c == expected => {
+				self.position += c.len_utf8();
+				Ok(())
+			}
+			Some(c) => Err(self.error(format!("expected '{}' but found '{}'", expected, c))),
+			None => Err(self.error(format!(
+				"expected '{}' but reached the end of the input",
+				expected
+			))),
+		}
+	}
+
+	fn next(&mut self) -> Option<char> {
+		let c = self.peek()?;
+		self.position += c.len_utf8();
+		Some(c)
+	}
+
+	fn peek(&self) -> Option<char> { self.input[self.position..].chars().next() }
+
+	fn skip_whitespace(&mut self) {
+		while let Some(c) = self.peek() {
+			if !c.is_whitespace() {
+				break;
+			}
+			self.position += c.len_utf8();
+		}
+	}
+
+	fn parse_value(&mut self) -> Result<JsValue, JsParseError> {
+		match self.peek() {
+			None => Err(self.error("unexpected end of input")),
+			Some('[') => self.parse_array(),
+			Some('{') => self.parse_object(),
+			Some(quote @ ('"' | '\'')) => Ok(JsValue::String(self.parse_string(quote)?)),
+			Some('-' | '+' | '.' | '0'..='9') => Ok(JsValue::Number(self.parse_number()?)),
+			Some(c) if is_identifier_start(c) => Ok(match self.parse_identifier() {
+				"undefined" => JsValue::Undefined,
+				"null" => JsValue::Null,
+				"true" => JsValue::Boolean(true),
+				"false" => JsValue::Boolean(false),
+				"NaN" => JsValue::Number(NAN),
+				"Infinity" => JsValue::Number(INF_POS),
+				other => JsValue::Other(other.to_owned()),
+			}),
+			Some(c) => Err(self.error(format!("unexpected character '{}'", c))),
+		}
+	}
+
+	fn parse_array(&mut self) -> Result<JsValue, JsParseError> {
+		self.expect('[')?;
+		let mut array = Vec::new();
+		loop {
+			self.skip_whitespace();
+			if self.peek() == Some(']') {
+				self.position += 1;
+				return Ok(JsValue::Array(array));
+			}
+			array.push(self.parse_value()?);
+			self.skip_whitespace();
+			match self.peek() {
+				Some(',') => self.position += 1,
+				Some(']') => {}
+				Some(c) => return Err(self.error(format!("expected ',' or ']' but found '{}'", c))),
+				None => return Err(self.error("unterminated array")),
+			}
+		}
+	}
+
+	fn parse_object(&mut self) -> Result<JsValue, JsParseError> {
+		self.expect('{')?;
+		let mut map = HashMap::new();
+		loop {
+			self.skip_whitespace();
+			let key = match self.peek() {
+				Some('}') => {
+					self.position += 1;
+					return Ok(JsValue::Object(map));
+				}
+				Some(quote @ ('"' | '\'')) => self.parse_string(quote)?,
+				Some('0'..='9') => {
+					let start = self.position;
+					self.parse_number()?;
+					self.input[start..self.position].to_owned()
+				}
+				Some(c) if is_identifier_start(c) => self.parse_identifier().to_owned(),
+				Some(c) =>
+					return Err(self.error(format!("unexpected character '{}' in object key", c))),
+				None => return Err(self.error("unterminated object")),
+			};
+			self.skip_whitespace();
+			self.expect(':')?;
+			self.skip_whitespace();
+			let value = self.parse_value()?;
+			map.insert(key, value);
+			self.skip_whitespace();
+			match self.peek() {
+				Some(',') => self.position += 1,
+				Some('}') => {}
+				Some(c) =>
+					return Err(self.error(format!("expected ',' or '}}' but found '{}'", c))),
+				None => return Err(self.error("unterminated object")),
+			}
+		}
+	}
+
+	fn parse_identifier(&mut self) -> &'a str {
+		let start = self.position;
+		while let Some(c) = self.peek() {
+			if !is_identifier_start(c) && !c.is_ascii_digit() {
+				break;
+			}
+			self.position += c.len_utf8();
+		}
+		&self.input[start..self.position]
+	}
+
+	fn parse_number(&mut self) -> Result<BigFloat, JsParseError> {
+		let start = self.position;
+		let negative = match self.peek() {
+			Some('-') => {
+				self.position += 1;
+				true
+			}
+			Some('+') => {
+				self.position += 1;
+				false
+			}
+			_ => false,
+		};
+		if self.peek() == Some('I') {
+			return if self.parse_identifier() == "Infinity" {
+				Ok(if negative { INF_NEG } else { INF_POS })
+			} else {
+				Err(JsParseError {
+					message: "invalid number".into(),
+					position: start,
+				})
+			};
+		}
+
+		let integer = self.parse_digits();
+		let mut fraction = "";
+		if self.peek() == Some('.') {
+			self.position += 1;
+			fraction = self.parse_digits();
+		}
+		if integer.is_empty() && fraction.is_empty() {
+			return Err(self.error("expected a digit"));
+		}
+		let mut exponent = String::new();
+		if let Some('e' | 'E') = self.peek() {
+			self.position += 1;
+			if let Some(sign @ ('-' | '+')) = self.peek() {
+				self.position += 1;
+				exponent.push(sign);
+			}
+			let digits = self.parse_digits();
+			if digits.is_empty() {
+				return Err(self.error("expected a digit in the exponent"));
+			}
+			exponent.push_str(digits);
+		}
+
+		// Normalize the number, so that leading or trailing dots and a missing
+		// exponent don't matter to `BigFloat::parse`.
+		let normalized = format!(
+			"{}{}.{}e{}",
+			if negative { "-" } else { "" },
+			if integer.is_empty() { "0" } else { integer },
+			if fraction.is_empty() { "0" } else { fraction },
+			if exponent.is_empty() { "0" } else { &exponent }
+		);
+		BigFloat::parse(&normalized).ok_or_else(|| JsParseError {
+			message: format!("invalid number '{}'", &self.input[start..self.position]),
+			position: start,
+		})
+	}
+
+	fn parse_digits(&mut self) -> &'a str {
+		let start = self.position;
+		while let Some('0'..='9') = self.peek() {
+			self.position += 1;
+		}
+		&self.input[start..self.position]
+	}
+
+	fn parse_string(&mut self, quote: char) -> Result<String, JsParseError> {
+		let start = self.position;
+		self.expect(quote)?;
+		let mut string = String::new();
+		loop {
+			match self.next() {
+				None =>
+					return Err(JsParseError {
+						message: "unterminated string".into(),
+						position: start,
+					}),
+				Some('\\') => self.parse_escape(&mut string)?,
+				Some(c) if c == quote => return Ok(string),
+				Some(c) => string.push(c),
+			}
+		}
+	}
+
+	fn parse_escape(&mut self, string: &mut String) -> Result<(), JsParseError> {
+		let start = self.position - 1;
+		let c = match self.next() {
+			None => return Err(self.error("unterminated string")),
+			Some(c) => c,
+		};
+		match c {
+			'n' => string.push('\n'),
+			'r' => string.push('\r'),
+			't' => string.push('\t'),
+			'b' => string.push('\x08'),
+			'f' => string.push('\x0c'),
+			'v' => string.push('\x0b'),
+			'0' => string.push('\0'),
+			'x' => {
+				let code = self.parse_hex(2, start)?;
+				string.push(char::from_u32(code).unwrap());
+			}
+			'u' => {
+				let code = if self.peek() == Some('{') {
+					self.position += 1;
+					let digits_start = self.position;
+					while let Some(c) = self.peek() {
+						if !c.is_ascii_hexdigit() {
+							break;
+						}
+						self.position += 1;
+					}
+					let digits = &self.input[digits_start..self.position];
+					self.expect('}')?;
+					u32::from_str_radix(digits, 16).ok()
+				} else {
+					let code = self.parse_hex(4, start)?;
+					// A high surrogate should be followed by an escaped low surrogate.
+					if (0xD800..0xDC00).contains(&code)
+						&& self.input[self.position..].starts_with("\\u")
+					{
+						self.position += 2;
+						let low = self.parse_hex(4, start)?;
+						if !(0xDC00..0xE000).contains(&low) {
+							return Err(JsParseError {
+								message: "invalid surrogate pair".into(),
+								position: start,
+							});
+						}
+						Some(0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00))
+					} else {
+						Some(code)
+					}
+				};
+				match code.and_then(char::from_u32) {
+					Some(c) => string.push(c),
+					None =>
+						return Err(JsParseError {
+							message: format!(
+								"invalid unicode escape '{}'",
+								&self.input[start..self.position]
+							),
+							position: start,
+						}),
+				}
+			}
+			// An escaped line break is a line continuation.
+			'\n' => {}
+			'\r' =>
+				if self.peek() == Some('\n') {
+					self.position += 1;
+				},
+			// Any other character is just itself, like `\"`, `\'` and `\\`.
+			other => string.push(other),
+		}
+		Ok(())
+	}
+
+	fn parse_hex(&mut self, length: usize, start: usize) -> Result<u32, JsParseError> {
+		let digits = self.input[self.position..]
+			.get(..length)
+			.filter(|d| d.chars().all(|c| c.is_ascii_hexdigit()));
+		match digits {
+			Some(d) => {
+				self.position += length;
+				Ok(u32::from_str_radix(d, 16).unwrap())
+			}
+			None => Err(JsParseError {
+				message: "invalid escape sequence".into(),
+				position: start,
+			}),
+		}
+	}
+}
+
+fn is_identifier_start(c: char) -> bool { c.is_alphabetic() || c == '_' || c == '$' }
@@ -0,0 +1,389 @@
+use std::{collections::HashMap, fmt};
+
+use num_bigfloat::BigFloat;
+use serde::ser::{self, Serialize};
+
+use super::{JsConversionError, JsValue, integer_i64, integer_u64};
+
+/// Converts any serializable type into a `JsValue`.
+///
+/// Structs and maps become `JsValue::Object`s, sequences and tuples become
+/// `JsValue::Array`s, and `None` and `()` become `JsValue::Null`.
+/// Enum variants that carry data are represented as an object with the
+/// variant name as its only key, just like `serde_json` does.
+///
+/// The result can be formatted into JavaScript code with its `Display`
+/// implementation:
+/// ```ignore
+/// let data = to_value(&my_struct).unwrap();
+/// bw.exec_js(&format!("render({})", data));
+/// ```
+pub fn to_value<T>(value: &T) -> Result<JsValue, JsConversionError>
+where
+	T: Serialize + ?Sized,
+{
+	value.serialize(Serializer)
+}
+
+impl Serialize for JsValue {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: ser::Serializer,
+	{
+		match self {
+			Self::Array(a) => serializer.collect_seq(a),
+			Self::Boolean(b) => serializer.serialize_bool(*b),
+			Self::Null | Self::Undefined => serializer.serialize_unit(),
+			Self::Number(n) =>
+				if let Some(i) = integer_i64(n) {
+					serializer.serialize_i64(i)
+				} else if let Some(u) = integer_u64(n) {
+					serializer.serialize_u64(u)
+				} else {
+					serializer.serialize_f64(n.to_f64())
+				},
+			Self::Object(o) => serializer.collect_map(o),
+			Self::String(s) | Self::Other(s) => serializer.serialize_str(s),
+		}
+	}
+}
+
+impl ser::Error for JsConversionError {
+	fn custom<T: fmt::Display>(msg: T) -> Self { Self::new(msg.to_string()) }
+}
+
+/// The serializer that produces `JsValue`s.
+struct Serializer;
+
+struct SerializeVec {
+	array: Vec<JsValue>,
+}
+
+struct SerializeTupleVariant {
+	name: &'static str,
+	array: Vec<JsValue>,
+}
+
+struct SerializeMap {
+	map: HashMap<String, JsValue>,
+	next_key: Option<String>,
+}
+
+struct SerializeStructVariant {
+	name: &'static str,
+	map: HashMap<String, JsValue>,
+}
+
+fn variant_object(name: &str, value: JsValue) -> JsValue {
+	let mut map = HashMap::with_capacity(1);
+	map.insert(name.to_owned(), value);
+	JsValue::Object(map)
+}
+
+impl ser::Serializer for Serializer {
+	type Error = JsConversionError;
+	type Ok = JsValue;
+	type SerializeMap = SerializeMap;
+	type SerializeSeq = SerializeVec;
+	type SerializeStruct = SerializeMap;
+	type SerializeStructVariant = SerializeStructVariant;
+	type SerializeTuple = SerializeVec;
+	type SerializeTupleStruct = SerializeVec;
+	type SerializeTupleVariant = SerializeTupleVariant;
+
+	fn serialize_bool(self, v: bool) -> Result<JsValue, Self::Error> { Ok(JsValue::Boolean(v)) }
+
+	fn serialize_i8(self, v: i8) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_i8(v)))
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_i16(v)))
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_i32(v)))
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_i64(v)))
+	}
+
+	fn serialize_i128(self, v: i128) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_i128(v)))
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_u8(v)))
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_u16(v)))
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_u32(v)))
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_u64(v)))
+	}
+
+	fn serialize_u128(self, v: u128) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Number(BigFloat::from_u128(v)))
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<JsValue, Self::Error> { self.serialize_f64(v as f64) }
+
+	fn serialize_f64(self, v: f64) -> Result<JsValue, Self::Error> {
+		if v.is_finite() {
+			Ok(JsValue::Number(BigFloat::from_f64(v)))
+		} else {
+			Err(JsConversionError::new(format!(
+				"unable to represent {} as a number",
+				v
+			)))
+		}
+	}
+
+	fn serialize_char(self, v: char) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::String(v.to_string()))
+	}
+
+	fn serialize_str(self, v: &str) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::String(v.to_owned()))
+	}
+
+	fn serialize_bytes(self, v: &[u8]) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Array(
+			v.iter()
+				.map(|b| JsValue::Number(BigFloat::from_u8(*b)))
+				.collect(),
+		))
+	}
+
+	fn serialize_none(self) -> Result<JsValue, Self::Error> { Ok(JsValue::Null) }
+
+	fn serialize_some<T>(self, value: &T) -> Result<JsValue, Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<JsValue, Self::Error> { Ok(JsValue::Null) }
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::Null)
+	}
+
+	fn serialize_unit_variant(
+		self, _name: &'static str, _variant_index: u32, variant: &'static str,
+	) -> Result<JsValue, Self::Error> {
+		Ok(JsValue::String(variant.to_owned()))
+	}
+
+	fn serialize_newtype_struct<T>(
+		self, _name: &'static str, value: &T,
+	) -> Result<JsValue, Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T>(
+		self, _name: &'static str, _variant_index: u32, variant: &'static str, value: &T,
+	) -> Result<JsValue, Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		Ok(variant_object(variant, value.serialize(self)?))
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, Self::Error> {
+		Ok(SerializeVec {
+			array: Vec::with_capacity(len.unwrap_or(0)),
+		})
+	}
+
+	fn serialize_tuple(self, len: usize) -> Result<SerializeVec, Self::Error> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_struct(
+		self, _name: &'static str, len: usize,
+	) -> Result<SerializeVec, Self::Error> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_variant(
+		self, _name: &'static str, _variant_index: u32, variant: &'static str, len: usize,
+	) -> Result<SerializeTupleVariant, Self::Error> {
+		Ok(SerializeTupleVariant {
+			name: variant,
+			array: Vec::with_capacity(len),
+		})
+	}
+
+	fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, Self::Error> {
+		Ok(SerializeMap {
+			map: HashMap::with_capacity(len.unwrap_or(0)),
+			next_key: None,
+		})
+	}
+
+	fn serialize_struct(
+		self, _name: &'static str, len: usize,
+	) -> Result<SerializeMap, Self::Error> {
+		self.serialize_map(Some(len))
+	}
+
+	fn serialize_struct_variant(
+		self, _name: &'static str, _variant_index: u32, variant: &'static str, len: usize,
+	) -> Result<SerializeStructVariant, Self::Error> {
+		Ok(SerializeStructVariant {
+			name: variant,
+			map: HashMap::with_capacity(len),
+		})
+	}
+}
+
+impl ser::SerializeSeq for SerializeVec {
+	type Error = JsConversionError;
+	type Ok = JsValue;
+
+	fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		self.array.push(value.serialize(Serializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<JsValue, Self::Error> { Ok(JsValue::Array(self.array)) }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+	type Error = JsConversionError;
+	type Ok = JsValue;
+
+	fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<JsValue, Self::Error> { ser::SerializeSeq::end(self) }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+	type Error = JsConversionError;
+	type Ok = JsValue;
+
+	fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<JsValue, Self::Error> { ser::SerializeSeq::end(self) }
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+	type Error = JsConversionError;
+	type Ok = JsValue;
+
+	fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		self.array.push(value.serialize(Serializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<JsValue, Self::Error> {
+		Ok(variant_object(self.name, JsValue::Array(self.array)))
+	}
+}
+
+impl ser::SerializeMap for SerializeMap {
+	type Error = JsConversionError;
+	type Ok = JsValue;
+
+	fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		// Object keys are always strings in JavaScript, so numbers and booleans
+		// are converted into one as well.
+		self.next_key = Some(match key.serialize(Serializer)? {
+			JsValue::String(s) => s,
+			JsValue::Boolean(b) => b.to_string(),
+			JsValue::Number(n) =>
+				if let Some(i) = integer_i64(&n) {
+					i.to_string()
+				} else if let Some(u) = integer_u64(&n) {
+					u.to_string()
+				} else {
+					n.to_f64().to_string()
+				},
+			other =>
+				return Err(JsConversionError::new(format!(
+					"unable to use {} as an object key",
+					other
+				))),
+		});
+		Ok(())
+	}
+
+	fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		let key = self
+			.next_key
+			.take()
+			.expect("serialize_value called before serialize_key");
+		self.map.insert(key, value.serialize(Serializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<JsValue, Self::Error> { Ok(JsValue::Object(self.map)) }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+	type Error = JsConversionError;
+	type Ok = JsValue;
+
+	fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		self.map
+			.insert(key.to_owned(), value.serialize(Serializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<JsValue, Self::Error> { Ok(JsValue::Object(self.map)) }
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+	type Error = JsConversionError;
+	type Ok = JsValue;
+
+	fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+	where
+		T: Serialize + ?Sized,
+	{
+		self.map
+			.insert(key.to_owned(), value.serialize(Serializer)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<JsValue, Self::Error> {
+		Ok(variant_object(self.name, JsValue::Object(self.map)))
+	}
+}
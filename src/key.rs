@@ -0,0 +1,163 @@
+//! This module contains the types that describe the keys that are pressed in a
+//! browser window.
+//!
+//! Keys are identified by their Windows virtual-key code, on every platform.
+//! That is the code that CEF uses, and it doesn't depend on the modifiers that
+//! are held down: `A` is always `0x41`, whether or not shift is pressed.
+//! The codes of letters and digits are the same as their (uppercase) ASCII
+//! codes. The codes of some of the other keys are defined in this module.
+//!
+//! Shortcuts can be registered with
+//! [`BrowserWindow::add_shortcut`](crate::browser::BrowserWindow::add_shortcut),
+//! by a string like `"Ctrl+Shift+P"`.
+
+use std::{error::Error as StdError, fmt, str::FromStr};
+
+pub const BACKSPACE: u32 = 0x08;
+pub const TAB: u32 = 0x09;
+pub const ENTER: u32 = 0x0D;
+pub const ESCAPE: u32 = 0x1B;
+pub const SPACE: u32 = 0x20;
+pub const PAGE_UP: u32 = 0x21;
+pub const PAGE_DOWN: u32 = 0x22;
+pub const END: u32 = 0x23;
+pub const HOME: u32 = 0x24;
+pub const LEFT: u32 = 0x25;
+pub const UP: u32 = 0x26;
+pub const RIGHT: u32 = 0x27;
+pub const DOWN: u32 = 0x28;
+pub const INSERT: u32 = 0x2D;
+pub const DELETE: u32 = 0x2E;
+/// The code of F1. The codes of F2 up to F24 follow it.
+pub const F1: u32 = 0x70;
+/// The `=`/`+` key.
+pub const PLUS: u32 = 0xBB;
+pub const COMMA: u32 = 0xBC;
+pub const MINUS: u32 = 0xBD;
+pub const PERIOD: u32 = 0xBE;
+
+/// The modifier keys that are held down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+	pub ctrl: bool,
+	pub shift: bool,
+	pub alt: bool,
+	/// The command key on macOS, or the Windows/super key elsewhere.
+	pub meta: bool,
+}
+
+/// A key combined with modifiers, like `Ctrl+Shift+P`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+	pub key_code: u32,
+	pub modifiers: Modifiers,
+}
+
+/// The error that is returned when a string isn't a valid shortcut.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseShortcutError(String);
+
+impl Shortcut {
+	/// Whether or not the key with the given modifiers, and no others, is this
+	/// shortcut.
+	pub fn matches(&self, key_code: u32, modifiers: Modifiers) -> bool {
+		self.key_code == key_code && self.modifiers == modifiers
+	}
+}
+
+/// Parses the modifiers and the key, separated by `+`. The names are case
+/// insensitive.
+/// The modifiers are `Ctrl` (or `Control`), `Shift`, `Alt` and `Meta` (or
+/// `Super`, `Cmd` or `Command`).
+/// The key is a letter, a digit, `F1` up to `F24`, or one of `Enter`,
+/// `Escape`, `Tab`, `Space`, `Backspace`, `Delete`, `Insert`, `Home`, `End`,
+/// `PageUp`, `PageDown`, `Left`, `Up`, `Right`, `Down`, `Plus`, `Minus`,
+/// `Comma` and `Period`.
+impl FromStr for Shortcut {
+	type Err = ParseShortcutError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let error = || ParseShortcutError(s.to_owned());
+
+		let mut parts: Vec<&str> = s.split('+').map(|p| p.trim()).collect();
+		// A trailing `+` is the plus key itself.
+		if parts.len() > 1 && parts[parts.len() - 1].is_empty() && parts[parts.len() - 2].is_empty()
+		{
+			parts.pop();
+			*parts.last_mut().unwrap() = "plus";
+		}
+		let key = parts.pop().ok_or_else(error)?;
+
+		let mut modifiers = Modifiers::default();
+		for part in parts {
+			let modifier = match part.to_lowercase().as_str() {
+				"ctrl" | "control" => &mut modifiers.ctrl,
+				"shift" => &mut modifiers.shift,
+				"alt" => &mut modifiers.alt,
+				"meta" | "super" | "cmd" | "command" => &mut modifiers.meta,
+				_ => return Err(error()),
+			};
+			*modifier = true;
+		}
+
+		Ok(Self {
+			key_code: key_code(key).ok_or_else(error)?,
+			modifiers,
+		})
+	}
+}
+
+impl fmt::Display for ParseShortcutError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "invalid shortcut: {}", self.0)
+	}
+}
+
+impl StdError for ParseShortcutError {}
+
+/// The code of the key with the given name.
+fn key_code(name: &str) -> Option<u32> {
+	let name = name.to_lowercase();
+	let mut chars = name.chars();
+	if let (Some(c), None) = (chars.next(), chars.next()) {
+		return match c {
+			'a'..='z' | '0'..='9' => Some(c.to_ascii_uppercase() as u32),
+			'=' | '+' => Some(PLUS),
+			',' => Some(COMMA),
+			'-' => Some(MINUS),
+			'.' => Some(PERIOD),
+			_ => None,
+		};
+	}
+
+	if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<u32>().ok()) {
+		return if (1..=24).contains(&n) {
+			Some(F1 + n - 1)
+		} else {
+			None
+		};
+	}
+
+	Some(match name.as_str() {
+		"backspace" => BACKSPACE,
+		"tab" => TAB,
+		"enter" | "return" => ENTER,
+		"escape" | "esc" => ESCAPE,
+		"space" => SPACE,
+		"pageup" => PAGE_UP,
+		"pagedown" => PAGE_DOWN,
+		"end" => END,
+		"home" => HOME,
+		"left" | "arrowleft" => LEFT,
+		"up" | "arrowup" => UP,
+		"right" | "arrowright" => RIGHT,
+		"down" | "arrowdown" => DOWN,
+		"insert" => INSERT,
+		"delete" | "del" => DELETE,
+		"plus" => PLUS,
+		"comma" => COMMA,
+		"minus" => MINUS,
+		"period" => PERIOD,
+		_ => return None,
+	})
+}
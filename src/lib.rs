@@ -70,12 +70,16 @@ mod tests;
 
 pub mod application;
 pub mod browser;
+pub mod certificate;
 pub mod cookie;
+pub mod dialog;
 pub mod error;
 pub mod event;
 pub mod javascript;
+pub mod key;
 pub mod prelude;
 pub(crate) mod rc;
+pub mod scheme;
 pub mod window;
 
 #[cfg(feature = "threadsafe")]
@@ -6,3 +6,14 @@ pub(crate) type Weak<T> = std::sync::Weak<T>;
 pub(crate) type Rc<T> = std::rc::Rc<T>;
 #[cfg(feature = "threadsafe")]
 pub(crate) type Rc<T> = std::sync::Arc<T>;
+
+/// `Send` if the `threadsafe` feature is enabled, and implemented by anything
+/// otherwise. It is for bounds that only need `Send` in that case.
+#[cfg(not(feature = "threadsafe"))]
+pub(crate) trait MaybeSend {}
+#[cfg(not(feature = "threadsafe"))]
+impl<T> MaybeSend for T {}
+#[cfg(feature = "threadsafe")]
+pub(crate) trait MaybeSend: Send {}
+#[cfg(feature = "threadsafe")]
+impl<T: Send> MaybeSend for T {}
@@ -0,0 +1,114 @@
+//! This module contains the types needed to serve custom URL schemes from Rust.
+//!
+//! A custom scheme, like `app://`, lets you serve your user interface without
+//! having to run a local web server. Every request made to the scheme will be
+//! answered by the handler you provide. Custom schemes need to be registered
+//! before the application is initialized:
+//!
+//! ```no_run
+//! use browser_window::{application::*, scheme::*};
+//!
+//! let mut settings = ApplicationSettings::default();
+//! settings
+//! 	.schemes
+//! 	.push(Scheme::new("app", |request: &SchemeRequest| {
+//! 		if request.url.starts_with("app://ui/index.html") {
+//! 			SchemeResponse::new(200, "text/html", "<h1>Hello!</h1>")
+//! 		} else {
+//! 			SchemeResponse::new(404, "text/plain", "Not found")
+//! 		}
+//! 	}));
+//! let app = Application::initialize(&settings).unwrap();
+//! ```
+//!
+//! Handlers are always invoked on the GUI thread.
+//! Custom schemes are supported for CEF and WebkitGTK.
+
+use crate::rc::Rc;
+
+#[cfg(not(feature = "threadsafe"))]
+pub type SchemeHandlerCallback = dyn Fn(&SchemeRequest) -> SchemeResponse + 'static;
+#[cfg(feature = "threadsafe")]
+pub type SchemeHandlerCallback = dyn Fn(&SchemeRequest) -> SchemeResponse + Send + Sync + 'static;
+
+/// A custom scheme together with the handler that serves its requests.
+#[derive(Clone)]
+pub struct Scheme {
+	pub(crate) name: String,
+	pub(crate) handler: Rc<SchemeHandlerCallback>,
+}
+
+/// A request that has been made to a custom scheme.
+#[derive(Clone, Debug)]
+pub struct SchemeRequest {
+	pub url: String,
+	pub method: String,
+	pub headers: Vec<(String, String)>,
+	pub body: Vec<u8>,
+}
+
+/// The response to give back to the browser.
+#[derive(Clone, Debug)]
+pub struct SchemeResponse {
+	pub status: u16,
+	pub headers: Vec<(String, String)>,
+	pub body: Vec<u8>,
+}
+
+impl Scheme {
+	/// Creates a custom scheme with the given name, without the `://` part.
+	#[cfg(not(feature = "threadsafe"))]
+	pub fn new<H>(name: &str, handler: H) -> Self
+	where
+		H: Fn(&SchemeRequest) -> SchemeResponse + 'static,
+	{
+		Self {
+			name: name.to_owned(),
+			handler: Rc::new(handler),
+		}
+	}
+
+	/// Creates a custom scheme with the given name, without the `://` part.
+	#[cfg(feature = "threadsafe")]
+	pub fn new<H>(name: &str, handler: H) -> Self
+	where
+		H: Fn(&SchemeRequest) -> SchemeResponse + Send + Sync + 'static,
+	{
+		Self {
+			name: name.to_owned(),
+			handler: Rc::new(handler),
+		}
+	}
+
+	pub fn name(&self) -> &str { &self.name }
+}
+
+impl SchemeRequest {
+	/// Returns the value of the first header with the given name, if any.
+	/// The name is matched case-insensitively.
+	pub fn header(&self, name: &str) -> Option<&str> { find_header(&self.headers, name) }
+}
+
+impl SchemeResponse {
+	/// Creates a response with the `Content-Type` header already set.
+	pub fn new(status: u16, mime_type: &str, body: impl Into<Vec<u8>>) -> Self {
+		Self {
+			status,
+			headers: vec![("Content-Type".to_owned(), mime_type.to_owned())],
+			body: body.into(),
+		}
+	}
+
+	/// Returns the value of the first header with the given name, if any.
+	/// The name is matched case-insensitively.
+	pub fn header(&self, name: &str) -> Option<&str> { find_header(&self.headers, name) }
+}
+
+/// Returns the value of the first header with the given name, if any.
+/// The name is matched case-insensitively.
+pub(crate) fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+	headers
+		.iter()
+		.find(|(n, _)| n.eq_ignore_ascii_case(name))
+		.map(|(_, v)| v.as_str())
+}
@@ -3,21 +3,232 @@
 mod builder;
 
 pub use std::ffi::c_void;
+use std::{
+	any::Any,
+	cell::RefCell,
+	mem::ManuallyDrop,
+	ops::Deref,
+	path::PathBuf,
+	sync::{
+		Arc,
+		atomic::{AtomicBool, Ordering},
+	},
+};
 
 pub use builder::WindowBuilder;
+#[cfg(feature = "threadsafe")]
+use unsafe_send_sync::UnsafeSend;
 
 pub use super::core::window::WindowExt;
-use super::prelude::*;
+use super::{
+	HasHandle,
+	application::ApplicationHandle,
+	core::window::{WindowEventExt, WindowUserData},
+	decl_event, decl_window_event,
+	dialog::{self, FileDialogError, FileDialogMode, FileDialogOptions},
+	event::{EventHandler, EventHandlers, SharedEventHandlers},
+	prelude::*,
+	rc::{Rc, Weak},
+};
 
 /// A handle that exposes all windowing functionality.
 pub struct WindowHandle(pub(super) WindowImpl);
 
+/// A plain window without a browser in it, as created by
+/// [`WindowBuilder::build`](struct.WindowBuilder.html#method.build).
+///
+/// Like with a `BrowserWindow`, the window stays alive until it is closed,
+/// even if there are no more handles to it. Its resources are freed once it
+/// has been closed and all `Window` handles have been dropped.
+#[derive(Clone)]
+pub struct Window(pub(super) Rc<WindowOwner>);
+
+/// The owner of a window. It is kept alive by the `Window` handles, and by the
+/// window itself until it is closed.
+/// The owner of a browser window keeps the owner of its window alive.
+/// Its fields are only dropped on the GUI thread, see `Drop`.
+pub struct WindowOwner(
+	pub(super) ManuallyDrop<WindowHandle>,
+	/// The handler lists of the events. The backends only keep weak references
+	/// to them.
+	ManuallyDrop<RefCell<Vec<Rc<dyn Any>>>>,
+	/// Obtained up front, because asking the window for it is only possible on
+	/// the GUI thread, and the owner may be dropped on any thread.
+	ManuallyDrop<ApplicationHandle>,
+);
+
+pub type WindowEventHandler<A> = EventHandler<WindowHandle, Window, A>;
+
+/// The argument of `on_close_requested`.
+/// All handlers of the same request share the same decision, so a handler can
+/// see whether one of the handlers before it has prevented the close.
+#[derive(Clone)]
+pub struct CloseRequestedEventArgs {
+	prevented: Arc<AtomicBool>,
+}
+
+decl_window_event!(CloseRequestedEvent);
+decl_window_event!(FocusChangedEvent);
+decl_window_event!(MaximizedChangedEvent);
+decl_window_event!(MinimizedChangedEvent);
+decl_window_event!(MovedEvent);
+decl_window_event!(ResizedEvent);
+decl_window_event!(VisibilityChangedEvent);
+
+impl Window {
+	pub(super) fn new(inner: WindowImpl) -> Self {
+		// Put a reference counted handle in the user data of the window, so that there
+		// exists 'ownership' for as long as the window actually lives.
+		let this = Self::new_unattached(inner);
+		let user_data = Box::into_raw(Box::new(WindowUserData {
+			_owner: this.0.clone(),
+		}));
+		this.0.0.0.set_user_data(user_data as _);
+
+		this
+	}
+
+	/// Creates the owner of a window without attaching it to the window.
+	/// Used for the window of a browser window, which is attached to the
+	/// browser window's owner instead.
+	pub(crate) fn new_unattached(inner: WindowImpl) -> Self {
+		let app = ApplicationHandle::new(inner.app());
+		Self(Rc::new(WindowOwner(
+			ManuallyDrop::new(WindowHandle::new(inner)),
+			ManuallyDrop::new(RefCell::new(Vec::new())),
+			ManuallyDrop::new(app),
+		)))
+	}
+
+	/// Whenever the user tries to close the window, e.g. by clicking on its
+	/// close button.
+	/// Any handler can keep the window open by calling
+	/// [`prevent`](struct.CloseRequestedEventArgs.html#method.prevent), which
+	/// is useful to ask the user to save their changes first.
+	/// Only the handlers that are registered with `register` can do this, async
+	/// handlers only get to run after the decision has been made.
+	/// Not fired when the window is closed by the program itself, which can't
+	/// be prevented.
+	pub fn on_close_requested(&self) -> CloseRequestedEvent {
+		self.0.0.0.on_close_requested(Rc::downgrade(&self.0))
+	}
+
+	/// Whenever the window gains (`true`) or loses (`false`) the keyboard
+	/// focus.
+	pub fn on_focus_changed(&self) -> FocusChangedEvent {
+		self.0.0.0.on_focus_changed(Rc::downgrade(&self.0))
+	}
+
+	/// Whenever the window gets maximized (`true`) or unmaximized (`false`).
+	pub fn on_maximized_changed(&self) -> MaximizedChangedEvent {
+		self.0.0.0.on_maximized_changed(Rc::downgrade(&self.0))
+	}
+
+	/// Whenever the window gets minimized (`true`) or restored (`false`).
+	pub fn on_minimized_changed(&self) -> MinimizedChangedEvent {
+		self.0.0.0.on_minimized_changed(Rc::downgrade(&self.0))
+	}
+
+	/// Whenever the window has moved, with its new position.
+	pub fn on_moved(&self) -> MovedEvent { self.0.0.0.on_moved(Rc::downgrade(&self.0)) }
+
+	/// Whenever the window has been resized, with its new window dimensions.
+	pub fn on_resized(&self) -> ResizedEvent { self.0.0.0.on_resized(Rc::downgrade(&self.0)) }
+
+	/// Whenever the window gets shown (`true`) or hidden (`false`).
+	pub fn on_visibility_changed(&self) -> VisibilityChangedEvent {
+		self.0.0.0.on_visibility_changed(Rc::downgrade(&self.0))
+	}
+}
+
+impl Deref for Window {
+	type Target = WindowHandle;
+
+	fn deref(&self) -> &Self::Target { &self.0.0 }
+}
+
+impl HasHandle<WindowHandle> for Window {
+	fn handle(&self) -> &WindowHandle { &self.0.0 }
+}
+
+impl WindowOwner {
+	/// Creates an empty list of handlers for an event, that lives as long as
+	/// this owner does.
+	pub(crate) fn new_event_handlers<A>(&self) -> SharedEventHandlers<WindowHandle, Window, A>
+	where
+		A: 'static,
+	{
+		let handlers = Rc::new(EventHandlers::new());
+		self.1.borrow_mut().push(handlers.clone());
+		handlers
+	}
+
+	/// Invokes the handlers of an event, if the window still exists.
+	pub(crate) fn fire_event<A>(
+		owner: &Weak<Self>, handlers: &SharedEventHandlers<WindowHandle, Window, A>, arg: A,
+	) where
+		A: Clone,
+	{
+		let this = match owner.upgrade() {
+			Some(this) => this,
+			None => return,
+		};
+		handlers.fire(&this.0, &|| Window(this.clone()), &this.2, arg);
+	}
+}
+
+impl Drop for WindowOwner {
+	fn drop(&mut self) {
+		// The fields hold on to objects of the GUI framework, so they are moved to the
+		// GUI thread to be dropped there.
+		let (window, handlers, app) = unsafe {
+			(
+				ManuallyDrop::take(&mut self.0),
+				ManuallyDrop::take(&mut self.1),
+				ManuallyDrop::take(&mut self.2),
+			)
+		};
+		#[cfg(not(feature = "threadsafe"))]
+		{
+			window.0.free();
+			drop((handlers, window, app));
+		}
+		#[cfg(feature = "threadsafe")]
+		{
+			let threaded = unsafe { app.clone() }.into_threaded();
+			let fields = UnsafeSend::new((window, handlers, app));
+			threaded.dispatch(move |_| {
+				let (window, handlers, app) = fields.unwrap();
+				window.0.free();
+				drop((handlers, window, app));
+			});
+		}
+	}
+}
+
+impl CloseRequestedEventArgs {
+	pub(crate) fn new() -> Self {
+		Self {
+			prevented: Arc::new(AtomicBool::new(false)),
+		}
+	}
+
+	/// Keeps the window open.
+	pub fn prevent(&self) { self.prevented.store(true, Ordering::Relaxed); }
+
+	/// Whether a handler has prevented the window from closing.
+	pub fn is_prevented(&self) -> bool { self.prevented.load(Ordering::Relaxed) }
+}
+
 impl WindowHandle {
 	#[cfg(feature = "threadsafe")]
 	pub(crate) unsafe fn clone(&self) -> Self { Self(self.0.clone()) }
 
 	pub(super) fn new(inner: WindowImpl) -> Self { Self(inner) }
 
+	/// Returns the application handle associated with this window.
+	pub fn app(&self) -> ApplicationHandle { ApplicationHandle::new(self.0.app()) }
+
 	pub fn content_dimensions(&self) -> Dims2D { self.0.content_dimensions() }
 
 	#[cfg(feature = "gtk")]
@@ -31,6 +242,9 @@ impl WindowHandle {
 
 	pub fn window_dimensions(&self) -> Dims2D { self.0.window_dimensions() }
 
+	/// Brings the window to the front and gives it the keyboard focus.
+	pub fn focus(&self) { self.0.focus(); }
+
 	/// Hides the window.
 	/// Keep in mind that hiding the window is not the same as closing it.
 	/// Hiding the window will keep it's resources alive.
@@ -40,16 +254,71 @@ impl WindowHandle {
 
 	pub fn impl_handle(&self) -> &WindowImpl { &self.0 }
 
+	/// Shows a dialog on top of the window to open files with, and resolves to
+	/// the chosen files. If the dialog is cancelled, there are none.
+	/// Implemented for CEF and GTK. CEF can only show dialogs for browsers, so
+	/// for other windows it uses any browser window that there is, and fails
+	/// if there is none.
+	pub async fn open_file(
+		&self, options: &FileDialogOptions,
+	) -> Result<Vec<PathBuf>, FileDialogError> {
+		let mode = if options.multiple {
+			FileDialogMode::OpenMultiple
+		} else {
+			FileDialogMode::Open
+		};
+		dialog::show(Some(&self.0), mode, options).await
+	}
+
+	/// Like `open_file`, but for choosing a folder.
+	pub async fn pick_folder(
+		&self, options: &FileDialogOptions,
+	) -> Result<Option<PathBuf>, FileDialogError> {
+		let paths = dialog::show(Some(&self.0), FileDialogMode::OpenFolder, options).await?;
+		Ok(paths.into_iter().next())
+	}
+
+	/// Like `open_file`, but for choosing where to save a file.
+	pub async fn save_file(
+		&self, options: &FileDialogOptions,
+	) -> Result<Option<PathBuf>, FileDialogError> {
+		let paths = dialog::show(Some(&self.0), FileDialogMode::Save, options).await?;
+		Ok(paths.into_iter().next())
+	}
+
 	pub unsafe fn inner_handle(&self) -> *mut c_void { self.impl_handle().inner_handle() }
 
+	/// Whether the window covers the whole screen.
+	pub fn is_fullscreen(&self) -> bool { self.0.is_fullscreen() }
+
+	/// Whether the window is shown, see `show` and `hide`.
+	pub fn is_visible(&self) -> bool { self.0.is_visible() }
+
+	pub fn maximize(&self) { self.0.maximize(); }
+
+	pub fn minimize(&self) { self.0.minimize(); }
+
+	/// Restores the window to its normal size, if it was minimized or
+	/// maximized.
+	pub fn restore(&self) { self.0.restore(); }
+
 	pub fn set_content_dimensions(&self, dimensions: Dims2D) {
 		self.0.set_content_dimensions(dimensions);
 	}
 
+	/// Makes the window cover the whole screen, or restores it from that.
+	pub fn set_fullscreen(&self, fullscreen: bool) { self.0.set_fullscreen(fullscreen); }
+
+	/// Whether the window should stay on top of other windows.
+	pub fn set_keep_above(&self, keep_above: bool) { self.0.set_keep_above(keep_above); }
+
 	pub fn set_opacity(&self, opacity: u8) { self.0.set_opacity(opacity); }
 
 	pub fn set_position(&self, position: Pos2D) { self.0.set_position(position); }
 
+	/// Whether the user is able to resize the window.
+	pub fn set_resizable(&self, resizable: bool) { self.0.set_resizable(resizable); }
+
 	pub fn set_title(&self, title: &str) { self.0.set_title(title); }
 
 	pub fn set_window_dimensions(&self, dimensions: Dims2D) {
@@ -1,17 +1,29 @@
 use unsafe_send_sync::UnsafeSend;
 
-use crate::{HasHandle, application::*, core::prelude::*, window::*};
+use crate::{
+	HasHandle,
+	application::*,
+	core::{prelude::*, window::WindowOptions},
+	window::*,
+};
 
 /// Exposes functionality related to constructing a window.
 pub struct WindowBuilder {
 	pub(crate) borders: bool,
+	pub(crate) center: bool,
 	pub(crate) decorated: bool,
+	pub(crate) fullscreen: bool,
 	pub(crate) height: Option<u32>,
 	pub(crate) keep_above: bool,
+	pub(crate) max_size: Option<Dims2D>,
+	pub(crate) maximized: bool,
+	pub(crate) min_size: Option<Dims2D>,
 	pub(crate) minimizable: bool,
 	pub(crate) parent: Option<UnsafeSend<WindowImpl>>,
+	pub(crate) position: Option<(i32, i32)>,
 	pub(crate) resizable: bool,
 	pub(crate) title: Option<String>,
+	pub(crate) visible: bool,
 	pub(crate) width: Option<u32>,
 }
 
@@ -20,25 +32,26 @@ impl WindowBuilder {
 	/// Default is true.
 	pub fn borders(&mut self, value: bool) { self.borders = value; }
 
+	/// Places the window in the center of its parent, or in the center of the
+	/// screen if it has no parent.
+	/// Takes precedence over `position`.
+	/// On GTK, the window is also made transient for its parent if that is
+	/// visible, because GTK only centers windows on the window that they are
+	/// transient for. This keeps the window on top of its parent.
+	pub fn center(&mut self) { self.center = true; }
+
 	pub fn decorated(&mut self, value: bool) { self.decorated = value; }
 
-	// TODO: Create a Window struct that can be created with this method.
-	#[allow(dead_code)]
-	fn build(self, app: ApplicationHandle) {
+	/// Creates a window without a browser in it.
+	/// Like all windows, it is hidden until `show` is called on it.
+	pub fn build(self, app: &ApplicationHandle) -> Window {
 		// Title
 		let title: &str = match self.title.as_ref() {
 			None => "",
 			Some(t) => t,
 		};
 
-		// Convert options to the FFI struct
-		let window_options = cbw_WindowOptions {
-			borders: self.borders,
-			decorated: self.decorated,
-			keep_above: self.keep_above,
-			minimizable: self.minimizable,
-			resizable: self.resizable,
-		};
+		let window_options = self.window_options();
 
 		// Unwrap the parent ffi handle
 		let parent_impl_handle = match self.parent {
@@ -46,22 +59,44 @@ impl WindowBuilder {
 			Some(parent) => (*parent).clone(),
 		};
 
-		let _impl_handle = WindowImpl::new(
-			app.inner,
+		let inner = WindowImpl::new(
+			app.inner.clone(),
 			parent_impl_handle,
-			title.into(),
-			self.width as _,
-			self.height as _,
+			title,
+			self.width,
+			self.height,
 			&window_options,
 		);
+		Window::new(inner)
 	}
 
+	/// Sets whether or not the window covers the whole screen once it is
+	/// shown.
+	/// Default is false.
+	pub fn fullscreen(&mut self, value: bool) { self.fullscreen = value; }
+
 	/// Sets the height that the browser window will be created with initially
 	pub fn height(&mut self, height: u32) { self.height = Some(height); }
 
 	/// If set to true, keeps the window above all other windows
 	pub fn keep_above(&mut self, value: bool) { self.keep_above = value; }
 
+	/// Sets the size that the user can't make the window any larger than.
+	/// A width or height of 0 leaves that dimension unconstrained.
+	pub fn max_size(&mut self, width: u16, height: u16) {
+		self.max_size = Some(Dims2D { width, height });
+	}
+
+	/// Sets whether or not the window is maximized once it is shown.
+	/// Default is false.
+	pub fn maximized(&mut self, value: bool) { self.maximized = value; }
+
+	/// Sets the size that the user can't make the window any smaller than.
+	/// A width or height of 0 leaves that dimension unconstrained.
+	pub fn min_size(&mut self, width: u16, height: u16) {
+		self.min_size = Some(Dims2D { width, height });
+	}
+
 	/// Sets whether or not the window has a minimize button on the title bar
 	/// Default is true
 	pub fn minimizable(&mut self, value: bool) { self.minimizable = value; }
@@ -79,17 +114,28 @@ impl WindowBuilder {
 	pub fn new() -> Self {
 		Self {
 			borders: true,
+			center: false,
 			decorated: true,
+			fullscreen: false,
 			height: None,
 			keep_above: false,
+			max_size: None,
+			maximized: false,
+			min_size: None,
 			minimizable: true,
 			parent: None,
+			position: None,
 			resizable: true,
 			title: None,
+			visible: false,
 			width: None,
 		}
 	}
 
+	/// Sets the position that the window will be created at, relative to the
+	/// screen. With multiple monitors, it can be negative.
+	pub fn position(&mut self, x: i32, y: i32) { self.position = Some((x, y)); }
+
 	/// Sets the width and height of the browser window
 	pub fn size(&mut self, width: u32, height: u32) {
 		self.width = Some(width);
@@ -99,9 +145,39 @@ impl WindowBuilder {
 	/// Sets the title of the window.
 	pub fn title<S: Into<String>>(&mut self, title: S) { self.title = Some(title.into()); }
 
+	/// Sets whether or not the window is shown right after it has been
+	/// created, so that `show` doesn't need to be called on it.
+	/// Default is false.
+	pub fn visible(&mut self, value: bool) { self.visible = value; }
+
 	/// Sets the width that the browser window will be created with initially.
 	pub fn width(&mut self, width: u32) { self.width = Some(width); }
 
+	/// Converts the options to the FFI struct.
+	pub(crate) fn window_options(&self) -> WindowOptions {
+		let unconstrained = Dims2D {
+			width: 0,
+			height: 0,
+		};
+		let (x, y) = self.position.unwrap_or((0, 0));
+		WindowOptions {
+			borders: self.borders,
+			decorated: self.decorated,
+			keep_above: self.keep_above,
+			minimizable: self.minimizable,
+			resizable: self.resizable,
+			center: self.center,
+			fullscreen: self.fullscreen,
+			has_position: self.position.is_some(),
+			maximized: self.maximized,
+			visible: self.visible,
+			x,
+			y,
+			min_size: self.min_size.unwrap_or(unconstrained),
+			max_size: self.max_size.unwrap_or(unconstrained),
+		}
+	}
+
 	/// Sets whether or not the window will be resizable.
 	/// Default is true.
 	pub fn resizable(&mut self, resizable: bool) { self.resizable = resizable; }